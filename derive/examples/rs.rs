@@ -0,0 +1,6 @@
+use seedle_derive::seedle;
+
+#[seedle(file = "examples/test.cddl", language = "rust")]
+pub mod cddl {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use seedle_derive::seedle;
+
+#[seedle(file = "examples/unbounded.cddl", language = "rust")]
+pub mod cddl {}
+
+fn main() {}
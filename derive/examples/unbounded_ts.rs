@@ -0,0 +1,6 @@
+use seedle_derive::seedle;
+
+#[seedle(file = "examples/unbounded.cddl", language = "typescript")]
+pub mod cddl {}
+
+fn main() {}
@@ -1,3 +1,5 @@
+use super::choices::Choice;
+use super::enumerations::Enumeration;
 use super::literals::LitToks;
 use super::vtable::VTable;
 use crate::parse::Attributes;
@@ -6,12 +8,12 @@ use crate::parse::Mod;
 use crate::print::structs::Struct;
 use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
-use seedle_parser::Fields;
+use seedle_parser::{Fields, LinkedNode, Sources, Variants};
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use quote::quote;
-use std::fs;
 use syn::Error as SynError;
 
 pub fn build(s: Mod, attrs: Attributes) -> syn::Result<TokenStream> {
@@ -36,11 +38,47 @@ pub fn build(s: Mod, attrs: Attributes) -> syn::Result<TokenStream> {
         }
     };
 
-    // Parse the users type definitions as CDDL file
-    let cddl = fs::read(path)
-        .map_err(|e| into_syn_error(file.span(), e))
-        .and_then(|bytes| String::from_utf8(bytes).map_err(|e| into_syn_error(file.span(), e)))?;
-    let ctx = seedle_parser::parse(&cddl).map_err(|e| into_syn_error(file.span(), e))?;
+    // Parse the users type definitions as CDDL file, merged with the files it includes
+    let sources = Sources::read(path).map_err(|e| into_syn_error(file.span(), e))?;
+    let (ctx, diagnostics) = sources.parse_diagnostics();
+    let (keys, pin_error) = match sources.pinned_keys() {
+        Ok(keys) => (keys, None),
+        Err(e) => (BTreeMap::new(), Some(e)),
+    };
+
+    // Members that hold their own struct are held through a Box, which C does not have
+    let recursive = seedle_parser::recursive_members(&ctx);
+    let recursive_errors = recursive
+        .iter()
+        .filter(|_| matches!(language, Language::C))
+        .flat_map(|(name, members)| members.iter().map(move |member| (name, member)))
+        .map(|(name, member)| {
+            let msg = format!(
+                "struct [{}] holds itself through member [{}], which the C language can not hold inline",
+                name, member
+            );
+            SynError::new(file.span(), msg)
+        });
+
+    // Report every error in the schema at once, each as its own compile_error!
+    let errors = diagnostics
+        .errors
+        .into_iter()
+        .chain(pin_error)
+        .map(|e| into_syn_error(file.span(), e))
+        .chain(recursive_errors);
+    if let Some(error) = errors.reduce(|mut all, e| {
+        all.combine(e);
+        all
+    }) {
+        return Err(error);
+    }
+
+    // Warnings do not stop the build
+    let warnings = diagnostics
+        .warnings
+        .into_iter()
+        .map(|e| into_warning(file.span(), e));
 
     // Get the prelude for the module
     let prelude = match language {
@@ -49,6 +87,9 @@ pub fn build(s: Mod, attrs: Attributes) -> syn::Result<TokenStream> {
         _ => quote! {},
     };
 
+    // Types that own memory on the heap can not be Copy
+    let unbounded = seedle_parser::unbounded_types(&ctx);
+
     let struct_nodes: Vec<(_, Cow<'_, Fields>)> = ctx
         .iter()
         .filter_map(seedle_parser::structs_borrowed)
@@ -58,6 +99,7 @@ pub fn build(s: Mod, attrs: Attributes) -> syn::Result<TokenStream> {
         Language::Typescript | Language::C => Some(VTable {
             ident: &ident,
             structs: struct_nodes.clone(),
+            keys: &keys,
             language,
             prefix: &prefix,
         }),
@@ -71,6 +113,33 @@ pub fn build(s: Mod, attrs: Attributes) -> syn::Result<TokenStream> {
             prefix: prefix.as_ref(),
             fields,
             language,
+            copy: !unbounded.contains(name),
+            record: matches!(ctx.get(name), Some(LinkedNode::Record(_))),
+            rules: &ctx,
+            boxed: recursive.get(name).cloned().unwrap_or_default(),
+        })
+        .collect();
+
+    let choices: Vec<Choice> = ctx
+        .iter()
+        .filter_map(seedle_parser::choices_borrowed)
+        .map(|(name, variants): (_, Cow<'_, Variants>)| Choice {
+            name,
+            prefix: prefix.as_ref(),
+            variants,
+            language,
+            copy: !unbounded.contains(name),
+        })
+        .collect();
+
+    let enumerations: Vec<Enumeration> = ctx
+        .iter()
+        .filter_map(seedle_parser::enumerations_borrowed)
+        .map(|(name, members)| Enumeration {
+            name,
+            prefix: prefix.as_ref(),
+            members,
+            language,
         })
         .collect();
 
@@ -81,6 +150,7 @@ pub fn build(s: Mod, attrs: Attributes) -> syn::Result<TokenStream> {
         .map(|(name, lit)| {
             LitToks {
                 name,
+                prefix: prefix.as_ref(),
                 lit: lit.as_ref(),
                 language,
             }
@@ -92,9 +162,12 @@ pub fn build(s: Mod, attrs: Attributes) -> syn::Result<TokenStream> {
         #(#outer_attrs)*
         pub mod #ident {
             #prelude
+            #(#warnings)*
             #vtable
             #(#literals)*
+            #(#enumerations)*
             #(#structs)*
+            #(#choices)*
         }
     })
 }
@@ -102,3 +175,17 @@ pub fn build(s: Mod, attrs: Attributes) -> syn::Result<TokenStream> {
 fn into_syn_error<E: std::error::Error>(span: Span, e: E) -> SynError {
     SynError::new(span, e.to_string())
 }
+
+/// A macro can not raise a warning on stable rust, so a warning is raised as the use of a
+/// deprecated item whose note is the warning, at the span of the file attribute
+fn into_warning<E: std::error::Error>(span: Span, e: E) -> TokenStream {
+    let note = e.to_string();
+    let warning = quote::quote_spanned! {span=> let _ = SeedleWarning;};
+    quote! {
+        const _: () = {
+            #[deprecated(note = #note)]
+            struct SeedleWarning;
+            #warning
+        };
+    }
+}
@@ -0,0 +1,392 @@
+use super::structs::{
+    is_half, AttrTokens, CApiImpl, DefaultTokens, RegexpImpl, Totality, TypeTokens,
+};
+use crate::parse::Language;
+use heck::*;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use seedle_parser::*;
+use std::borrow::Cow;
+use syn::LitStr;
+
+pub struct Choice<'a> {
+    pub name: &'a str,
+    pub prefix: Option<&'a LitStr>,
+    pub variants: Cow<'a, Variants>,
+    pub language: Language,
+    /// False when a variant holds an unbounded tstr or bstr
+    pub copy: bool,
+}
+impl<'a> Choice<'a> {
+    fn render(&self) -> TokenStream {
+        match self.language {
+            Language::C => self.render_c(),
+            Language::Rust => self.render_rust(),
+            Language::Typescript => self.render_typescript(),
+        }
+    }
+
+    fn render_c(&self) -> TokenStream {
+        let language = self.language;
+        let enum_ident = self.ident();
+        let variants = self.variants();
+        let copy = self.copy.then(|| quote! {Copy,});
+        let default_impl = DefaultVariantImpl {
+            ident: &enum_ident,
+            variants: &self.variants,
+            language,
+        };
+        let ffi_impl = CApiImpl {
+            ident: &enum_ident,
+            language,
+        };
+        let codec_impl = CodecImpl {
+            ident: &enum_ident,
+            variants: &self.variants,
+            language,
+        };
+        quote! {
+            #[repr(C, u8)]
+            #[allow(non_camel_case_types)]
+            #[derive(#copy Clone)]
+            #[cfg_attr(feature="testing", derive(Debug, PartialEq))]
+            pub enum #enum_ident {
+                #(#variants),*
+            }
+            #default_impl
+            #ffi_impl
+            #codec_impl
+        }
+    }
+
+    fn render_rust(&self) -> TokenStream {
+        let language = self.language;
+        let enum_ident = self.ident();
+        let variants = self.variants();
+        let copy = self.copy.then(|| quote! {Copy,});
+        let default_impl = DefaultVariantImpl {
+            ident: &enum_ident,
+            variants: &self.variants,
+            language,
+        };
+        let codec_impl = CodecImpl {
+            ident: &enum_ident,
+            variants: &self.variants,
+            language,
+        };
+        quote! {
+            #[derive(#copy Clone, Debug, serde::Serialize, serde::Deserialize)]
+            pub enum #enum_ident {
+                #(#variants),*
+            }
+            #default_impl
+            #codec_impl
+        }
+    }
+
+    fn render_typescript(&self) -> TokenStream {
+        // NOTE wasm_bindgen can not export enums that carry data, so we describe the serde
+        //      representation of the enum to typescript instead
+        use proc_macro2::Literal;
+        let language = self.language;
+        let enum_ident = self.ident();
+        let variants = self.variants();
+        let copy = self.copy.then(|| quote! {Copy,});
+        let serde_rename_ts = Literal::string("camelCase");
+        let default_impl = DefaultVariantImpl {
+            ident: &enum_ident,
+            variants: &self.variants,
+            language,
+        };
+        let codec_impl = CodecImpl {
+            ident: &enum_ident,
+            variants: &self.variants,
+            language,
+        };
+        let name = enum_ident.to_string();
+        let ts_append_content_ident =
+            quote::format_ident!("TS_APPEND_CONTENT_{}", name.to_shouty_snake_case());
+        let ts_append_content = Literal::string(&format!(
+            "export type {}Props = {};",
+            name,
+            self.variants
+                .members
+                .iter()
+                .map(|LinkedKeyVal { key, node, .. }| match node {
+                    // serde writes a unit variant as its name
+                    LinkedNode::Nil => format!("\"{}\"", key.to_lower_camel_case()),
+                    node => format!("{{ {}: {} }}", key.to_lower_camel_case(), ts_type(node)),
+                })
+                .collect::<Vec<String>>()
+                .join(" | ")
+        ));
+        quote! {
+            #[derive(#copy Clone, Debug, serde::Serialize, serde::Deserialize)]
+            #[serde(rename_all=#serde_rename_ts)]
+            pub enum #enum_ident {
+                #(#variants),*
+            }
+            #default_impl
+            #codec_impl
+
+            #[wasm_bindgen(typescript_custom_section)]
+            const #ts_append_content_ident: &'static str = #ts_append_content;
+        }
+    }
+
+    fn ident(&self) -> syn::Ident {
+        let prefix = self
+            .prefix
+            .map(|v| Cow::Owned(v.value()))
+            .unwrap_or(Cow::Borrowed(""));
+        let name = &format!("{}{}", prefix, self.name);
+        quote::format_ident!("{}", self.language.structify(name))
+    }
+
+    fn variants(&self) -> impl Iterator<Item = VariantTokens<'_>> {
+        let language = self.language;
+        self.variants
+            .members
+            .iter()
+            .map(move |LinkedKeyVal { key, node, .. }| VariantTokens {
+                node,
+                language,
+                key,
+            })
+    }
+}
+
+impl<'a> ToTokens for Choice<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.render().to_tokens(tokens);
+    }
+}
+
+/// A variant holding a single value, IE: Ping(Ping). A nil variant holds nothing, IE: Nil
+struct VariantTokens<'a> {
+    node: &'a LinkedNode,
+    language: Language,
+    key: &'a str,
+}
+impl<'a> ToTokens for VariantTokens<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let variant = quote::format_ident!("{}", self.language.enumify(self.key));
+        if let LinkedNode::Nil = self.node {
+            return variant.to_tokens(tokens);
+        }
+        // The variant is encoded by CodecImpl, only the serde attributes remain
+        let (_, serde) = split_attrs(self.node, self.language, self.key);
+        let ty = TypeTokens {
+            node: self.node,
+            language: self.language,
+        };
+        quote! {#variant(#(#serde)* #ty)}.to_tokens(tokens)
+    }
+}
+
+/// A type choice is discriminated, each variant is written as the position of its alternative
+/// followed by its value. IE: Pong(Pong { ack: 9 }) is [1, [9]], and a nil variant is [n, nil]
+///
+/// The codec of each alternative is that of a member, so a transparent wrapper is derived for
+/// each alternative, IE: struct DecodePing(#[n(0)] Ping). The wrappers are scoped to an
+/// anonymous const so they never collide with the types of the schema
+struct CodecImpl<'a> {
+    ident: &'a syn::Ident,
+    variants: &'a Variants,
+    language: Language,
+}
+impl<'a> ToTokens for CodecImpl<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ident = self.ident;
+        let mut wrappers = Vec::new();
+        let mut encoders = Vec::new();
+        let mut lens = Vec::new();
+        let mut decoders = Vec::new();
+        for (n, member) in self.variants.members.iter().enumerate() {
+            let LinkedKeyVal { key, node, .. } = member;
+            let variant = quote::format_ident!("{}", self.language.enumify(key));
+            let n = proc_macro2::Literal::u32_suffixed(n as u32);
+            if let LinkedNode::Nil = node {
+                encoders.push(quote! {#ident::#variant => e.array(2)?.u32(#n)?.null()?.ok()});
+                lens.push(quote! {#ident::#variant => 2 + minicbor::CborLen::cbor_len(&#n, ctx)});
+                decoders.push(quote! {
+                    #n => {
+                        d.null()?;
+                        Ok(#ident::#variant)
+                    }
+                });
+                continue;
+            }
+            let (cbor, _) = split_attrs(node, self.language, key);
+            let ty = TypeTokens {
+                node,
+                language: self.language,
+            };
+            let encoder = quote::format_ident!("Encode{}", variant);
+            let decoder = quote::format_ident!("Decode{}", variant);
+            // float16 has no codec for references, the (copy) value is encoded instead
+            let (field, value, lifetime) = match is_half(node) {
+                true => (quote! {#ty}, quote! {*value}, quote! {}),
+                false => (quote! {&'a #ty}, quote! {value}, quote! {<'a>}),
+            };
+            let regexp_impl = RegexpImpl {
+                ident: &decoder,
+                members: std::slice::from_ref(member),
+            };
+            wrappers.push(quote! {
+                #[derive(minicbor::Encode, minicbor::CborLen)]
+                #[cbor(transparent)]
+                struct #encoder #lifetime(#(#cbor)* #field);
+
+                #[derive(minicbor::Decode)]
+                #[cbor(transparent)]
+                struct #decoder(#(#cbor)* #ty);
+                #regexp_impl
+            });
+            encoders.push(quote! {
+                #ident::#variant(value) => {
+                    e.array(2)?.u32(#n)?;
+                    minicbor::Encode::encode(&#encoder(#value), e, ctx)
+                }
+            });
+            // CborLen is not transparent, the wrapper is counted as an array of one member
+            lens.push(quote! {
+                #ident::#variant(value) => {
+                    minicbor::CborLen::cbor_len(&#n, ctx)
+                        + minicbor::CborLen::cbor_len(&#encoder(#value), ctx)
+                }
+            });
+            decoders.push(quote! {
+                #n => {
+                    let #decoder(value) = minicbor::Decode::decode(d, ctx)?;
+                    Ok(#ident::#variant(value))
+                }
+            });
+        }
+        quote! {
+            const _: () = {
+                #(#wrappers)*
+
+                impl<Ctx> minicbor::Encode<Ctx> for #ident {
+                    fn encode<W: minicbor::encode::Write>(
+                        &self,
+                        e: &mut minicbor::Encoder<W>,
+                        ctx: &mut Ctx,
+                    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+                        match self {
+                            #(#encoders),*
+                        }
+                    }
+                }
+
+                impl<Ctx> minicbor::CborLen<Ctx> for #ident {
+                    fn cbor_len(&self, ctx: &mut Ctx) -> usize {
+                        match self {
+                            #(#lens),*
+                        }
+                    }
+                }
+
+                impl<'b, Ctx> minicbor::Decode<'b, Ctx> for #ident {
+                    fn decode(
+                        d: &mut minicbor::Decoder<'b>,
+                        ctx: &mut Ctx,
+                    ) -> Result<Self, minicbor::decode::Error> {
+                        let p = d.position();
+                        if d.array()? != Some(2) {
+                            let msg = "expected the alternative of a choice and its value";
+                            return Err(minicbor::decode::Error::message(msg).at(p));
+                        }
+                        match d.u32()? {
+                            #(#decoders)*
+                            _ => {
+                                let msg = "unknown alternative of a choice";
+                                Err(minicbor::decode::Error::message(msg).at(p))
+                            }
+                        }
+                    }
+                }
+            };
+        }
+        .to_tokens(tokens)
+    }
+}
+
+/// The attributes of a variant split into its cbor attributes and its serde attributes
+fn split_attrs(
+    node: &LinkedNode,
+    language: Language,
+    key: &str,
+) -> (Vec<syn::Attribute>, Vec<syn::Attribute>) {
+    use syn::parse::Parser;
+    let attr = AttrTokens {
+        node,
+        language,
+        totality: Totality::Complete,
+        optional: false,
+        boxed: false,
+        n: 0,
+        key,
+    };
+    syn::Attribute::parse_outer
+        .parse2(attr.into_token_stream())
+        .unwrap_or_default()
+        .into_iter()
+        .partition(|attr| !attr.path().is_ident("serde"))
+}
+
+/// A choice defaults to its first alternative
+struct DefaultVariantImpl<'a> {
+    ident: &'a syn::Ident,
+    variants: &'a Variants,
+    language: Language,
+}
+impl<'a> ToTokens for DefaultVariantImpl<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ident = self.ident;
+        if let Some(LinkedKeyVal { key, node, .. }) = self.variants.members.first() {
+            let variant = quote::format_ident!("{}", self.language.enumify(key));
+            let default_impl = match node {
+                LinkedNode::Nil => quote! {#ident::#variant},
+                node => {
+                    let default_impl = DefaultTokens { node };
+                    quote! {#ident::#variant(#default_impl)}
+                }
+            };
+            quote! {
+                impl Default for #ident {
+                    fn default() -> #ident {
+                        #default_impl
+                    }
+                }
+            }
+            .to_tokens(tokens)
+        }
+    }
+}
+
+/// The typescript type of the serde representation of a node
+pub(crate) fn ts_type(node: &LinkedNode) -> String {
+    match node {
+        LinkedNode::Primative(ConstrainedPrimative::Bool) => "boolean".into(),
+        LinkedNode::Primative(ConstrainedPrimative::Str(_))
+        | LinkedNode::Primative(ConstrainedPrimative::UnboundedStr)
+        | LinkedNode::Regexp(_) => "string".into(),
+        LinkedNode::Primative(ConstrainedPrimative::Bytes(_))
+        | LinkedNode::Primative(ConstrainedPrimative::UnboundedBytes) => "number[]".into(),
+        // The encoded item is serialized as bytes
+        LinkedNode::Primative(ConstrainedPrimative::Any(_))
+        | LinkedNode::Primative(ConstrainedPrimative::UnboundedAny) => "Uint8Array".into(),
+        LinkedNode::Primative(_)
+        | LinkedNode::Range(_)
+        | LinkedNode::Constrained(_)
+        | LinkedNode::Bits(_) => "number".into(),
+        LinkedNode::ForeignStruct(s) | LinkedNode::ForeignChoice(s) => {
+            format!("{}Props", s.to_upper_camel_case())
+        }
+        LinkedNode::ForeignEnumeration(s) => s.to_upper_camel_case(),
+        LinkedNode::Array(LinkedArray { ty, .. }) => format!("{}[]", ts_type(ty)),
+        LinkedNode::Tag(LinkedTag { ty, .. }) => ts_type(ty),
+        _ => "unknown".into(),
+    }
+}
@@ -0,0 +1,393 @@
+use super::literals::LitToks;
+use crate::parse::Language;
+use proc_macro2::{Literal, TokenStream};
+use quote::{quote, ToTokens};
+use std::borrow::Cow;
+use syn::{Ident, LitStr};
+
+pub struct Enumeration<'a> {
+    pub name: &'a str,
+    pub prefix: Option<&'a LitStr>,
+    pub members: Cow<'a, seedle_parser::Enumeration>,
+    pub language: Language,
+}
+impl<'a> Enumeration<'a> {
+    fn render(&self) -> TokenStream {
+        let language = self.language;
+        let ident = self.ident();
+        let repr = self.repr();
+        let variants = self.variants();
+        let (idents, discriminants): (Vec<&Ident>, Vec<Literal>) = variants
+            .iter()
+            .enumerate()
+            .map(|(idx, (ident, lit))| (ident, discriminant(idx, lit)))
+            .unzip();
+        let default_variant = &idents[0];
+        let unsigned = repr.starts_with('u');
+        let cbor_impl = CborImpl {
+            ident: &ident,
+            variants: &variants,
+            unsigned,
+        };
+        let repr = quote::format_ident!("{}", repr);
+        let enum_impl = quote! {
+            pub enum #ident {
+                #(#idents = #discriminants),*
+            }
+
+            impl Default for #ident {
+                fn default() -> #ident {
+                    #ident::#default_variant
+                }
+            }
+
+            #cbor_impl
+        };
+        match language {
+            Language::C => {
+                // C reads the text of a variant through a getter, IE: mode_dhcp_text()
+                let texts = variants
+                    .iter()
+                    .filter_map(|(ident, lit)| match lit {
+                        seedle_parser::Literal::Str(s) => Some((ident, s.to_string())),
+                        seedle_parser::Literal::Char(c) => Some((ident, c.to_string())),
+                        _ => None,
+                    })
+                    .map(|(ident, s)| (format!("{}-text", ident), seedle_parser::Literal::Str(s)))
+                    .collect::<Vec<_>>();
+                let texts = texts.iter().map(|(name, lit)| LitToks {
+                    name,
+                    prefix: self.prefix,
+                    lit,
+                    language,
+                });
+                quote! {
+                    #[repr(#repr)]
+                    #[allow(non_camel_case_types)]
+                    #[derive(Copy, Clone, PartialEq, Eq)]
+                    #[cfg_attr(feature="testing", derive(Debug))]
+                    #enum_impl
+                    #(#texts)*
+                }
+            }
+            Language::Rust => {
+                let serde_impl = SerdeImpl {
+                    ident: &ident,
+                    variants: &variants,
+                    unsigned,
+                };
+                quote! {
+                    #[repr(#repr)]
+                    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+                    #enum_impl
+                    #serde_impl
+                }
+            }
+            Language::Typescript => {
+                let serde_impl = SerdeImpl {
+                    ident: &ident,
+                    variants: &variants,
+                    unsigned,
+                };
+                let ts_name = quote::format_ident!("TS_{}", ident);
+                let ts = Literal::string(&format!(
+                    "export type {} = {};",
+                    ident,
+                    self.members
+                        .members
+                        .iter()
+                        .map(|lit| match lit {
+                            seedle_parser::Literal::Str(s) => format!("\"{}\"", s),
+                            seedle_parser::Literal::Char(c) => format!("\"{}\"", c),
+                            seedle_parser::Literal::Int(i) => i.to_string(),
+                            seedle_parser::Literal::UInt(u) => u.to_string(),
+                            _ => "never".to_string(),
+                        })
+                        .collect::<Vec<String>>()
+                        .join(" | ")
+                ));
+                quote! {
+                    #[repr(#repr)]
+                    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+                    #enum_impl
+                    #serde_impl
+
+                    #[wasm_bindgen(typescript_custom_section)]
+                    const #ts_name: &'static str = #ts;
+                }
+            }
+        }
+    }
+
+    fn ident(&self) -> Ident {
+        let prefix = self
+            .prefix
+            .map(|v| Cow::Owned(v.value()))
+            .unwrap_or(Cow::Borrowed(""));
+        let name = &format!("{}{}", prefix, self.name);
+        quote::format_ident!("{}", self.language.structify(name))
+    }
+
+    /// Each variant is named after its value. Integers, and text that would not make an
+    /// identifier, are qualified with the name of the enumeration. In C every variant is
+    /// qualified because enum constants share a global namespace. IE: MODE_DHCP
+    fn variants(&self) -> Vec<(Ident, &seedle_parser::Literal)> {
+        use seedle_parser::Literal::*;
+        self.members
+            .members
+            .iter()
+            .enumerate()
+            .map(|(idx, lit)| {
+                let value = match lit {
+                    Str(s) => s.to_string(),
+                    Char(c) => c.to_string(),
+                    Int(i) if *i < 0 => format!("neg-{}", i.unsigned_abs()),
+                    Int(i) => i.to_string(),
+                    UInt(u) => u.to_string(),
+                    _ => idx.to_string(),
+                };
+                let value = match self.language.enumify(&value) {
+                    s if s.is_empty() => idx.to_string(),
+                    _ => value,
+                };
+                let qualify = matches!(self.language, Language::C)
+                    || !value.starts_with(|c: char| c.is_ascii_alphabetic());
+                let variant = match qualify {
+                    true => format!("{}-{}", self.name, value),
+                    false => value,
+                };
+                (
+                    quote::format_ident!("{}", self.language.enumify(&variant)),
+                    lit,
+                )
+            })
+            .collect()
+    }
+
+    /// The variant that holds a value, IE: the default of a member
+    pub fn variant(&self, value: &seedle_parser::Literal) -> Option<Ident> {
+        let position = self.members.position(value)?;
+        self.variants().into_iter().nth(position).map(|(ident, _)| ident)
+    }
+
+    /// The smallest integer representation that holds every discriminant
+    fn repr(&self) -> &'static str {
+        use seedle_parser::Literal::*;
+        let ints = self
+            .members
+            .members
+            .iter()
+            .filter_map(|lit| match lit {
+                Int(i) => Some(*i as i128),
+                UInt(u) => Some(*u as i128),
+                _ => None,
+            })
+            .collect::<Vec<i128>>();
+        if ints.is_empty() {
+            return match self.members.members.len() {
+                n if n <= u8::MAX as usize + 1 => "u8",
+                _ => "u16",
+            };
+        }
+        let min = ints.iter().copied().min().unwrap_or(0);
+        let max = ints.iter().copied().max().unwrap_or(0);
+        match (min, max) {
+            (min, max) if min >= 0 && max <= u8::MAX as i128 => "u8",
+            (min, max) if min >= i8::MIN as i128 && max <= i8::MAX as i128 => "i8",
+            (min, max) if min >= 0 && max <= u16::MAX as i128 => "u16",
+            (min, max) if min >= i16::MIN as i128 && max <= i16::MAX as i128 => "i16",
+            (min, max) if min >= 0 && max <= u32::MAX as i128 => "u32",
+            (min, max) if min >= i32::MIN as i128 && max <= i32::MAX as i128 => "i32",
+            (min, _) if min >= 0 => "u64",
+            _ => "i64",
+        }
+    }
+}
+
+impl<'a> ToTokens for Enumeration<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.render().to_tokens(tokens);
+    }
+}
+
+/// Integer enumerations are discriminated by their value, text enumerations by their position
+fn discriminant(idx: usize, lit: &seedle_parser::Literal) -> Literal {
+    match lit {
+        seedle_parser::Literal::Int(i) => Literal::i64_unsuffixed(*i),
+        seedle_parser::Literal::UInt(u) => Literal::u64_unsuffixed(*u),
+        _ => Literal::usize_unsuffixed(idx),
+    }
+}
+
+/// Encode and decode each variant as its literal CBOR value
+struct CborImpl<'a> {
+    ident: &'a Ident,
+    variants: &'a [(Ident, &'a seedle_parser::Literal)],
+    unsigned: bool,
+}
+impl<'a> ToTokens for CborImpl<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        use seedle_parser::Literal::*;
+        let ident = self.ident;
+        let unknown = Literal::string(&format!("unknown {}", ident));
+        let idents = self.variants.iter().map(|(ident, _)| ident);
+        let (encode, len, decode) = match self.variants.first() {
+            Some((_, Str(_) | Char(_))) => {
+                let values = self
+                    .variants
+                    .iter()
+                    .map(|(_, lit)| match lit {
+                        Str(s) => Literal::string(s),
+                        Char(c) => Literal::string(&c.to_string()),
+                        _ => Literal::string(""),
+                    })
+                    .collect::<Vec<Literal>>();
+                let idents = idents.clone();
+                let encode = quote! {
+                    match self {
+                        #(#ident::#idents => e.str(#values)?),*
+                    };
+                };
+                let idents = self.variants.iter().map(|(ident, _)| ident);
+                let len = quote! {
+                    match self {
+                        #(#ident::#idents => minicbor::CborLen::<C>::cbor_len(#values, ctx)),*
+                    }
+                };
+                let idents = self.variants.iter().map(|(ident, _)| ident);
+                let decode = quote! {
+                    match d.str()? {
+                        #(#values => Ok(#ident::#idents),)*
+                        _ => Err(minicbor::decode::Error::message(#unknown)),
+                    }
+                };
+                (encode, len, decode)
+            }
+            _ => {
+                let values = self
+                    .variants
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, (_, lit))| discriminant(idx, lit))
+                    .collect::<Vec<Literal>>();
+                let (ty, method) = match self.unsigned {
+                    true => (quote! {u64}, quote! {u64}),
+                    false => (quote! {i64}, quote! {i64}),
+                };
+                let encode = quote! {e.#method(*self as #ty)?;};
+                let len = quote! {minicbor::CborLen::<C>::cbor_len(&(*self as #ty), ctx)};
+                let decode = quote! {
+                    match d.#method()? {
+                        #(#values => Ok(#ident::#idents),)*
+                        _ => Err(minicbor::decode::Error::message(#unknown)),
+                    }
+                };
+                (encode, len, decode)
+            }
+        };
+        quote! {
+            impl<C> minicbor::Encode<C> for #ident {
+                fn encode<W: minicbor::encode::Write>(
+                    &self,
+                    e: &mut minicbor::Encoder<W>,
+                    _ctx: &mut C,
+                ) -> Result<(), minicbor::encode::Error<W::Error>> {
+                    #encode
+                    Ok(())
+                }
+            }
+
+            impl<C> minicbor::CborLen<C> for #ident {
+                fn cbor_len(&self, ctx: &mut C) -> usize {
+                    #len
+                }
+            }
+
+            impl<'b, C> minicbor::Decode<'b, C> for #ident {
+                fn decode(
+                    d: &mut minicbor::Decoder<'b>,
+                    _ctx: &mut C,
+                ) -> Result<#ident, minicbor::decode::Error> {
+                    #decode
+                }
+            }
+        }
+        .to_tokens(tokens)
+    }
+}
+
+/// Serialize each variant as its literal value so that JSON matches the CBOR representation
+struct SerdeImpl<'a> {
+    ident: &'a Ident,
+    variants: &'a [(Ident, &'a seedle_parser::Literal)],
+    unsigned: bool,
+}
+impl<'a> ToTokens for SerdeImpl<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        use seedle_parser::Literal::*;
+        let ident = self.ident;
+        let unknown = Literal::string(&format!("unknown {}", ident));
+        let idents = self.variants.iter().map(|(ident, _)| ident).collect::<Vec<_>>();
+        let (serialize, deserialize) = match self.variants.first() {
+            Some((_, Str(_) | Char(_))) => {
+                let values = self
+                    .variants
+                    .iter()
+                    .map(|(_, lit)| match lit {
+                        Str(s) => Literal::string(s),
+                        Char(c) => Literal::string(&c.to_string()),
+                        _ => Literal::string(""),
+                    })
+                    .collect::<Vec<Literal>>();
+                (
+                    quote! {
+                        match self {
+                            #(#ident::#idents => s.serialize_str(#values)),*
+                        }
+                    },
+                    quote! {
+                        match <String as serde::Deserialize>::deserialize(d)?.as_str() {
+                            #(#values => Ok(#ident::#idents),)*
+                            _ => Err(<D::Error as serde::de::Error>::custom(#unknown)),
+                        }
+                    },
+                )
+            }
+            _ => {
+                let values = self
+                    .variants
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, (_, lit))| discriminant(idx, lit))
+                    .collect::<Vec<Literal>>();
+                let (ty, method) = match self.unsigned {
+                    true => (quote! {u64}, quote! {serialize_u64}),
+                    false => (quote! {i64}, quote! {serialize_i64}),
+                };
+                (
+                    quote! {s.#method(*self as #ty)},
+                    quote! {
+                        match <#ty as serde::Deserialize>::deserialize(d)? {
+                            #(#values => Ok(#ident::#idents),)*
+                            _ => Err(<D::Error as serde::de::Error>::custom(#unknown)),
+                        }
+                    },
+                )
+            }
+        };
+        quote! {
+            impl serde::Serialize for #ident {
+                fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                    #serialize
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for #ident {
+                fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<#ident, D::Error> {
+                    #deserialize
+                }
+            }
+        }
+        .to_tokens(tokens)
+    }
+}
@@ -1,10 +1,14 @@
+use super::utils::method_attrs;
 use crate::parse::Language;
+use heck::*;
 use proc_macro2::{Literal, TokenStream};
 use quote::{quote, ToTokens};
 use std::fmt::Display;
+use syn::LitStr;
 
 pub struct LitToks<'a> {
     pub name: &'a str,
+    pub prefix: Option<&'a LitStr>,
     pub lit: &'a seedle_parser::Literal,
     pub language: Language,
 }
@@ -33,19 +37,27 @@ impl<'a> ToTokens for LitToks<'a> {
                 ty: "u64",
             }
             .to_tokens(tokens),
-            seedle_parser::Literal::Char(lit) => CharToks {
-                lit: *lit,
+            // Text of one character is text like any other
+            seedle_parser::Literal::Char(lit) => StrToks {
+                lit: lit.encode_utf8(&mut [0; 4]),
                 name: self.name,
+                prefix: self.prefix,
                 language: self.language,
             }
             .to_tokens(tokens),
             seedle_parser::Literal::Str(lit) => StrToks {
+                lit,
+                name: self.name,
+                prefix: self.prefix,
+                language: self.language,
+            }
+            .to_tokens(tokens),
+            seedle_parser::Literal::Bytes(lit) => BytesToks {
                 lit,
                 name: self.name,
                 language: self.language,
             }
             .to_tokens(tokens),
-            _ => unimplemented!(),
         };
     }
 }
@@ -64,7 +76,8 @@ impl<'a, T: ToTokens + Copy + Display> ToTokens for CopyToks<'a, T> {
         let val = self.lit;
         match self.language {
             Language::Typescript => {
-                let s = Literal::string(&format!("export type {} = {}", self.name, self.lit));
+                let ts = self.name.to_upper_camel_case();
+                let s = Literal::string(&format!("export type {} = {}", ts, self.lit));
                 quote! {
                     pub const #name: #ty = #val;
                     #[wasm_bindgen(typescript_custom_section)]
@@ -77,51 +90,78 @@ impl<'a, T: ToTokens + Copy + Display> ToTokens for CopyToks<'a, T> {
     }
 }
 
-struct CharToks<'a> {
+struct StrToks<'a> {
     name: &'a str,
-    lit: char,
+    prefix: Option<&'a LitStr>,
+    lit: &'a str,
     language: Language,
 }
-impl<'a> ToTokens for CharToks<'a> {
+/// Text is a str for rust and typescript. C reads text through a pointer, so the text is NUL
+/// terminated and exported with a getter named with the prefix of the exported functions.
+/// IE: pub const NAME: [u8; 3] = *b"hi\0"; and name() or my_name() with the prefix "my"
+impl<'a> ToTokens for StrToks<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let name = quote::format_ident!("{}", self.language.enumify(self.name));
         let ts_name = quote::format_ident!("TS_{}", self.language.enumify(self.name));
-        let val = Literal::character(self.lit);
+        let val = Literal::string(self.lit);
         match self.language {
+            Language::C => {
+                let len = Literal::usize_unsuffixed(self.lit.len() + 1);
+                let val = Literal::byte_string(format!("{}\0", self.lit).as_bytes());
+                let prefix = match self.prefix {
+                    Some(prefix) => format!("{}_", self.language.functionify(&prefix.value())),
+                    None => String::new(),
+                };
+                let getter = self.language.functionify(self.name);
+                let getter = quote::format_ident!("{}{}", prefix, getter);
+                let (getter, getter_attrs) = method_attrs(self.language, getter);
+                quote! {
+                    pub const #name: [u8; #len] = *#val;
+                    #getter_attrs
+                    fn #getter() -> *const core::ffi::c_char {
+                        static VALUE: [u8; #len] = #name;
+                        VALUE.as_ptr() as *const core::ffi::c_char
+                    }
+                }
+            }
+            Language::Rust => quote! {pub const #name: &'static str = #val;},
             Language::Typescript => {
-                let s = Literal::string(&format!("export type {} = '{}'", self.name, self.lit));
+                let ts = self.name.to_upper_camel_case();
+                let s = Literal::string(&format!("export type {} = {:?}", ts, self.lit));
                 quote! {
-                    pub const #name: char = #val;
+                    pub const #name: &'static str = #val;
                     #[wasm_bindgen(typescript_custom_section)]
                     const #ts_name: &'static str = #s ;
                 }
             }
-            _ => quote! {pub const #name: char = #val;},
         }
         .to_tokens(tokens)
     }
 }
 
-struct StrToks<'a> {
+/// A byte string literal is an array of its bytes. IE: pub const MAGIC: [u8; 2] = [222, 173];
+struct BytesToks<'a> {
     name: &'a str,
-    lit: &'a str,
+    lit: &'a [u8],
     language: Language,
 }
-impl<'a> ToTokens for StrToks<'a> {
+impl<'a> ToTokens for BytesToks<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let name = quote::format_ident!("{}", self.language.enumify(self.name));
         let ts_name = quote::format_ident!("TS_{}", self.language.enumify(self.name));
-        let val = quote::format_ident!("\"{}\"'", self.lit);
+        let len = Literal::usize_unsuffixed(self.lit.len());
+        let val = self.lit.iter().map(|b| Literal::u8_unsuffixed(*b));
         match self.language {
             Language::Typescript => {
-                let s = Literal::string(&format!("export type {} = \"{}\"", self.name, self.lit));
+                let ts = self.name.to_upper_camel_case();
+                let s = Literal::string(&format!("export type {} = Uint8Array", ts));
                 quote! {
-                    pub const #name: &'static str = #val;
+                    pub const #name: [u8; #len] = [#(#val),*];
                     #[wasm_bindgen(typescript_custom_section)]
                     const #ts_name: &'static str = #s ;
                 }
             }
-            _ => quote! {pub const #name: &'static str = #val;},
+            _ => quote! {pub const #name: [u8; #len] = [#(#val),*];},
         }
         .to_tokens(tokens)
     }
@@ -1,10 +1,15 @@
 pub mod cddl;
 //pub mod ffi;
 pub(crate) mod utils;
+pub(crate) mod choices;
+pub(crate) mod enumerations;
 pub(crate) mod literals;
 pub(crate) mod structs;
 pub mod vtable;
 
+#[cfg(test)]
+mod tests;
+
 //pub use ffi::*;
 pub use vtable::*;
 pub use cddl::*;
@@ -1,3 +1,5 @@
+use super::choices::ts_type;
+use super::enumerations::Enumeration;
 use super::utils::method_attrs;
 use crate::parse::Language;
 use heck::*;
@@ -6,6 +8,7 @@ use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens};
 use seedle_parser::*;
 use std::borrow::Cow;
+use std::collections::BTreeSet;
 use syn::LitStr;
 
 #[derive(Copy, Clone)]
@@ -28,6 +31,14 @@ pub struct Struct<'a> {
     pub prefix: Option<&'a LitStr>,
     pub fields: Cow<'a, Fields>,
     pub language: Language,
+    /// False when the struct holds an unbounded tstr or bstr
+    pub copy: bool,
+    /// True when the struct is a record encoded as an array of its fields
+    pub record: bool,
+    /// The schema, to name the variant a member defaults to when it holds an enumeration
+    pub rules: &'a Rules<LinkedNode>,
+    /// The members that hold the struct itself, which are held through a Box
+    pub boxed: BTreeSet<String>,
 }
 impl<'a> Struct<'a> {
     fn render(&self) -> TokenStream {
@@ -38,6 +49,18 @@ impl<'a> Struct<'a> {
         }
     }
 
+    /// Structs with optional members are encoded as maps so absent members can be skipped, and
+    /// structs with integer keys are encoded as maps keyed by the declared keys. Records are
+    /// always encoded as arrays, where absent trailing members are skipped
+    fn cbor_attrs(&self) -> TokenStream {
+        let members = &self.fields.members;
+        match members.iter().any(|m| m.is_optional() || m.is_indexed()) {
+            _ if self.record => quote! {#[cbor(array)]},
+            true => quote! {#[cbor(map)]},
+            false => quote! {},
+        }
+    }
+
     fn render_c(&self) -> TokenStream {
         let language = self.language;
         let prefix = self
@@ -51,27 +74,48 @@ impl<'a> Struct<'a> {
             .members
             .iter()
             .enumerate()
-            .map(|(n, LinkedKeyVal(key, node))| FieldAttrTokens {
+            .map(|(n, LinkedKeyVal { key, node, optional, index, .. })| FieldAttrTokens {
                 node,
                 language,
                 key,
                 totality: Totality::Complete,
-                n,
+                optional: *optional,
+                boxed: self.boxed.contains(key),
+                n: index.map_or(n, |index| index as usize),
             });
         let default_impl = DefaultImpl {
             ident: &struct_ident,
             fields: &self.fields,
             language,
+            rules: self.rules,
+            boxed: &self.boxed,
         };
         let ffi_impl = CApiImpl {
             ident: &struct_ident,
             language,
         };
+        let validate_impl = ValidateImpl {
+            ident: &struct_ident,
+            fields: &self.fields,
+            language,
+        };
+        let regexp_impl = RegexpImpl {
+            ident: &struct_ident,
+            members: &self.fields.members,
+        };
+        let bits_impl = BitsImpl {
+            ident: &struct_ident,
+            fields: &self.fields,
+            language,
+        };
+        let cbor_attrs = self.cbor_attrs();
+        let copy = self.copy.then(|| quote! {Copy,});
         let struct_attrs = quote! {
             #[repr(C)]
             #[allow(non_camel_case_types)]
-            #[derive(Copy, Clone, minicbor::CborLen, minicbor::Encode, minicbor::Decode)]
+            #[derive(#copy Clone, minicbor::CborLen, minicbor::Encode, minicbor::Decode)]
             #[cfg_attr(feature="testing", derive(Debug, PartialEq))]
+            #cbor_attrs
         };
         let struct_impl = quote! {
             pub struct #struct_ident{
@@ -83,6 +127,9 @@ impl<'a> Struct<'a> {
             #struct_impl
             #default_impl
             #ffi_impl
+            #validate_impl
+            #regexp_impl
+            #bits_impl
         }
     }
 
@@ -99,20 +146,41 @@ impl<'a> Struct<'a> {
             .members
             .iter()
             .enumerate()
-            .map(|(n, LinkedKeyVal(key, node))| FieldAttrTokens {
+            .map(|(n, LinkedKeyVal { key, node, optional, index, .. })| FieldAttrTokens {
                 node,
                 language,
                 key,
                 totality: Totality::Complete,
-                n,
+                optional: *optional,
+                boxed: self.boxed.contains(key),
+                n: index.map_or(n, |index| index as usize),
             });
         let default_impl = DefaultImpl {
             ident: &struct_ident,
             fields: &self.fields,
             language,
+            rules: self.rules,
+            boxed: &self.boxed,
         };
+        let validate_impl = ValidateImpl {
+            ident: &struct_ident,
+            fields: &self.fields,
+            language,
+        };
+        let regexp_impl = RegexpImpl {
+            ident: &struct_ident,
+            members: &self.fields.members,
+        };
+        let bits_impl = BitsImpl {
+            ident: &struct_ident,
+            fields: &self.fields,
+            language,
+        };
+        let cbor_attrs = self.cbor_attrs();
+        let copy = self.copy.then(|| quote! {Copy,});
         let struct_attrs = quote! {
-            #[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize, minicbor::CborLen, minicbor::Encode, minicbor::Decode)]
+            #[derive(#copy Clone, Debug, serde::Serialize, serde::Deserialize, minicbor::CborLen, minicbor::Encode, minicbor::Decode)]
+            #cbor_attrs
         };
         let struct_impl = quote! {
             pub struct #struct_ident{
@@ -123,6 +191,9 @@ impl<'a> Struct<'a> {
             #struct_attrs
             #struct_impl
             #default_impl
+            #validate_impl
+            #regexp_impl
+            #bits_impl
         }
     }
 
@@ -143,24 +214,28 @@ impl<'a> Struct<'a> {
                 .members
                 .iter()
                 .enumerate()
-                .map(|(n, LinkedKeyVal(key, node))| FieldAttrTokens {
+                .map(|(n, LinkedKeyVal { key, node, optional, index, .. })| FieldAttrTokens {
                     node,
                     language,
                     key,
                     totality: Totality::Complete,
-                    n,
+                    optional: *optional,
+                    boxed: self.boxed.contains(key),
+                    n: index.map_or(n, |index| index as usize),
                 });
         let fields_partial =
             self.fields
                 .members
                 .iter()
                 .enumerate()
-                .map(|(n, LinkedKeyVal(key, node))| FieldAttrTokens {
+                .map(|(n, LinkedKeyVal { key, node, optional, index, .. })| FieldAttrTokens {
                     node,
                     language,
                     key,
                     totality: Totality::Partial,
-                    n,
+                    optional: *optional,
+                    boxed: self.boxed.contains(key),
+                    n: index.map_or(n, |index| index as usize),
                 });
 
         let ffi_impl = CApiImpl {
@@ -171,19 +246,42 @@ impl<'a> Struct<'a> {
             ident: &struct_ident_complete,
             fields: &self.fields,
             language,
+            rules: self.rules,
+            boxed: &self.boxed,
         };
         let from_impl = FromImpl {
             ident: &struct_ident_complete,
             fields: &self.fields,
+            language,
+            rules: self.rules,
+            boxed: &self.boxed,
         };
         let wasm_impl = WasmImpl {
             ident: &struct_ident_complete,
             fields: &self.fields,
+            boxed: &self.boxed,
         };
+        let validate_impl = ValidateImpl {
+            ident: &struct_ident_complete,
+            fields: &self.fields,
+            language,
+        };
+        let regexp_impl = RegexpImpl {
+            ident: &struct_ident_complete,
+            members: &self.fields.members,
+        };
+        let bits_impl = BitsImpl {
+            ident: &struct_ident_complete,
+            fields: &self.fields,
+            language,
+        };
+        let cbor_attrs = self.cbor_attrs();
+        let copy = self.copy.then(|| quote! {Copy,});
         let struct_attrs = quote! {
             #[wasm_bindgen]
-            #[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize, minicbor::CborLen, minicbor::Encode, minicbor::Decode)]
+            #[derive(#copy Clone, Debug, serde::Serialize, serde::Deserialize, minicbor::CborLen, minicbor::Encode, minicbor::Decode)]
             #[serde(rename_all=#serde_rename_ts)]
+            #cbor_attrs
         };
         let struct_complete_impl = quote! {
             pub struct #struct_ident_complete {
@@ -204,6 +302,9 @@ impl<'a> Struct<'a> {
             #default_impl
             #from_impl
             #wasm_impl
+            #validate_impl
+            #regexp_impl
+            #bits_impl
         }
     }
 }
@@ -218,6 +319,8 @@ pub struct FieldAttrTokens<'a> {
     node: &'a LinkedNode,
     language: Language,
     totality: Totality,
+    optional: bool,
+    boxed: bool,
     n: usize,
     key: &'a str,
 }
@@ -227,12 +330,17 @@ impl<'a> ToTokens for FieldAttrTokens<'a> {
             node: self.node,
             language: self.language,
             totality: self.totality,
+            optional: self.optional,
+            boxed: self.boxed,
             n: self.n,
+            key: self.key,
         };
         let field = FieldTokens {
             node: self.node,
             language: self.language,
             totality: self.totality,
+            optional: self.optional,
+            boxed: self.boxed,
             key: self.key,
         };
         quote! {
@@ -244,79 +352,340 @@ impl<'a> ToTokens for FieldAttrTokens<'a> {
 }
 
 pub struct AttrTokens<'a> {
-    node: &'a LinkedNode,
-    language: Language,
-    n: usize,
-    totality: Totality,
+    pub(crate) node: &'a LinkedNode,
+    pub(crate) language: Language,
+    pub(crate) n: usize,
+    pub(crate) totality: Totality,
+    pub(crate) optional: bool,
+    /// True when the member is held through a Box, see Struct::boxed
+    pub(crate) boxed: bool,
+    pub(crate) key: &'a str,
 }
 impl<'a> ToTokens for AttrTokens<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let n = proc_macro2::Literal::usize_unsuffixed(self.n);
-        let bytes = proc_macro2::Literal::string("minicbor::bytes");
         let ser = proc_macro2::Literal::string("seedle_extra::serde::ser_bytes_as_str");
         let de;
         let def;
-        if self.totality.is_partial() {
+        if self.totality.is_partial() || self.optional {
             de = proc_macro2::Literal::string("seedle_extra::serde::de_option_str_as_bytes");
             def = proc_macro2::Literal::string("seedle_extra::serde::make_option_default_bytes");
         } else {
             de = proc_macro2::Literal::string("seedle_extra::serde::de_str_as_bytes");
             def = proc_macro2::Literal::string("seedle_extra::serde::make_default_bytes");
         }
+        // Absent optional members are absent in json too
+        let serde_optional = match (self.language, self.optional) {
+            (Language::C, _) | (_, false) => quote! {},
+            _ => quote! {#[serde(default, skip_serializing_if="Option::is_none")]},
+        };
+        // Patterns are matched by the decoder of the member, see RegexpImpl
+        if let (false, LinkedNode::Regexp(regexp)) = (self.totality.is_partial(), self.node) {
+            let decoder = format!("Self::{}", regexp_decoder(self.key));
+            let dec = proc_macro2::Literal::string(&decoder);
+            let node = LinkedNode::Primative(regexp.ty);
+            let is_nil = proc_macro2::Literal::string("seedle_extra::optional::is_nil");
+            let nil = nil_path(&node, self.language, false);
+            let codec = match (regexp.ty, self.optional) {
+                (ConstrainedPrimative::Str(_), optional) => {
+                    let enc = proc_macro2::Literal::string("minicbor::bytes::encode");
+                    let len = proc_macro2::Literal::string("minicbor::bytes::cbor_len");
+                    match optional {
+                        true => quote! {
+                            encode_with=#enc, decode_with=#dec, cbor_len=#len, is_nil=#is_nil, nil=#nil
+                        },
+                        false => quote! {encode_with=#enc, decode_with=#dec, cbor_len=#len},
+                    }
+                }
+                (_, true) => {
+                    let enc = proc_macro2::Literal::string("seedle_extra::optional::encode");
+                    quote! {encode_with=#enc, is_nil=#is_nil, decode_with=#dec, nil=#nil}
+                }
+                (_, false) => quote! {decode_with=#dec},
+            };
+            let serde_str = match (self.language, regexp.ty, self.optional) {
+                (Language::C, ..) | (_, ConstrainedPrimative::UnboundedStr, _) => quote! {},
+                (_, _, true) => quote! {
+                    #[serde(serialize_with=#ser)]
+                    #[serde(deserialize_with=#de)]
+                },
+                (_, _, false) => quote! {
+                    #[serde(default=#def)]
+                    #[serde(serialize_with=#ser)]
+                    #[serde(deserialize_with=#de)]
+                },
+            };
+            return quote! {
+                #[cbor(n(#n), #codec)]
+                #serde_str
+                #serde_optional
+            }
+            .to_tokens(tokens);
+        }
+        // Constraints are checked by the validator and are encoded as the number they constrain
+        if let Cow::Owned(node) = self.node.unconstrained() {
+            return AttrTokens { node: &node, ..*self }.to_tokens(tokens);
+        }
+        let bytes = CodecTokens {
+            codec: "minicbor::bytes",
+            node: self.node,
+            language: self.language,
+            optional: self.optional,
+        };
+        if let LinkedNode::Tag(tag) = self.node {
+            let codec = TagCodecTokens {
+                tag,
+                language: self.language,
+                nil: self.optional || self.totality.is_partial(),
+                boxed: self.boxed,
+            };
+            let serde_str = match (self.language, tag.ty.as_ref()) {
+                (Language::C, _) => quote! {},
+                (_, LinkedNode::Primative(ConstrainedPrimative::Str(_)))
+                    if self.totality.is_partial() || self.optional =>
+                {
+                    quote! {
+                        #[serde(serialize_with=#ser)]
+                        #[serde(deserialize_with=#de)]
+                    }
+                }
+                (_, LinkedNode::Primative(ConstrainedPrimative::Str(_))) => quote! {
+                    #[serde(default=#def)]
+                    #[serde(serialize_with=#ser)]
+                    #[serde(deserialize_with=#de)]
+                },
+                _ => quote! {},
+            };
+            return quote! {
+                #[cbor(n(#n), #codec)]
+                #serde_str
+                #serde_optional
+            }
+            .to_tokens(tokens);
+        }
+        if let (false, Some(decode_with)) = (self.totality.is_partial(), range_decoder(self.node)) {
+            return match self.optional {
+                true => {
+                    let enc = proc_macro2::Literal::string("seedle_extra::optional::encode");
+                    let is_nil = proc_macro2::Literal::string("seedle_extra::optional::is_nil");
+                    let nil = nil_path(self.node, self.language, false);
+                    quote! {
+                        #[cbor(n(#n), encode_with=#enc, is_nil=#is_nil, decode_with=#decode_with, nil=#nil)]
+                        #serde_optional
+                    }
+                }
+                false => quote! {#[cbor(n(#n), decode_with=#decode_with)]},
+            }
+            .to_tokens(tokens);
+        }
+        if is_half(self.node) {
+            let half = CodecTokens {
+                codec: "seedle_extra::float",
+                node: self.node,
+                language: self.language,
+                optional: self.optional,
+            };
+            return quote! {
+                #[cbor(n(#n), #half)]
+                #serde_optional
+            }
+            .to_tokens(tokens);
+        }
         match self.language {
             Language::C => match self.node {
                 LinkedNode::Array(LinkedArray { ty, .. }) => match ty.as_ref() {
                     LinkedNode::Primative(ConstrainedPrimative::U8) => {
-                        quote! {#[cbor(n(#n), with=#bytes)]}.to_tokens(tokens)
+                        quote! {#[cbor(n(#n), #bytes)]}.to_tokens(tokens)
                     }
                     _ => quote! {#[n(#n)]}.to_tokens(tokens),
                 },
                 LinkedNode::Primative(ConstrainedPrimative::Str(_)) => {
-                    quote! {#[cbor(n(#n), with=#bytes)]}.to_tokens(tokens)
+                    quote! {#[cbor(n(#n), #bytes)]}.to_tokens(tokens)
                 }
                 _ => quote! {#[n(#n)]}.to_tokens(tokens),
             },
             Language::Rust | Language::Typescript => match self.node {
-                LinkedNode::Array(LinkedArray { ty, len }) => match ty.as_ref() {
-                    LinkedNode::Primative(ConstrainedPrimative::U8) if *len <= 32 => {
-                        quote! {#[cbor(n(#n), with=#bytes)]}.to_tokens(tokens)
+                // Bounded arrays serialize themselves, only big fixed arrays need serde helpers
+                LinkedNode::Array(array) => match array.ty.as_ref() {
+                    LinkedNode::Primative(ConstrainedPrimative::U8)
+                        if array.len <= 32 || !array.is_fixed() =>
+                    {
+                        quote! {
+                            #[cbor(n(#n), #bytes)]
+                            #serde_optional
+                        }
+                        .to_tokens(tokens)
                     }
                     LinkedNode::Primative(ConstrainedPrimative::U8) => quote! {
-                        #[cbor(n(#n), with=#bytes)]
+                        #[cbor(n(#n), #bytes)]
                         #[serde(serialize_with=#ser)]
                         #[serde(deserialize_with=#de)]
+                        #serde_optional
+                    }
+                    .to_tokens(tokens),
+                    _ => quote! {
+                        #[n(#n)]
+                        #serde_optional
                     }
                     .to_tokens(tokens),
-                    _ => quote! {#[n(#n)]}.to_tokens(tokens),
                 },
+                LinkedNode::Primative(ConstrainedPrimative::UnboundedBytes) => quote! {
+                    #[cbor(n(#n), #bytes)]
+                    #serde_optional
+                }
+                .to_tokens(tokens),
+                LinkedNode::Primative(ConstrainedPrimative::Str(_)) if self.optional => quote! {
+                    #[cbor(n(#n), #bytes)]
+                    #[serde(serialize_with=#ser)]
+                    #[serde(deserialize_with=#de)]
+                    #serde_optional
+                }
+                .to_tokens(tokens),
                 LinkedNode::Primative(ConstrainedPrimative::Str(_)) => quote! {
-                    #[cbor(n(#n), with=#bytes)]
+                    #[cbor(n(#n), #bytes)]
                     #[serde(default=#def)]
                     #[serde(serialize_with=#ser)]
                     #[serde(deserialize_with=#de)]
                 }
                 .to_tokens(tokens),
-                _ => quote! {#[n(#n)]}.to_tokens(tokens),
+                _ => quote! {
+                    #[n(#n)]
+                    #serde_optional
+                }
+                .to_tokens(tokens),
             },
         }
     }
 }
 
+/// The custom codec of a field, IE: minicbor::bytes. Optional members are skipped when absent,
+/// so their codec must also name the type of nil
+struct CodecTokens<'a> {
+    codec: &'a str,
+    node: &'a LinkedNode,
+    language: Language,
+    optional: bool,
+}
+impl<'a> ToTokens for CodecTokens<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let codec = self.codec;
+        match self.optional {
+            true => {
+                let enc = proc_macro2::Literal::string(&format!("{}::encode", codec));
+                let dec = proc_macro2::Literal::string(&format!("{}::decode", codec));
+                let len = proc_macro2::Literal::string(&format!("{}::cbor_len", codec));
+                let is_nil = proc_macro2::Literal::string("seedle_extra::optional::is_nil");
+                let nil = nil_path(self.node, self.language, false);
+                quote! {encode_with=#enc, decode_with=#dec, cbor_len=#len, is_nil=#is_nil, nil=#nil}
+            }
+            false => {
+                let codec = proc_macro2::Literal::string(codec);
+                quote! {with=#codec}
+            }
+        }
+        .to_tokens(tokens)
+    }
+}
+
+/// Tagged members are written after their tag, and rejected when decoded with another tag. IE:
+/// encode_with="seedle_extra::tag::encode::<_, _, _, {1}>"
+struct TagCodecTokens<'a> {
+    tag: &'a LinkedTag,
+    language: Language,
+    nil: bool,
+    boxed: bool,
+}
+impl<'a> ToTokens for TagCodecTokens<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let codec = match self.tag.ty.as_ref() {
+            LinkedNode::Primative(ConstrainedPrimative::Str(_))
+            | LinkedNode::Primative(ConstrainedPrimative::Bytes(_))
+            | LinkedNode::Primative(ConstrainedPrimative::UnboundedBytes) => "seedle_extra::tag::bytes",
+            _ => "seedle_extra::tag",
+        };
+        let tag = self.tag.tag;
+        let enc = format!("{}::encode::<_, _, _, {{{}}}>", codec, tag);
+        let dec = format!("{}::decode::<_, _, {{{}}}>", codec, tag);
+        let len = format!("{}::cbor_len::<_, _, {{{}}}>", codec, tag);
+        let enc = proc_macro2::Literal::string(&enc);
+        let dec = proc_macro2::Literal::string(&dec);
+        let len = proc_macro2::Literal::string(&len);
+        match self.nil {
+            true => {
+                let is_nil = proc_macro2::Literal::string("seedle_extra::optional::is_nil");
+                let nil = nil_path(&self.tag.ty, self.language, self.boxed);
+                quote! {encode_with=#enc, decode_with=#dec, cbor_len=#len, is_nil=#is_nil, nil=#nil}
+            }
+            false => quote! {encode_with=#enc, decode_with=#dec, cbor_len=#len},
+        }
+        .to_tokens(tokens)
+    }
+}
+
+/// float16 members are stored as an f32 and need a codec to encode them as half floats
+pub(crate) fn is_half(node: &LinkedNode) -> bool {
+    match node {
+        LinkedNode::Primative(ConstrainedPrimative::F16) => true,
+        LinkedNode::Constrained(Constrained { ty, .. }) => *ty == ConstrainedPrimative::F16,
+        LinkedNode::Array(LinkedArray { ty, .. }) => is_half(ty),
+        _ => false,
+    }
+}
+
+/// minicbor cannot infer the type of a custom nil, so the path names the optional type
+fn nil_path(node: &LinkedNode, language: Language, boxed: bool) -> proc_macro2::Literal {
+    let ty = OptionalTypeTokens {
+        node,
+        language,
+        boxed,
+    };
+    proc_macro2::Literal::string(&format!("seedle_extra::optional::nil::<{}>", ty.into_token_stream()))
+}
+
+/// The decoder of a member holding text that must match a pattern. IE: decode_hostname
+fn regexp_decoder(key: &str) -> syn::Ident {
+    quote::format_ident!("decode_{}", key.to_snake_case())
+}
+
+/// Ranges are decoded with a helper that rejects values outside of the range
+fn range_decoder(node: &LinkedNode) -> Option<proc_macro2::Literal> {
+    let path = match node {
+        LinkedNode::Range(range) => {
+            let (min, max) = range.bounds();
+            format!("seedle_extra::range::decode::<_, _, {{{}}}, {{{}}}>", min, max)
+        }
+        // The decoder checks each member of nested arrays
+        LinkedNode::Array(LinkedArray { ty, .. }) => return range_decoder(ty),
+        _ => return None,
+    };
+    Some(proc_macro2::Literal::string(&path))
+}
+
 pub struct FieldTokens<'a> {
     node: &'a LinkedNode,
     language: Language,
     key: &'a str,
     totality: Totality,
+    optional: bool,
+    boxed: bool,
     // Public/private based on lang?
 }
 impl<'a> ToTokens for FieldTokens<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let key = quote::format_ident!("{}", self.key.to_snake_case());
-        let ty = TypeTokens {
+        let ty = BoxedTypeTokens {
             node: self.node,
             language: self.language,
+            boxed: self.boxed,
+        };
+        let optional = OptionalTypeTokens {
+            node: self.node,
+            language: self.language,
+            boxed: self.boxed,
         };
         match (self.language, self.totality) {
+            (Language::Typescript, _) if self.optional => quote! {#key: #optional},
+            (Language::C | Language::Rust, _) if self.optional => quote! {pub #key: #optional},
             (Language::Typescript, Totality::Partial) => quote! {#key: Option<#ty>},
             (Language::Typescript, Totality::Complete) => quote! {#key: #ty},
             (Language::C | Language::Rust, Totality::Complete) => quote! {pub #key: #ty},
@@ -326,9 +695,50 @@ impl<'a> ToTokens for FieldTokens<'a> {
     }
 }
 
-pub struct TypeTokens<'a> {
+/// Optional members are a C compatible Optional<T> in C, and an Option<T> otherwise
+pub struct OptionalTypeTokens<'a> {
+    node: &'a LinkedNode,
+    language: Language,
+    boxed: bool,
+}
+impl<'a> ToTokens for OptionalTypeTokens<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ty = BoxedTypeTokens {
+            node: self.node,
+            language: self.language,
+            boxed: self.boxed,
+        };
+        match self.language {
+            Language::C => quote! {seedle_extra::optional::Optional<#ty>},
+            Language::Rust | Language::Typescript => quote! {Option<#ty>},
+        }
+        .to_tokens(tokens)
+    }
+}
+
+/// A member that holds the struct it is a member of is held through a Box. IE: Box<Branch>
+pub struct BoxedTypeTokens<'a> {
     node: &'a LinkedNode,
     language: Language,
+    boxed: bool,
+}
+impl<'a> ToTokens for BoxedTypeTokens<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ty = TypeTokens {
+            node: self.node,
+            language: self.language,
+        };
+        match self.boxed {
+            true => quote! {Box<#ty>},
+            false => quote! {#ty},
+        }
+        .to_tokens(tokens)
+    }
+}
+
+pub struct TypeTokens<'a> {
+    pub(crate) node: &'a LinkedNode,
+    pub(crate) language: Language,
 }
 impl<'a> ToTokens for TypeTokens<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
@@ -339,8 +749,21 @@ impl<'a> ToTokens for TypeTokens<'a> {
                 node: *node,
             }
             .to_tokens(tokens),
+            LinkedNode::Range(Range { ty, .. })
+            | LinkedNode::Constrained(Constrained { ty, .. })
+            | LinkedNode::Regexp(LinkedRegexp { ty, .. })
+            | LinkedNode::Bits(LinkedBits { ty, .. }) => PrimativeTokens {
+                language,
+                node: *ty,
+            }
+            .to_tokens(tokens),
             LinkedNode::Array(node) => ArrayTokens { language, node }.to_tokens(tokens),
-            LinkedNode::ForeignStruct(node) => StructTokens { language, node }.to_tokens(tokens),
+            LinkedNode::Tag(LinkedTag { ty, .. }) => TypeTokens { language, node: ty }.to_tokens(tokens),
+            LinkedNode::ForeignStruct(node)
+            | LinkedNode::ForeignChoice(node)
+            | LinkedNode::ForeignEnumeration(node) => {
+                StructTokens { language, node }.to_tokens(tokens)
+            }
             field => syn::Error::new(Span::call_site(), format!("Invalid field! {:?}", field))
                 .to_compile_error()
                 .to_tokens(tokens),
@@ -364,11 +787,42 @@ impl ToTokens for PrimativeTokens {
             ConstrainedPrimative::I32 => quote::format_ident!("i32").to_tokens(tokens),
             ConstrainedPrimative::I64 => quote::format_ident!("i64").to_tokens(tokens),
             ConstrainedPrimative::Bool => quote::format_ident!("bool").to_tokens(tokens),
+            ConstrainedPrimative::F16 | ConstrainedPrimative::F32 => {
+                quote::format_ident!("f32").to_tokens(tokens)
+            }
+            ConstrainedPrimative::F64 => quote::format_ident!("f64").to_tokens(tokens),
             ConstrainedPrimative::Bytes(n) | ConstrainedPrimative::Str(n) => ArrayTokens {
                 language: self.language,
                 node: &LinkedArray::new(ConstrainedPrimative::U8.into(), n as usize),
             }
             .to_tokens(tokens),
+            ConstrainedPrimative::Any(n) => {
+                let n = proc_macro2::Literal::u64_unsuffixed(n);
+                quote! {seedle_extra::any::Any<#n>}.to_tokens(tokens)
+            }
+            ConstrainedPrimative::UnboundedStr | ConstrainedPrimative::UnboundedBytes
+                if matches!(self.language, Language::C) =>
+            {
+                syn::Error::new(
+                    Span::call_site(),
+                    "tstr and bstr must be constrained with .size for the C language",
+                )
+                .to_compile_error()
+                .to_tokens(tokens)
+            }
+            ConstrainedPrimative::UnboundedAny if matches!(self.language, Language::C) => {
+                syn::Error::new(
+                    Span::call_site(),
+                    "any must be constrained with .size for the C language",
+                )
+                .to_compile_error()
+                .to_tokens(tokens)
+            }
+            ConstrainedPrimative::UnboundedStr => quote! {String}.to_tokens(tokens),
+            ConstrainedPrimative::UnboundedBytes => quote! {Vec<u8>}.to_tokens(tokens),
+            ConstrainedPrimative::UnboundedAny => {
+                quote! {seedle_extra::any::AnyBuf}.to_tokens(tokens)
+            }
         };
     }
 }
@@ -394,7 +848,14 @@ impl<'a> ToTokens for ArrayTokens<'a> {
             node: self.node.ty.as_ref(),
         };
         let len = proc_macro2::Literal::usize_unsuffixed(self.node.len);
-        quote! {[ #node; #len ]}.to_tokens(tokens);
+        match self.node.is_fixed() {
+            true => quote! {[ #node; #len ]},
+            false => {
+                let min = proc_macro2::Literal::usize_unsuffixed(self.node.min);
+                quote! {seedle_extra::array::BoundedArray<#node, #min, #len>}
+            }
+        }
+        .to_tokens(tokens);
     }
 }
 
@@ -402,6 +863,8 @@ pub struct DefaultImpl<'a> {
     ident: &'a syn::Ident,
     fields: &'a Fields,
     language: Language,
+    rules: &'a Rules<LinkedNode>,
+    boxed: &'a BTreeSet<String>,
 }
 impl<'a> ToTokens for DefaultImpl<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
@@ -410,32 +873,24 @@ impl<'a> ToTokens for DefaultImpl<'a> {
             .fields
             .members
             .iter()
-            .map(|LinkedKeyVal(key, node)| {
-                let key = quote::format_ident!("{}", self.language.fieldify(key));
-                let default_impl = match node {
-                    LinkedNode::Primative(ConstrainedPrimative::Str(n)) => {
-                        let init = proc_macro2::Literal::u8_unsuffixed(0);
-                        let len = proc_macro2::Literal::u64_unsuffixed(*n);
-                        quote! {[ #init; #len ]}
-                    }
-                    LinkedNode::Array(LinkedArray { ty, len }) => match ty.as_ref() {
-                        LinkedNode::Primative(ConstrainedPrimative::U8)
-                        | LinkedNode::Primative(ConstrainedPrimative::U16)
-                        | LinkedNode::Primative(ConstrainedPrimative::U32)
-                        | LinkedNode::Primative(ConstrainedPrimative::U64)
-                        | LinkedNode::Primative(ConstrainedPrimative::I8)
-                        | LinkedNode::Primative(ConstrainedPrimative::I16)
-                        | LinkedNode::Primative(ConstrainedPrimative::I32)
-                        | LinkedNode::Primative(ConstrainedPrimative::I64) => {
-                            let init = proc_macro2::Literal::u8_unsuffixed(0);
-                            let len = proc_macro2::Literal::u64_unsuffixed(*len as u64);
-                            quote! {[ #init; #len ]}
+            .map(|LinkedKeyVal { key, node, optional, default, .. }| {
+                let boxed = self.boxed.contains(key);
+                let key = quote::format_ident!("{}", key.to_snake_case());
+                match optional {
+                    true => quote! {#key: Default::default()},
+                    false => {
+                        let default_impl = FieldDefaultTokens {
+                            node,
+                            default: default.as_ref(),
+                            language: self.language,
+                            rules: self.rules,
+                        };
+                        match boxed {
+                            true => quote! {#key: Box::new(#default_impl)},
+                            false => quote! {#key: #default_impl},
                         }
-                        _ => quote! {[Default::default(); #len]},
-                    },
-                    _ => quote! {Default::default()},
-                };
-                quote! {#key: #default_impl}
+                    }
+                }
             })
             .collect::<Vec<TokenStream>>();
         quote! {
@@ -451,10 +906,464 @@ impl<'a> ToTokens for DefaultImpl<'a> {
     }
 }
 
-struct CApiImpl<'a> {
+/// Check every member against the constraints of its type, and the members holding a struct
+/// against the constraints of the struct. The C API returns the position of the first invalid
+/// member starting at 1, or 0 when every member is valid
+pub(crate) struct ValidateImpl<'a> {
+    ident: &'a syn::Ident,
+    fields: &'a Fields,
+    language: Language,
+}
+impl<'a> ToTokens for ValidateImpl<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        use quote::format_ident;
+        let ident = &self.ident;
+        let checks = self
+            .fields
+            .members
+            .iter()
+            .enumerate()
+            .filter_map(|(n, LinkedKeyVal { key, node, optional, .. })| {
+                let check = ValidTokens::new(node)?;
+                let member = format_ident!("{}", key.to_snake_case());
+                let valid = match (self.language, optional) {
+                    (_, false) => quote! {{ let value = &self.#member; #check }},
+                    (Language::C, true) => quote! {self.#member.get().map_or(true, |value| #check)},
+                    (_, true) => quote! {self.#member.as_ref().map_or(true, |value| #check)},
+                };
+                let n = proc_macro2::Literal::usize_unsuffixed(n);
+                let key = proc_macro2::Literal::string(key);
+                Some(quote! {
+                    if !#valid {
+                        return Err(seedle_extra::validate::Invalid::new(#n, #key));
+                    }
+                })
+            });
+        let ffi = match self.language {
+            Language::Rust => quote! {},
+            lang => {
+                let name = ident.to_string();
+                let func = format_ident!("{}", lang.functionify(&name));
+                let (validate, attrs) = method_attrs(lang, format_ident!("validate_{}", func));
+                quote! {
+                    #attrs
+                    fn #validate(src: &#ident) -> i32 {
+                        seedle_extra::validate::error_code(src.validate())
+                    }
+                }
+            }
+        };
+        quote! {
+            impl #ident {
+                pub fn validate(&self) -> Result<(), seedle_extra::validate::Invalid> {
+                    #(#checks)*
+                    Ok(())
+                }
+            }
+            #ffi
+        }
+        .to_tokens(tokens)
+    }
+}
+
+/// minicbor names the decoder of a member with a path, and a pattern can not be passed in a path,
+/// so each member holding text that must match a pattern is given a decoder of its own by the
+/// type holding it. IE: Self::decode_hostname
+pub(crate) struct RegexpImpl<'a> {
+    pub(crate) ident: &'a syn::Ident,
+    pub(crate) members: &'a [LinkedKeyVal],
+}
+impl<'a> ToTokens for RegexpImpl<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ident = &self.ident;
+        let decoders = self
+            .members
+            .iter()
+            .filter_map(|LinkedKeyVal { key, node, .. }| match node {
+                LinkedNode::Regexp(regexp) => Some((regexp_decoder(key), regexp)),
+                _ => None,
+            })
+            .map(|(decoder, LinkedRegexp { ty, pattern })| {
+                let pattern = proc_macro2::Literal::string(pattern);
+                let (codec, bound) = match ty {
+                    ConstrainedPrimative::Str(_) => (
+                        quote! {seedle_extra::regexp::bytes},
+                        quote! {minicbor::bytes::DecodeBytes<'b, Ctx>},
+                    ),
+                    _ => (
+                        quote! {seedle_extra::regexp},
+                        quote! {minicbor::Decode<'b, Ctx>},
+                    ),
+                };
+                quote! {
+                    fn #decoder<'b, Ctx, T>(
+                        d: &mut minicbor::Decoder<'b>,
+                        ctx: &mut Ctx,
+                    ) -> Result<T, minicbor::decode::Error>
+                    where
+                        T: #bound + seedle_extra::regexp::Matches,
+                    {
+                        #codec::decode(d, ctx, #pattern)
+                    }
+                }
+            })
+            .collect::<Vec<TokenStream>>();
+        if !decoders.is_empty() {
+            quote! {
+                impl #ident {
+                    #(#decoders)*
+                }
+            }
+            .to_tokens(tokens)
+        }
+    }
+}
+
+/// Accessors of the named bits of the members holding them, so that the bits are read and
+/// written by name. IE: status_ready and set_status_ready for status: uint .size 1 .bits flags.
+/// The C API is given the same accessors as functions. Optional members are left to their value
+pub(crate) struct BitsImpl<'a> {
     ident: &'a syn::Ident,
+    fields: &'a Fields,
     language: Language,
 }
+impl<'a> ToTokens for BitsImpl<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        use quote::format_ident;
+        let ident = &self.ident;
+        let func = format_ident!("{}", self.language.functionify(&ident.to_string()));
+        let (methods, ffi): (Vec<TokenStream>, Vec<TokenStream>) = self
+            .fields
+            .members
+            .iter()
+            .filter(|member| !member.is_optional())
+            .filter_map(|member| match member.val() {
+                LinkedNode::Bits(bits) => Some((member.key(), bits)),
+                LinkedNode::Tag(LinkedTag { ty, .. }) => match ty.as_ref() {
+                    LinkedNode::Bits(bits) => Some((member.key(), bits)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .flat_map(|(key, bits)| bits.bits.iter().map(move |bit| (key, bit)))
+            .map(|(key, Bit { name, position })| {
+                let member = format_ident!("{}", key.to_snake_case());
+                let name = format!("{}_{}", key.to_snake_case(), name.to_snake_case());
+                let get = format_ident!("{}", name);
+                let set = format_ident!("set_{}", name);
+                let position = proc_macro2::Literal::u32_unsuffixed(*position);
+                let method = quote! {
+                    pub fn #get(&self) -> bool {
+                        self.#member & (1 << #position) != 0
+                    }
+
+                    pub fn #set(&mut self, on: bool) {
+                        match on {
+                            true => self.#member |= 1 << #position,
+                            false => self.#member &= !(1 << #position),
+                        }
+                    }
+                };
+                let ffi = match self.language {
+                    Language::Rust => quote! {},
+                    lang => {
+                        let (get_ffi, get_attrs) =
+                            method_attrs(lang, format_ident!("get_{}_{}", func, name));
+                        let (set_ffi, set_attrs) =
+                            method_attrs(lang, format_ident!("set_{}_{}", func, name));
+                        quote! {
+                            #get_attrs
+                            fn #get_ffi(src: &#ident) -> bool {
+                                src.#get()
+                            }
+
+                            #set_attrs
+                            fn #set_ffi(dst: &mut #ident, on: bool) {
+                                dst.#set(on)
+                            }
+                        }
+                    }
+                };
+                (method, ffi)
+            })
+            .unzip();
+        if !methods.is_empty() {
+            quote! {
+                impl #ident {
+                    #(#methods)*
+                }
+                #(#ffi)*
+            }
+            .to_tokens(tokens)
+        }
+    }
+}
+
+/// An expression that is true when the value of a node satisfies its constraints. IE:
+/// (*value <= 100) for percent: uint .size 1 .le 100
+struct ValidTokens(TokenStream);
+impl ValidTokens {
+    /// None when the node has nothing to check
+    fn new(node: &LinkedNode) -> Option<ValidTokens> {
+        match node {
+            LinkedNode::Constrained(Constrained { ty, constraints }) => {
+                let checks = constraints.iter().map(|Constraint { op, value }| {
+                    let n = match value {
+                        Literal::Int(i) => *i as i128,
+                        Literal::UInt(u) => *u as i128,
+                        _ => 0,
+                    };
+                    let n = NumberTokens { ty: *ty, n };
+                    match op {
+                        Comparison::Lt => quote! {*value < #n},
+                        Comparison::Le => quote! {*value <= #n},
+                        Comparison::Gt => quote! {*value > #n},
+                        Comparison::Ge => quote! {*value >= #n},
+                        Comparison::Ne => quote! {*value != #n},
+                    }
+                });
+                Some(ValidTokens(quote! {(#(#checks)&&*)}))
+            }
+            LinkedNode::Tag(LinkedTag { ty, .. }) => ValidTokens::new(ty),
+            LinkedNode::Array(LinkedArray { ty, .. }) => ValidTokens::new(ty)
+                .map(|check| ValidTokens(quote! {value.iter().all(|value| #check)})),
+            LinkedNode::Regexp(LinkedRegexp { pattern, .. }) => {
+                let pattern = proc_macro2::Literal::string(pattern);
+                Some(ValidTokens(quote! {seedle_extra::regexp::Matches::matches(value, #pattern)}))
+            }
+            LinkedNode::ForeignStruct(_) => Some(ValidTokens(quote! {value.validate().is_ok()})),
+            _ => None,
+        }
+    }
+}
+impl ToTokens for ValidTokens {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.0.to_tokens(tokens)
+    }
+}
+
+/// The initializer of a field, the default of the schema if it has one. IE: port: u16 .default 80
+/// or mode: mode .default "dhcp", which is the variant of the enumeration that holds "dhcp"
+struct FieldDefaultTokens<'a> {
+    node: &'a LinkedNode,
+    default: Option<&'a Literal>,
+    language: Language,
+    rules: &'a Rules<LinkedNode>,
+}
+impl<'a> ToTokens for FieldDefaultTokens<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let value = match self.default {
+            Some(value) => value,
+            None => return DefaultTokens { node: self.node }.to_tokens(tokens),
+        };
+        let node = match self.node {
+            LinkedNode::Tag(LinkedTag { ty, .. }) => ty.unconstrained(),
+            node => node.unconstrained(),
+        };
+        let node = node.as_ref();
+        if let LinkedNode::ForeignEnumeration(name) = node {
+            let variant = match self.rules.get(name) {
+                Some(LinkedNode::Enumeration(members)) => Enumeration {
+                    name,
+                    prefix: None,
+                    members: Cow::Borrowed(members),
+                    language: self.language,
+                }
+                .variant(value),
+                _ => None,
+            };
+            let ty = StructTokens {
+                language: self.language,
+                node: name,
+            };
+            return match variant {
+                Some(variant) => quote! {#ty::#variant},
+                None => quote! {#ty::default()},
+            }
+            .to_tokens(tokens);
+        }
+        match value {
+            Literal::Str(_) | Literal::Char(_) => match node {
+                LinkedNode::Primative(ConstrainedPrimative::Str(n)) => {
+                    let mut bytes = literal_text(value).into_bytes();
+                    bytes.resize(*n as usize, 0);
+                    let bytes = proc_macro2::Literal::byte_string(&bytes);
+                    quote! {*#bytes}
+                }
+                _ => {
+                    let text = proc_macro2::Literal::string(&literal_text(value));
+                    quote! {String::from(#text)}
+                }
+            },
+            Literal::Int(_) | Literal::UInt(_) => {
+                let n = match value {
+                    Literal::Int(i) => *i as i128,
+                    Literal::UInt(u) => *u as i128,
+                    _ => 0,
+                };
+                let abs = match node {
+                    LinkedNode::Primative(
+                        ConstrainedPrimative::F16
+                        | ConstrainedPrimative::F32
+                        | ConstrainedPrimative::F64,
+                    ) => proc_macro2::Literal::f64_unsuffixed(n.unsigned_abs() as f64),
+                    _ => proc_macro2::Literal::u128_unsuffixed(n.unsigned_abs()),
+                };
+                match n < 0 {
+                    true => quote! {-#abs},
+                    false => quote! {#abs},
+                }
+            }
+            Literal::Bool(b) => quote! {#b},
+            Literal::Bytes(_) => DefaultTokens { node: self.node }.into_token_stream(),
+        }
+        .to_tokens(tokens)
+    }
+}
+
+fn literal_text(value: &Literal) -> String {
+    match value {
+        Literal::Str(s) => s.clone(),
+        Literal::Char(c) => c.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// The initializer of a single field or variant when rendering a Default impl
+pub(crate) struct DefaultTokens<'a> {
+    pub(crate) node: &'a LinkedNode,
+}
+impl<'a> ToTokens for DefaultTokens<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self.node {
+            LinkedNode::Primative(ConstrainedPrimative::Str(n)) => {
+                let init = proc_macro2::Literal::u8_unsuffixed(0);
+                let len = proc_macro2::Literal::u64_unsuffixed(*n);
+                quote! {[ #init; #len ]}
+            }
+            LinkedNode::Range(range) => RangeMinTokens(range).into_token_stream(),
+            LinkedNode::Constrained(constrained) => InitialTokens(constrained).into_token_stream(),
+            LinkedNode::Regexp(_) | LinkedNode::Bits(_) => {
+                DefaultTokens { node: &self.node.unconstrained() }.into_token_stream()
+            }
+            LinkedNode::Tag(LinkedTag { ty, .. }) => DefaultTokens { node: ty }.into_token_stream(),
+            LinkedNode::Array(array) if !array.is_fixed() => {
+                let init = DefaultTokens { node: &array.ty };
+                quote! {seedle_extra::array::BoundedArray::filled(#init)}
+            }
+            LinkedNode::Array(LinkedArray { ty, len, .. }) => match ty.as_ref() {
+                LinkedNode::Range(range) => {
+                    let init = RangeMinTokens(range);
+                    let len = proc_macro2::Literal::u64_unsuffixed(*len as u64);
+                    quote! {[ #init; #len ]}
+                }
+                LinkedNode::Constrained(constrained) => {
+                    let init = InitialTokens(constrained);
+                    let len = proc_macro2::Literal::u64_unsuffixed(*len as u64);
+                    quote! {[ #init; #len ]}
+                }
+                LinkedNode::Bits(_)
+                | LinkedNode::Primative(ConstrainedPrimative::U8)
+                | LinkedNode::Primative(ConstrainedPrimative::U16)
+                | LinkedNode::Primative(ConstrainedPrimative::U32)
+                | LinkedNode::Primative(ConstrainedPrimative::U64)
+                | LinkedNode::Primative(ConstrainedPrimative::I8)
+                | LinkedNode::Primative(ConstrainedPrimative::I16)
+                | LinkedNode::Primative(ConstrainedPrimative::I32)
+                | LinkedNode::Primative(ConstrainedPrimative::I64) => {
+                    let init = proc_macro2::Literal::u8_unsuffixed(0);
+                    let len = proc_macro2::Literal::u64_unsuffixed(*len as u64);
+                    quote! {[ #init; #len ]}
+                }
+                // Nested arrays repeat the initializer of their inner array. IE: [[0; 4]; 4]
+                LinkedNode::Array(inner) => {
+                    let init = DefaultTokens { node: ty };
+                    let len = proc_macro2::Literal::u64_unsuffixed(*len as u64);
+                    match is_repeated(inner) {
+                        true => quote! {[ #init; #len ]},
+                        false => quote! {core::array::from_fn(|_| #init)},
+                    }
+                }
+                // Arrays of types that are not Copy can not use the repeat expression
+                _ => quote! {core::array::from_fn(|_| Default::default())},
+            },
+            _ => quote! {Default::default()},
+        }
+        .to_tokens(tokens)
+    }
+}
+
+/// True when the initializer of a fixed array is a repeat expression, so that it is Copy
+fn is_repeated(array: &LinkedArray) -> bool {
+    match array.ty.as_ref() {
+        LinkedNode::Array(inner) => is_repeated(inner),
+        LinkedNode::Range(_)
+        | LinkedNode::Constrained(_)
+        | LinkedNode::Bits(_)
+        | LinkedNode::Primative(ConstrainedPrimative::U8)
+        | LinkedNode::Primative(ConstrainedPrimative::U16)
+        | LinkedNode::Primative(ConstrainedPrimative::U32)
+        | LinkedNode::Primative(ConstrainedPrimative::U64)
+        | LinkedNode::Primative(ConstrainedPrimative::I8)
+        | LinkedNode::Primative(ConstrainedPrimative::I16)
+        | LinkedNode::Primative(ConstrainedPrimative::I32)
+        | LinkedNode::Primative(ConstrainedPrimative::I64) => array.is_fixed(),
+        _ => false,
+    }
+}
+
+/// A range defaults to its smallest value because zero may be out of range
+struct RangeMinTokens<'a>(&'a Range);
+impl<'a> ToTokens for RangeMinTokens<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self.0.bounds() {
+            (min, _) if min < 0 => {
+                let abs = proc_macro2::Literal::u128_unsuffixed(min.unsigned_abs());
+                quote! {-#abs}
+            }
+            (min, _) => {
+                let min = proc_macro2::Literal::u128_unsuffixed(min as u128);
+                quote! {#min}
+            }
+        }
+        .to_tokens(tokens)
+    }
+}
+
+/// A constrained number starts with the value closest to zero that satisfies its constraints
+struct InitialTokens<'a>(&'a Constrained);
+impl<'a> ToTokens for InitialTokens<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let n = self.0.initial().unwrap_or_default();
+        NumberTokens { ty: self.0.ty, n }.to_tokens(tokens)
+    }
+}
+
+/// A number literal of a primative. IE: -40 or 0.0
+struct NumberTokens {
+    ty: ConstrainedPrimative,
+    n: i128,
+}
+impl ToTokens for NumberTokens {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let abs = match self.ty {
+            ConstrainedPrimative::F16 | ConstrainedPrimative::F32 | ConstrainedPrimative::F64 => {
+                proc_macro2::Literal::f64_unsuffixed(self.n.unsigned_abs() as f64)
+            }
+            _ => proc_macro2::Literal::u128_unsuffixed(self.n.unsigned_abs()),
+        };
+        match self.n < 0 {
+            true => quote! {-#abs},
+            false => quote! {#abs},
+        }
+        .to_tokens(tokens)
+    }
+}
+
+pub(crate) struct CApiImpl<'a> {
+    pub(crate) ident: &'a syn::Ident,
+    pub(crate) language: Language,
+}
 impl<'a> ToTokens for CApiImpl<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         use quote::format_ident;
@@ -499,11 +1408,12 @@ impl<'a> ToTokens for CApiImpl<'a> {
                 unsafe {
                     let slice = core::slice::from_raw_parts(src, srclen as usize);
                     let mut decoder = minicbor::Decoder::new(slice);
-                    if let Ok(t) = decoder.decode::<#strct>() {
-                        *(dst as *mut #strct) = t;
-                        decoder.position() as i32
-                    } else {
-                        -1
+                    match decoder.decode::<#strct>() {
+                        Ok(t) => {
+                            *(dst as *mut #strct) = t;
+                            decoder.position() as i32
+                        }
+                        Err(e) => seedle_extra::range::error_code(&e),
                     }
                 }
             }
@@ -511,7 +1421,7 @@ impl<'a> ToTokens for CApiImpl<'a> {
             #dec_arr_attrs
             fn #dec_arr (dst: &mut #strct, dstlen: u32, src: *const u8, srclen: u32) -> i32 {
                 seedle_extra::ffi::cbor_dec_slice::<#strct>(dst as *mut #strct as *mut core::ffi::c_void, dstlen, src, srclen)
-                    .unwrap_or(-1)
+                    .unwrap_or_else(|e| seedle_extra::range::error_code(&e))
             }
 
             #len_attrs
@@ -535,35 +1445,30 @@ impl<'a> ToTokens for CApiImpl<'a> {
 struct FromImpl<'a> {
     ident: &'a syn::Ident,
     fields: &'a Fields,
+    language: Language,
+    rules: &'a Rules<LinkedNode>,
+    boxed: &'a BTreeSet<String>,
 }
 impl<'a> ToTokens for FromImpl<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         // NOTE the value in value.#member is hard coded to match the parameter of the from impl
-        use proc_macro2::Literal;
-        let from_partial_fields = self.fields.members.iter().map(|LinkedKeyVal(key, val)| {
+        let from_partial_fields = self.fields.members.iter().map(|LinkedKeyVal { key, node: val, optional, default, .. }| {
             let member = quote::format_ident!("{}", key.to_snake_case());
-            let default_impl = match val {
-                LinkedNode::Primative(ConstrainedPrimative::Str(n)) => {
-                    let size = Literal::u64_unsuffixed(*n);
-                    quote! {[0; #size]}
-                }
-                LinkedNode::Array(LinkedArray { ty, len }) => {
-                    let size = Literal::usize_unsuffixed(*len);
-                    match **ty {
-                        LinkedNode::Primative(ConstrainedPrimative::U8)
-                        | LinkedNode::Primative(ConstrainedPrimative::U16)
-                        | LinkedNode::Primative(ConstrainedPrimative::U32)
-                        | LinkedNode::Primative(ConstrainedPrimative::U64)
-                        | LinkedNode::Primative(ConstrainedPrimative::I8)
-                        | LinkedNode::Primative(ConstrainedPrimative::I16)
-                        | LinkedNode::Primative(ConstrainedPrimative::I32)
-                        | LinkedNode::Primative(ConstrainedPrimative::I64) => quote! {[0; #size]},
-                        _ => quote! {[Default::default(); #size]},
-                    }
-                }
-                _ => quote! {Default::default()},
+            let default_impl = FieldDefaultTokens {
+                node: val,
+                default: default.as_ref(),
+                language: self.language,
+                rules: self.rules,
             };
-            quote! {#member: value.#member.unwrap_or_else(|| #default_impl)}
+            match (optional, self.boxed.contains(key)) {
+                (true, _) => quote! {#member: value.#member},
+                (false, true) => {
+                    quote! {#member: value.#member.unwrap_or_else(|| Box::new(#default_impl))}
+                }
+                (false, false) => {
+                    quote! {#member: value.#member.unwrap_or_else(|| #default_impl)}
+                }
+            }
         });
         let name = self.ident.to_string().to_upper_camel_case();
         let struct_ident = quote::format_ident!("{}", name);
@@ -584,91 +1489,38 @@ impl<'a> ToTokens for FromImpl<'a> {
 struct WasmImpl<'a> {
     ident: &'a syn::Ident,
     fields: &'a Fields,
+    boxed: &'a BTreeSet<String>,
 }
 impl<'a> ToTokens for WasmImpl<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         use proc_macro2::Literal;
-        let fields = self
+        // A member that can not be described to typescript fails the build
+        let fields = match self
             .fields
             .members
             .iter()
-            .map(|LinkedKeyVal(key, val)| {
-                match val {
-                    LinkedNode::Primative(ConstrainedPrimative::U8)
-                    | LinkedNode::Primative(ConstrainedPrimative::I8)
-                    | LinkedNode::Primative(ConstrainedPrimative::U16)
-                    | LinkedNode::Primative(ConstrainedPrimative::I16)
-                    | LinkedNode::Primative(ConstrainedPrimative::U32)
-                    | LinkedNode::Primative(ConstrainedPrimative::I32)
-                    | LinkedNode::Primative(ConstrainedPrimative::U64)
-                    | LinkedNode::Primative(ConstrainedPrimative::I64) => {
-                        quote! {#key: number}
-                    }
-                    LinkedNode::Primative(ConstrainedPrimative::Bool) => {
-                        quote! {#key: boolean}
-                    }
-                    LinkedNode::Primative(ConstrainedPrimative::Str(_len)) => {
-                        quote! {#key: string}
-                    }
-                    LinkedNode::ForeignStruct(s) => {
-                        let ident = quote::format_ident!("Partial{}Props", s.to_upper_camel_case());
-                        quote! {#key: #ident}
-                    }
-                    LinkedNode::Array(LinkedArray { ty, .. }) => match &**ty {
-                        LinkedNode::Primative(ConstrainedPrimative::I8) => {
-                            quote! {#key: Int8Array}
-                        }
-                        LinkedNode::Primative(ConstrainedPrimative::U8) => {
-                            quote! {#key: UInt8Array}
-                        }
-                        LinkedNode::Primative(ConstrainedPrimative::I16) => {
-                            quote! {#key: Int16Array}
-                        }
-                        LinkedNode::Primative(ConstrainedPrimative::U16) => {
-                            quote! {#key: UInt16Array}
-                        }
-                        LinkedNode::Primative(ConstrainedPrimative::I32) => {
-                            quote! {#key: Int32Array}
-                        }
-                        LinkedNode::Primative(ConstrainedPrimative::U32) => {
-                            quote! {#key: UInt32Array}
-                        }
-                        LinkedNode::Primative(ConstrainedPrimative::I64) => {
-                            quote! {#key: Int64Array}
-                        }
-                        LinkedNode::Primative(ConstrainedPrimative::U64) => {
-                            quote! {#key: UInt64Array}
-                        }
-                        LinkedNode::Primative(ConstrainedPrimative::Bool) => {
-                            quote! {#key: boolean[]}
-                        }
-                        LinkedNode::ForeignStruct(s) => {
-                            let ident =
-                                quote::format_ident!("Partial{}Props", s.to_upper_camel_case());
-                            quote! {#key: #ident[]}
-                        }
-                        s => syn::Error::new(
-                            Span::call_site(),
-                            &format!("Unexpected wasm field type {:?}", s),
-                        )
-                        .to_compile_error(),
-                    },
-                    s => syn::Error::new(
-                        Span::call_site(),
-                        &format!("Unexpected wasm field type {:?}", s),
-                    )
-                    .to_compile_error(),
-                }
-                .to_string()
+            .map(|LinkedKeyVal { key, node: val, optional, .. }| {
+                let field = wasm_field(key, &val.unconstrained())?.to_string();
+                Ok(match optional {
+                    true => field.replacen(" :", "?:", 1),
+                    false => field,
+                })
             })
-            .collect::<Vec<String>>()
-            .join(",\n");
+            .collect::<syn::Result<Vec<String>>>()
+        {
+            Ok(fields) => fields.join(",\n"),
+            Err(e) => return e.to_compile_error().to_tokens(tokens),
+        };
 
         let setter_getters = self
             .fields
             .members
             .iter()
-            .map(|LinkedKeyVal(key, val)| WasmSetterGetter::new(key, val));
+            .map(|LinkedKeyVal { key, node: val, optional, .. }| match (optional, self.boxed.contains(key)) {
+                (true, _) => WasmSetterGetter::optional(key),
+                (false, true) => WasmSetterGetter::boxed(key, val),
+                (false, false) => WasmSetterGetter::new(key, val),
+            });
 
         let name = self.ident.to_string().to_upper_camel_case();
         let name_const = self.ident.to_string().to_shouty_snake_case();
@@ -798,6 +1650,139 @@ impl<'a> ToTokens for WasmImpl<'a> {
     }
 }
 
+/// The member of the typescript props of a struct. IE: created: number
+fn wasm_field(key: &str, val: &LinkedNode) -> syn::Result<TokenStream> {
+    let field = match val {
+        LinkedNode::Primative(ConstrainedPrimative::U8)
+        | LinkedNode::Primative(ConstrainedPrimative::I8)
+        | LinkedNode::Primative(ConstrainedPrimative::U16)
+        | LinkedNode::Primative(ConstrainedPrimative::I16)
+        | LinkedNode::Primative(ConstrainedPrimative::U32)
+        | LinkedNode::Primative(ConstrainedPrimative::I32)
+        | LinkedNode::Primative(ConstrainedPrimative::U64)
+        | LinkedNode::Primative(ConstrainedPrimative::I64)
+        | LinkedNode::Primative(ConstrainedPrimative::F16)
+        | LinkedNode::Primative(ConstrainedPrimative::F32)
+        | LinkedNode::Primative(ConstrainedPrimative::F64)
+        | LinkedNode::Range(_) => {
+            quote! {#key: number}
+        }
+        LinkedNode::Primative(ConstrainedPrimative::Bool) => {
+            quote! {#key: boolean}
+        }
+        LinkedNode::Primative(ConstrainedPrimative::Str(_))
+        | LinkedNode::Primative(ConstrainedPrimative::UnboundedStr) => {
+            quote! {#key: string}
+        }
+        // Fixed bytes are serialized as an array of numbers, IE: a UUID
+        LinkedNode::Primative(ConstrainedPrimative::Bytes(_)) => {
+            quote! {#key: number[]}
+        }
+        LinkedNode::Primative(ConstrainedPrimative::UnboundedBytes)
+        | LinkedNode::Primative(ConstrainedPrimative::Any(_))
+        | LinkedNode::Primative(ConstrainedPrimative::UnboundedAny) => {
+            quote! {#key: Uint8Array}
+        }
+        // A tag is written in front of the value it holds, which is described as is
+        LinkedNode::Tag(LinkedTag { ty, .. }) => return wasm_field(key, ty),
+        LinkedNode::ForeignStruct(s) => {
+            let ident = quote::format_ident!("Partial{}Props", s.to_upper_camel_case());
+            quote! {#key: #ident}
+        }
+        LinkedNode::ForeignChoice(s) => {
+            let ident = quote::format_ident!("{}Props", s.to_upper_camel_case());
+            quote! {#key: #ident}
+        }
+        LinkedNode::ForeignEnumeration(s) => {
+            let ident = quote::format_ident!("{}", s.to_upper_camel_case());
+            quote! {#key: #ident}
+        }
+        LinkedNode::Array(array) if !array.is_fixed() => {
+            let ty = ts_type(val).parse::<TokenStream>().unwrap_or_default();
+            quote! {#key: #ty}
+        }
+        LinkedNode::Array(LinkedArray { ty, .. }) => match &**ty {
+            LinkedNode::Primative(ConstrainedPrimative::I8) => {
+                quote! {#key: Int8Array}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::U8) => {
+                quote! {#key: UInt8Array}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::I16) => {
+                quote! {#key: Int16Array}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::U16) => {
+                quote! {#key: UInt16Array}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::I32) => {
+                quote! {#key: Int32Array}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::U32) => {
+                quote! {#key: UInt32Array}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::I64) => {
+                quote! {#key: Int64Array}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::U64) => {
+                quote! {#key: UInt64Array}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::F16)
+            | LinkedNode::Primative(ConstrainedPrimative::F32) => {
+                quote! {#key: Float32Array}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::F64) => {
+                quote! {#key: Float64Array}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::Bool) => {
+                quote! {#key: boolean[]}
+            }
+            LinkedNode::Range(_) => {
+                quote! {#key: number[]}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::Str(_))
+            | LinkedNode::Primative(ConstrainedPrimative::UnboundedStr) => {
+                quote! {#key: string[]}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::Bytes(_)) => {
+                quote! {#key: number[][]}
+            }
+            LinkedNode::Primative(ConstrainedPrimative::UnboundedBytes)
+            | LinkedNode::Primative(ConstrainedPrimative::Any(_))
+            | LinkedNode::Primative(ConstrainedPrimative::UnboundedAny) => {
+                quote! {#key: Uint8Array[]}
+            }
+            LinkedNode::ForeignStruct(s) => {
+                let ident =
+                    quote::format_ident!("Partial{}Props", s.to_upper_camel_case());
+                quote! {#key: #ident[]}
+            }
+            LinkedNode::ForeignChoice(s) => {
+                let ident = quote::format_ident!("{}Props", s.to_upper_camel_case());
+                quote! {#key: #ident[]}
+            }
+            LinkedNode::ForeignEnumeration(s) => {
+                let ident = quote::format_ident!("{}", s.to_upper_camel_case());
+                quote! {#key: #ident[]}
+            }
+            // Nested arrays are converted with serde. IE: number[][]
+            LinkedNode::Array(_) => {
+                let ty = ts_type(val).parse::<TokenStream>().unwrap_or_default();
+                quote! {#key: #ty}
+            }
+            s => {
+                let msg = format!("Unexpected wasm field type {:?}", s);
+                return Err(syn::Error::new(Span::call_site(), msg));
+            }
+        },
+        s => {
+            let msg = format!("Unexpected wasm field type {:?}", s);
+            return Err(syn::Error::new(Span::call_site(), msg));
+        }
+    };
+    Ok(field)
+
+}
+
 macro_rules! wasm_copyable {
     ($name:expr, $ty:ty) => {{
         let member = quote::format_ident!("{}", $name);
@@ -811,6 +1796,19 @@ macro_rules! wasm_copyable {
     }};
 }
 
+macro_rules! wasm_clonable {
+    ($name:expr, $ty:ty) => {{
+        let member = quote::format_ident!("{}", $name);
+        WasmSetterGetter {
+            name: $name,
+            getter: quote! {self.#member.clone()},
+            setter: quote! {self.#member=val},
+            getter_ty: quote! {$ty},
+            setter_ty: quote! {$ty},
+        }
+    }};
+}
+
 macro_rules! wasm_array {
     ($name:expr, $ty:ty, $len:expr) => {{
         let member = quote::format_ident!("{}", $name);
@@ -821,7 +1819,7 @@ macro_rules! wasm_array {
             setter: quote! {
                 let min = core::cmp::min(val.len(), #len);
                 self.#member[0..min].copy_from_slice(&val[0..min]);
-                self.#member[min..].fill(0);
+                self.#member[min..].fill(Default::default());
             },
             getter_ty: quote! {Vec<$ty>},
             setter_ty: quote! {&[$ty]},
@@ -854,6 +1852,20 @@ macro_rules! wasm_struct {
     }};
 }
 
+macro_rules! wasm_boxed {
+    ($name:expr, $other:expr) => {{
+        let member = quote::format_ident!("{}", $name);
+        let other = quote::format_ident!("{}", $other.to_upper_camel_case());
+        WasmSetterGetter {
+            name: $name,
+            getter: quote! {self.#member.as_ref().clone()},
+            setter: quote! {self.#member=Box::new(val)},
+            getter_ty: quote! {#other},
+            setter_ty: quote! {#other},
+        }
+    }};
+}
+
 macro_rules! wasm_str {
     ($name:expr, $len:expr) => {{
         let member = quote::format_ident!("{}", $name);
@@ -889,9 +1901,30 @@ struct WasmSetterGetter<'a> {
     setter_ty: TokenStream,
 }
 impl<'a> WasmSetterGetter<'a> {
+    /// Optional members are passed to and from javascript as undefined or their value
+    fn optional(name: &'a str) -> Self {
+        wasm_struct!(name)
+    }
+
+    /// Members held through a Box are passed to and from javascript as the struct they hold
+    fn boxed(name: &'a str, node: &LinkedNode) -> Self {
+        match node {
+            LinkedNode::Tag(LinkedTag { ty, .. }) => Self::boxed(name, ty),
+            LinkedNode::ForeignStruct(s) => wasm_boxed!(name, s),
+            _ => wasm_struct!(name),
+        }
+    }
+
     // TODO add wasm_clonable and wasm_primative macros and cmoplete the getter/setters
-    fn new(name: &'a str, node: &'a LinkedNode) -> Self {
+    fn new(name: &'a str, node: &LinkedNode) -> Self {
         match node {
+            LinkedNode::Range(Range { ty, .. })
+            | LinkedNode::Constrained(Constrained { ty, .. })
+            | LinkedNode::Regexp(LinkedRegexp { ty, .. })
+            | LinkedNode::Bits(LinkedBits { ty, .. }) => {
+                Self::new(name, &LinkedNode::Primative(*ty))
+            }
+            LinkedNode::Tag(LinkedTag { ty, .. }) => Self::new(name, ty),
             LinkedNode::Primative(ConstrainedPrimative::U8) => wasm_copyable!(name, u8),
             LinkedNode::Primative(ConstrainedPrimative::U16) => wasm_copyable!(name, u16),
             LinkedNode::Primative(ConstrainedPrimative::U32) => wasm_copyable!(name, u32),
@@ -901,9 +1934,33 @@ impl<'a> WasmSetterGetter<'a> {
             LinkedNode::Primative(ConstrainedPrimative::I32) => wasm_copyable!(name, i32),
             LinkedNode::Primative(ConstrainedPrimative::I64) => wasm_copyable!(name, i64),
             LinkedNode::Primative(ConstrainedPrimative::Bool) => wasm_copyable!(name, bool),
+            LinkedNode::Primative(ConstrainedPrimative::F16)
+            | LinkedNode::Primative(ConstrainedPrimative::F32) => wasm_copyable!(name, f32),
+            LinkedNode::Primative(ConstrainedPrimative::F64) => wasm_copyable!(name, f64),
             LinkedNode::Primative(ConstrainedPrimative::Str(n)) => wasm_str!(name, n),
+            LinkedNode::Primative(ConstrainedPrimative::Bytes(n)) => {
+                wasm_array!(name, u8, *n as usize)
+            }
+            LinkedNode::Primative(ConstrainedPrimative::UnboundedStr) => {
+                wasm_clonable!(name, String)
+            }
+            LinkedNode::Primative(ConstrainedPrimative::UnboundedBytes) => {
+                wasm_clonable!(name, Vec<u8>)
+            }
+            LinkedNode::Primative(ConstrainedPrimative::Any(_))
+            | LinkedNode::Primative(ConstrainedPrimative::UnboundedAny) => wasm_struct!(name),
             LinkedNode::ForeignStruct(s) => wasm_struct!(name, s),
-            LinkedNode::Array(LinkedArray { ty, len }) => match &**ty {
+            LinkedNode::ForeignChoice(_s) | LinkedNode::ForeignEnumeration(_s) => {
+                wasm_struct!(name)
+            }
+            LinkedNode::Array(array) if !array.is_fixed() => wasm_struct!(name),
+            LinkedNode::Array(LinkedArray { ty, len, .. }) => match &**ty {
+                LinkedNode::Range(Range { ty, .. })
+                | LinkedNode::Constrained(Constrained { ty, .. })
+                | LinkedNode::Bits(LinkedBits { ty, .. }) => Self::new(
+                    name,
+                    &LinkedArray::new(LinkedNode::Primative(*ty), *len).into(),
+                ),
                 LinkedNode::Primative(ConstrainedPrimative::U8) => wasm_array!(name, u8, *len),
                 LinkedNode::Primative(ConstrainedPrimative::U16) => wasm_array!(name, u16, *len),
                 LinkedNode::Primative(ConstrainedPrimative::U32) => wasm_array!(name, u32, *len),
@@ -913,7 +1970,19 @@ impl<'a> WasmSetterGetter<'a> {
                 LinkedNode::Primative(ConstrainedPrimative::I32) => wasm_array!(name, i32, *len),
                 LinkedNode::Primative(ConstrainedPrimative::I64) => wasm_array!(name, i64, *len),
                 LinkedNode::Primative(ConstrainedPrimative::Bool) => wasm_array!(name, bool, *len),
-                LinkedNode::ForeignStruct(_s) => wasm_struct!(name),
+                LinkedNode::Primative(ConstrainedPrimative::F16)
+                | LinkedNode::Primative(ConstrainedPrimative::F32) => wasm_array!(name, f32, *len),
+                LinkedNode::Primative(ConstrainedPrimative::F64) => wasm_array!(name, f64, *len),
+                LinkedNode::Array(_)
+                | LinkedNode::ForeignStruct(_)
+                | LinkedNode::ForeignChoice(_)
+                | LinkedNode::ForeignEnumeration(_)
+                | LinkedNode::Primative(ConstrainedPrimative::Str(_))
+                | LinkedNode::Primative(ConstrainedPrimative::Bytes(_))
+                | LinkedNode::Primative(ConstrainedPrimative::UnboundedStr)
+                | LinkedNode::Primative(ConstrainedPrimative::UnboundedBytes)
+                | LinkedNode::Primative(ConstrainedPrimative::Any(_))
+                | LinkedNode::Primative(ConstrainedPrimative::UnboundedAny) => wasm_struct!(name),
                 n => panic!("unexpected type {:?} for wasm setter/getter impl", n),
             },
             n => panic!("unexpected type {:?} for wasm setter/getter impl", n),
@@ -0,0 +1,138 @@
+use super::literals::LitToks;
+use super::structs::Struct;
+use super::vtable::VTable;
+use crate::parse::Language;
+use proc_macro2::Span;
+use quote::ToTokens;
+use seedle_parser::{Fields, LinkedNode, Literal};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use syn::{Ident, LitStr};
+
+fn render(name: &str, prefix: Option<&LitStr>, lit: &Literal, language: Language) -> String {
+    LitToks {
+        name,
+        prefix,
+        lit,
+        language,
+    }
+    .into_token_stream()
+    .to_string()
+}
+
+#[test]
+fn literal_typescript_types() {
+    let text = render(
+        "greeting-text",
+        None,
+        &Literal::Str("hello".into()),
+        Language::Typescript,
+    );
+    assert!(text.contains(r#""export type GreetingText = \"hello\"""#));
+    let bytes = render(
+        "magic-bytes",
+        None,
+        &Literal::Bytes(vec![1, 2]),
+        Language::Typescript,
+    );
+    assert!(bytes.contains(r#""export type MagicBytes = Uint8Array""#));
+    let char = render(
+        "literal-char",
+        None,
+        &Literal::Char('C'),
+        Language::Typescript,
+    );
+    assert!(char.contains(r#""export type LiteralChar = \"C\"""#));
+    let number = render(
+        "literal-three",
+        None,
+        &Literal::UInt(3),
+        Language::Typescript,
+    );
+    assert!(number.contains(r#""export type LiteralThree = 3""#));
+}
+
+#[test]
+fn literal_c_getter_prefix() {
+    let lit = Literal::Str("hello".into());
+    let text = render("greeting", None, &lit, Language::C);
+    assert!(text.contains("fn greeting ()"));
+    let prefix = LitStr::new("my", Span::call_site());
+    let text = render("greeting", Some(&prefix), &lit, Language::C);
+    assert!(text.contains("fn my_greeting ()"));
+    // Text of one character is text, with a getter of its own
+    let text = render("initial", None, &Literal::Char('C'), Language::C);
+    assert!(text.contains("pub const INITIAL : [u8 ; 2] = * b\"C\\0\" ;"));
+    assert!(text.contains("fn initial ()"));
+}
+
+#[test]
+fn struct_typescript_tagged_members() {
+    let rules = seedle_parser::parse(
+        r#"
+        event = {
+            created: #6.1(uint .size 8),
+            id: #6.37(bstr .size 16),
+            ? parent: #6.37(bstr .size 16),
+        }
+        "#,
+    )
+    .unwrap();
+    let fields = match &rules["event"] {
+        LinkedNode::Struct(fields) => fields,
+        node => panic!("expected a struct, found {:?}", node),
+    };
+    let text = Struct {
+        name: "event",
+        prefix: None,
+        fields: Cow::Borrowed::<Fields>(fields),
+        language: Language::Typescript,
+        copy: true,
+        record: false,
+        rules: &rules,
+        boxed: Default::default(),
+    }
+    .into_token_stream()
+    .to_string();
+    // Tagged members are described as the type they hold
+    assert!(!text.contains("compile_error"));
+    assert!(text.contains(r#"export type EventProps = {"#));
+    assert!(text.contains(r#"\"created\" : number,"#));
+    assert!(text.contains(r#"\"id\" : number [],"#));
+    assert!(text.contains(r#"\"parent\"?: number []"#));
+}
+
+fn render_vtable(names: &[String], keys: &BTreeMap<String, u8>) -> String {
+    let ident = Ident::new("schema", Span::call_site());
+    VTable {
+        structs: names
+            .iter()
+            .map(|name| (name, Cow::Owned(Fields { members: vec![] })))
+            .collect(),
+        ident: &ident,
+        keys,
+        language: Language::C,
+        prefix: &None,
+    }
+    .into_token_stream()
+    .to_string()
+}
+
+#[test]
+fn vtable_keys() {
+    let names = (0..3).map(|n| format!("s{}", n)).collect::<Vec<String>>();
+    let keys = BTreeMap::from([("s1".to_string(), 0)]);
+    let text = render_vtable(&names, &keys);
+    assert!(text.contains("S0 = 1 , S1 = 0 , S2 = 2"));
+    // A key is a u8
+    let names = (0..257).map(|n| format!("s{}", n)).collect::<Vec<String>>();
+    let text = render_vtable(&names, &BTreeMap::new());
+    assert!(text.contains("compile_error"));
+    assert!(text.contains("at most 256 structs can be keyed, found 257"));
+    // Keys pinned on rules that are not structs leave too few keys for the structs
+    let names = (0..255).map(|n| format!("s{}", n)).collect::<Vec<String>>();
+    let keys = BTreeMap::from([("a".to_string(), 7), ("b".to_string(), 8)]);
+    let text = render_vtable(&names, &keys);
+    assert!(text.contains("compile_error"));
+    assert!(text.contains("struct [s254] has no key"));
+}
@@ -1,10 +1,11 @@
 use crate::parse::Language;
 use crate::print::utils::method_attrs;
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
 use quote::{format_ident, quote};
 use seedle_parser::Fields;
 use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
 use syn::{Ident, LitStr};
 
 /*
@@ -46,32 +47,32 @@ pub fn build(item: Enum, language: Language, oprefix: Option<LitStr>) -> TokenSt
 
         #enc_attrs
         fn #enc(dst: *mut u8, dstlen: u32, key: #key, src: *const core::ffi::c_void) -> i32 {
-            unsafe {(__SEEDLE_VTABLE[key as u8 as usize].encode)(dst, dstlen, &*src)}
+            unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].encode)(dst, dstlen, &*src)}
         }
 
         #enc_arr_attrs
         fn #enc_arr(dst: *mut u8, dstlen: u32, key: #key, src: *const core::ffi::c_void, srclen: u32) -> i32 {
-            unsafe {(__SEEDLE_VTABLE[key as u8 as usize].encode_array)(dst, dstlen, &*src, srclen)}
+            unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].encode_array)(dst, dstlen, &*src, srclen)}
         }
 
         #dec_attrs
         fn #dec (dst: *mut core::ffi::c_void, key: #key, src: *const u8, srclen: u32) -> i32 {
-            unsafe {(__SEEDLE_VTABLE[key as u8 as usize].decode)(&mut *dst, src, srclen)}
+            unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].decode)(&mut *dst, src, srclen)}
         }
 
         #dec_arr_attrs
         fn #dec_arr (dst: *mut core::ffi::c_void, dstlen: u32, key: #key, src: *const u8, srclen: u32) -> i32 {
-            unsafe {(__SEEDLE_VTABLE[key as u8 as usize].decode_array)(&mut *dst, dstlen, src, srclen)}
+            unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].decode_array)(&mut *dst, dstlen, src, srclen)}
         }
 
         #len_attrs
         fn #len(key: #key, src: *const core::ffi::c_void) -> u32 {
-            unsafe {(__SEEDLE_VTABLE[key as u8 as usize].len)(&*src)}
+            unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].len)(&*src)}
         }
 
         #len_arr_attrs
         fn #len_arr(key: #key, src: *const core::ffi::c_void, srclen: u32) -> u32 {
-            unsafe {(__SEEDLE_VTABLE[key as u8 as usize].array_len)(&*src, srclen)}
+            unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].array_len)(&*src, srclen)}
         }
     }
 }
@@ -80,6 +81,8 @@ pub fn build(item: Enum, language: Language, oprefix: Option<LitStr>) -> TokenSt
 pub struct VTable<'i> {
     pub structs: Vec<(&'i String, Cow<'i, Fields>)>,
     pub ident: &'i Ident,
+    /// Key numbers pinned in the schema. Other structs take the lowest free numbers in order
+    pub keys: &'i BTreeMap<String, u8>,
     pub language: Language,
     pub prefix: &'i Option<LitStr>,
 }
@@ -101,10 +104,22 @@ impl<'i> ToTokens for VTable<'i> {
             Language::Typescript => Some(quote! {#[wasm_bindgen]}),
             _ => None,
         };
-        let enumeration = self.structs.iter().enumerate().map(|(idx, (key, _node))| {
-            let name = format_ident!("{}", lang.enumify(key));
-            let idx = proc_macro2::Literal::i8_unsuffixed(idx as i8);
-            quote! {#name = #idx}
+        let names = self
+            .structs
+            .iter()
+            .map(|(key, _)| format_ident!("{}", lang.enumify(key)))
+            .collect::<Vec<Ident>>();
+        let numbers = match self.numbers() {
+            Ok(numbers) => numbers.into_iter().map(proc_macro2::Literal::u8_unsuffixed),
+            Err(e) => return e.to_compile_error().to_tokens(tokens),
+        };
+        let enumeration = names
+            .iter()
+            .zip(numbers)
+            .map(|(name, n)| quote! {#name = #n});
+        let index = names.iter().enumerate().map(|(idx, name)| {
+            let idx = proc_macro2::Literal::usize_unsuffixed(idx);
+            quote! {#key::#name => #idx}
         });
         let (enc, enc_attrs) = method_attrs(lang, format_ident!("{}encode", prefix));
         let (enc_arr, enc_arr_attrs) = method_attrs(lang, format_ident!("{}encode_array", prefix));
@@ -122,40 +137,79 @@ impl<'i> ToTokens for VTable<'i> {
                 #(#enumeration),*
             }
 
+            fn __seedle_vtable_index(key: #key) -> usize {
+                match key {
+                    #(#index),*
+                }
+            }
+
             #enc_attrs
             fn #enc(dst: *mut u8, dstlen: u32, key: #key, src: *const core::ffi::c_void) -> i32 {
-                unsafe {(__SEEDLE_VTABLE[key as u8 as usize].encode)(dst, dstlen, &*src)}
+                unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].encode)(dst, dstlen, &*src)}
             }
 
             #enc_arr_attrs
             fn #enc_arr(dst: *mut u8, dstlen: u32, key: #key, src: *const core::ffi::c_void, srclen: u32) -> i32 {
-                unsafe {(__SEEDLE_VTABLE[key as u8 as usize].encode_array)(dst, dstlen, &*src, srclen)}
+                unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].encode_array)(dst, dstlen, &*src, srclen)}
             }
 
             #dec_attrs
             fn #dec (dst: *mut core::ffi::c_void, key: #key, src: *const u8, srclen: u32) -> i32 {
-                unsafe {(__SEEDLE_VTABLE[key as u8 as usize].decode)(&mut *dst, src, srclen)}
+                unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].decode)(&mut *dst, src, srclen)}
             }
 
             #dec_arr_attrs
             fn #dec_arr (dst: *mut core::ffi::c_void, dstlen: u32, key: #key, src: *const u8, srclen: u32) -> i32 {
-                unsafe {(__SEEDLE_VTABLE[key as u8 as usize].decode_array)(&mut *dst, dstlen, src, srclen)}
+                unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].decode_array)(&mut *dst, dstlen, src, srclen)}
             }
 
             #len_attrs
             fn #len(key: #key, src: *const core::ffi::c_void) -> u32 {
-                unsafe {(__SEEDLE_VTABLE[key as u8 as usize].len)(&*src)}
+                unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].len)(&*src)}
             }
 
             #len_arr_attrs
             fn #len_arr(key: #key, src: *const core::ffi::c_void, srclen: u32) -> u32 {
-                unsafe {(__SEEDLE_VTABLE[key as u8 as usize].array_len)(&*src, srclen)}
+                unsafe {(__SEEDLE_VTABLE[__seedle_vtable_index(key)].array_len)(&*src, srclen)}
             }
         }
         .to_tokens(tokens);
     }
 }
 
+impl<'i> VTable<'i> {
+    /// The key number of each struct, in the order of the structs. A key is a u8, so there can
+    /// be no more structs than the numbers the pinned keys leave free
+    fn numbers(&self) -> syn::Result<Vec<u8>> {
+        if self.structs.len() > 256 {
+            let msg = format!(
+                "at most 256 structs can be keyed, found {}",
+                self.structs.len()
+            );
+            return Err(syn::Error::new(Span::call_site(), msg));
+        }
+        let mut used = self.keys.values().copied().collect::<BTreeSet<u8>>();
+        let mut next = 0..=u8::MAX;
+        self.structs
+            .iter()
+            .map(|(name, _)| match self.keys.get(*name) {
+                Some(n) => Ok(*n),
+                None => {
+                    let n = next.find(|n| !used.contains(n)).ok_or_else(|| {
+                        let msg = format!(
+                            "struct [{}] has no key, the keys left by the pinned keys are taken",
+                            name
+                        );
+                        syn::Error::new(Span::call_site(), msg)
+                    })?;
+                    used.insert(n);
+                    Ok(n)
+                }
+            })
+            .collect()
+    }
+}
+
 struct VTableEntry<'l> {
     entry: Ident,
     language: Language,
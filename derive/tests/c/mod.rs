@@ -31,13 +31,20 @@ fn test_enum() {
     assert_eq!(c::KEY::NETWORK as u8, 0);
     assert_eq!(c::KEY::PORT as u8, 1);
     assert_eq!(c::KEY::THING as u8, 2);
+    assert_eq!(c::KEY::VECTOR as u8, 3);
 }
 
 #[test]
 fn test_literals() {
-    assert_eq!(c::GROUPA_LITERAL_CHAR, 'C');
+    assert_eq!(&c::GROUPA_LITERAL_CHAR, b"C\0");
+    let char = unsafe { std::ffi::CStr::from_ptr(c::groupa_literal_char()) };
+    assert_eq!(char.to_str().unwrap(), "C");
     assert_eq!(c::GROUPA_LITERAL_THREE, 3);
     assert_eq!(c::GROUPB_LITERAL_FOUR, 4);
+    assert_eq!(c::GROUPB_LITERAL_MAGIC, [0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(&c::GROUPB_LITERAL_VENDOR, b"seedle \"labs\"\0");
+    let vendor = unsafe { std::ffi::CStr::from_ptr(c::groupb_literal_vendor()) };
+    assert_eq!(vendor.to_str().unwrap(), "seedle \"labs\"");
 }
 
 #[test]
@@ -95,3 +102,484 @@ fn test_encode_decode_array() {
     assert_eq!(encoder.into_writer().into_inner(), buff[0..cap as usize]);
     assert_eq!(*decoded, netw);
 }
+
+#[test]
+fn test_encode_decode_choice() {
+    let mut buff = [0; 4096];
+    let cmd = c::command::PORT(c::port {
+        http: 80,
+        ..Default::default()
+    });
+    let mut decoded = c::command::default();
+    let cap = c::len_command(&cmd);
+    let ret_encode = c::encode_command(buff.as_mut_ptr(), cap, &cmd);
+    let ret_decode = c::decode_command(&mut decoded, buff.as_ptr(), cap);
+    assert_eq!(cap as i32, ret_encode);
+    assert_eq!(cap as i32, ret_decode);
+    assert_eq!(decoded, cmd);
+    assert_eq!(c::command::default(), c::command::NETWORK(Default::default()));
+    assert_ne!(c::command::U8(8), c::command::PORT(Default::default()));
+}
+
+#[test]
+fn test_encode_map() {
+    // A map without optional or keyed members is an array, and a map with optional members is
+    // keyed by the position of each member
+    let version = c::version { major: 1, minor: 2 };
+    assert_eq!(minicbor::to_vec(version).unwrap(), [0x82, 0x01, 0x02]);
+    let mut release = c::release {
+        major: 1,
+        minor: 2,
+        ..Default::default()
+    };
+    assert_eq!(minicbor::to_vec(release).unwrap(), [0xa2, 0x00, 0x01, 0x01, 0x02]);
+    release.patch = seedle_extra::optional::Optional::some(3);
+    assert_eq!(
+        minicbor::to_vec(release).unwrap(),
+        [0xa3, 0x00, 0x01, 0x01, 0x02, 0x02, 0x03]
+    );
+    let decoded: c::release = minicbor::decode(&[0xa2, 0x01, 0x02, 0x00, 0x01]).unwrap();
+    assert_eq!(decoded, c::release { patch: Default::default(), ..release });
+    let decoded: c::version = minicbor::decode(&[0x82, 0x01, 0x02]).unwrap();
+    assert_eq!(decoded, version);
+}
+
+#[test]
+fn test_encode_decode_discriminated_choice() {
+    // A choice is written as the position of its alternative and its value
+    let ping = c::vendor_reply::VENDOR_PING(c::vendor_ping { seq: 7 });
+    assert_eq!(minicbor::to_vec(&ping).unwrap(), [0x82, 0x00, 0x81, 0x07]);
+    assert_eq!(minicbor::len(&ping), 4);
+    let decoded: c::vendor_reply = minicbor::decode(&[0x82, 0x00, 0x81, 0x07]).unwrap();
+    assert_eq!(decoded, ping);
+    // A pong has the shape of a ping, and is told apart by its position
+    let pong = c::vendor_reply::VENDOR_PONG(c::vendor_pong { ack: 9 });
+    assert_eq!(minicbor::to_vec(&pong).unwrap(), [0x82, 0x01, 0x81, 0x09]);
+    let decoded: c::vendor_reply = minicbor::decode(&minicbor::to_vec(&pong).unwrap()).unwrap();
+    assert_eq!(decoded, pong);
+    // nil is written as null
+    assert_eq!(minicbor::to_vec(c::vendor_reply::NIL).unwrap(), [0x82, 0x02, 0xf6]);
+    assert_eq!(minicbor::len(c::vendor_reply::NIL), 3);
+    let decoded: c::vendor_reply = minicbor::decode(&[0x82, 0x02, 0xf6]).unwrap();
+    assert_eq!(decoded, c::vendor_reply::NIL);
+    assert!(minicbor::decode::<c::vendor_reply>(&[0x82, 0x03, 0xf6]).is_err());
+    assert!(minicbor::decode::<c::vendor_reply>(&[0xf6]).is_err());
+
+    // command = network / port / u8
+    let decoded: c::command = minicbor::decode(&[0x82, 0x02, 0x07]).unwrap();
+    assert_eq!(decoded, c::command::U8(7));
+    assert_eq!(minicbor::to_vec(c::command::U8(7)).unwrap(), [0x82, 0x02, 0x07]);
+
+    // target = hostname / u16
+    let decoded: c::target = minicbor::decode(&[0x82, 0x01, 0x19, 0x01, 0xbb]).unwrap();
+    assert_eq!(decoded, c::target::U16(443));
+    let host = c::target::HOSTNAME(make_byte_str("node"));
+    let bytes = minicbor::to_vec(host).unwrap();
+    assert_eq!(minicbor::decode::<c::target>(&bytes).unwrap(), host);
+    assert!(minicbor::decode::<c::target>(&[0x82, 0x01, 0xf5]).is_err());
+}
+
+#[test]
+fn test_encode_decode_enumeration() {
+    let mut buff = [0; 16];
+    let mut encoder = Encoder::new(buff.as_mut());
+    encoder.encode(c::mode::MODE_STATIC).unwrap();
+    assert_eq!(minicbor::decode::<&str>(&buff).unwrap(), "static");
+    assert_eq!(minicbor::decode::<c::mode>(&buff).unwrap(), c::mode::MODE_STATIC);
+    let mut buff = [0; 16];
+    let mut encoder = Encoder::new(buff.as_mut());
+    encoder.encode(c::level::LEVEL_NEG_1).unwrap();
+    assert_eq!(minicbor::decode::<i64>(&buff).unwrap(), -1);
+    assert_eq!(minicbor::decode::<c::level>(&buff).unwrap(), c::level::LEVEL_NEG_1);
+    let mut buff = [0; 16];
+    let mut encoder = Encoder::new(buff.as_mut());
+    encoder.str("bogus").unwrap();
+    assert!(minicbor::decode::<c::mode>(&buff).is_err());
+    assert_eq!(c::level::LEVEL_NEG_1 as i8, -1);
+    assert_eq!(c::level::LEVEL_1 as i8, 1);
+    assert_eq!(c::mode::default(), c::mode::MODE_DHCP);
+    // The text of each variant is exported as a literal
+    assert_eq!(&c::MODE_STATIC_TEXT, b"static\0");
+    let text = unsafe { std::ffi::CStr::from_ptr(c::mode_static_text()) };
+    assert_eq!(text.to_str().unwrap(), "static");
+}
+
+#[test]
+fn test_decode_range() {
+    let mut buff = [0; 4096];
+    let port = c::port::default();
+    assert_eq!(port.channel, 1);
+    assert_eq!(port.temps, [-40, -40]);
+    let mut decoded = c::port::default();
+    let cap = c::len_port(&port);
+    assert_eq!(cap as i32, c::encode_port(buff.as_mut_ptr(), cap, &port));
+    assert_eq!(cap as i32, c::decode_port(&mut decoded, buff.as_ptr(), cap));
+    assert_eq!(decoded, port);
+    let port = c::port {
+        channel: 15,
+        ..Default::default()
+    };
+    let cap = c::len_port(&port);
+    assert_eq!(cap as i32, c::encode_port(buff.as_mut_ptr(), cap, &port));
+    assert_eq!(-2, c::decode_port(&mut decoded, buff.as_ptr(), cap));
+    let port = c::port {
+        temps: [125, 126],
+        ..Default::default()
+    };
+    let cap = c::len_port(&port);
+    assert_eq!(cap as i32, c::encode_port(buff.as_mut_ptr(), cap, &port));
+    assert!(minicbor::decode::<c::port>(&buff).is_err());
+    assert_eq!(-2, c::decode_port(&mut decoded, buff.as_ptr(), cap));
+}
+
+#[test]
+fn test_encode_decode_optional() {
+    use seedle_extra::optional::Optional;
+    let mut buff = [0; 4096];
+    let mut decoded = c::network::default();
+    let netw = make_netw();
+    assert_eq!(netw.dns, Optional::none());
+    let absent = c::len_network(&netw);
+    assert_eq!(absent as i32, c::encode_network(buff.as_mut_ptr(), absent, &netw));
+    assert_eq!(absent as i32, c::decode_network(&mut decoded, buff.as_ptr(), absent));
+    assert_eq!(decoded, netw);
+    assert_eq!(decoded.mtu.get(), None);
+    let netw = c::network {
+        dns: Optional::some(make_byte_str("8.8.8.8")),
+        mtu: Optional::some(1500),
+        ..make_netw()
+    };
+    let present = c::len_network(&netw);
+    assert!(present > absent);
+    assert_eq!(present as i32, c::encode_network(buff.as_mut_ptr(), present, &netw));
+    assert_eq!(present as i32, c::decode_network(&mut decoded, buff.as_ptr(), present));
+    assert_eq!(decoded, netw);
+    assert_eq!(decoded.mtu.get(), Some(&1500));
+    let port = c::port {
+        fallback: Optional::some(15),
+        ..Default::default()
+    };
+    let cap = c::len_port(&port);
+    assert_eq!(cap as i32, c::encode_port(buff.as_mut_ptr(), cap, &port));
+    let mut decoded = c::port::default();
+    assert_eq!(-2, c::decode_port(&mut decoded, buff.as_ptr(), cap));
+}
+
+#[test]
+fn test_encode_decode_bounded_array() {
+    use seedle_extra::array::BoundedArray;
+    let mut buff = [0; 4096];
+    let mut decoded = c::network::default();
+    let netw = make_netw();
+    assert!(netw.vlans.is_empty());
+    let cap = c::len_network(&netw);
+    assert_eq!(cap as i32, c::encode_network(buff.as_mut_ptr(), cap, &netw));
+    assert_eq!(cap as i32, c::decode_network(&mut decoded, buff.as_ptr(), cap));
+    assert_eq!(decoded, netw);
+    let netw = c::network {
+        vlans: BoundedArray::from_slice(&[1, 2, 3]).unwrap(),
+        ..make_netw()
+    };
+    let grown = c::len_network(&netw);
+    assert_eq!(grown, cap + 3);
+    assert_eq!(grown as i32, c::encode_network(buff.as_mut_ptr(), grown, &netw));
+    assert_eq!(grown as i32, c::decode_network(&mut decoded, buff.as_ptr(), grown));
+    assert_eq!(decoded.vlans.as_slice(), &[1, 2, 3]);
+    let port = c::port::default();
+    assert_eq!(port.key.as_slice(), &[0]);
+    let mut decoded = c::port::default();
+    let port = c::port {
+        key: BoundedArray::new(),
+        ..Default::default()
+    };
+    let cap = c::len_port(&port);
+    assert_eq!(cap as i32, c::encode_port(buff.as_mut_ptr(), cap, &port));
+    assert_eq!(-1, c::decode_port(&mut decoded, buff.as_ptr(), cap));
+}
+
+#[test]
+fn test_encode_decode_float() {
+    use seedle_extra::optional::Optional;
+    let mut buff = [0; 4096];
+    let mut decoded = c::port::default();
+    let port = c::port {
+        gain: 1.5,
+        drift: -0.125,
+        calib: [0.5, -2.0],
+        ..Default::default()
+    };
+    let absent = c::len_port(&port);
+    assert_eq!(absent as i32, c::encode_port(buff.as_mut_ptr(), absent, &port));
+    assert_eq!(absent as i32, c::decode_port(&mut decoded, buff.as_ptr(), absent));
+    assert_eq!(decoded, port);
+    let port = c::port {
+        offset: Optional::some(0.25),
+        ..port
+    };
+    // A float16 is encoded in 3 bytes, plus 1 byte for its key
+    let present = c::len_port(&port);
+    assert_eq!(present, absent + 4);
+    assert_eq!(present as i32, c::encode_port(buff.as_mut_ptr(), present, &port));
+    assert_eq!(present as i32, c::decode_port(&mut decoded, buff.as_ptr(), present));
+    assert_eq!(decoded.offset.get(), Some(&0.25));
+}
+
+#[test]
+fn test_encode_decode_integer_key() {
+    use seedle_extra::optional::Optional;
+    let mut buff = [0; 4096];
+    let mut decoded = c::port::default();
+    let port = c::port::default();
+    let absent = c::len_port(&port);
+    let port = c::port {
+        field_32: Optional::some(7),
+        ..port
+    };
+    // The key 32 is encoded in 2 bytes, plus 1 byte for its value
+    let present = c::len_port(&port);
+    assert_eq!(present, absent + 3);
+    assert_eq!(present as i32, c::encode_port(buff.as_mut_ptr(), present, &port));
+    assert_eq!(present as i32, c::decode_port(&mut decoded, buff.as_ptr(), present));
+    assert_eq!(decoded.field_32.get(), Some(&7));
+    assert_eq!(&buff[present as usize - 3..present as usize], &[0x18, 32, 7]);
+}
+
+#[test]
+fn test_encode_decode_record() {
+    use seedle_extra::optional::Optional;
+    let mut buff = [0; 16];
+    let mut decoded = c::vector::default();
+    let vector = c::vector {
+        x: 1,
+        y: -2,
+        ..Default::default()
+    };
+    // Records are arrays of their members, where absent trailing members are skipped
+    let cap = c::len_vector(&vector);
+    assert_eq!(cap as i32, c::encode_vector(buff.as_mut_ptr(), cap, &vector));
+    assert_eq!(&buff[0..cap as usize], &[0x82, 0x01, 0x21]);
+    assert_eq!(cap as i32, c::decode_vector(&mut decoded, buff.as_ptr(), cap));
+    assert_eq!(decoded, vector);
+    let vector = c::vector {
+        z: Optional::some(3),
+        ..vector
+    };
+    let cap = c::len_vector(&vector);
+    assert_eq!(cap as i32, c::encode_vector(buff.as_mut_ptr(), cap, &vector));
+    assert_eq!(&buff[0..cap as usize], &[0x83, 0x01, 0x21, 0x03]);
+    assert_eq!(cap as i32, c::decode_vector(&mut decoded, buff.as_ptr(), cap));
+    assert_eq!(decoded.z.get(), Some(&3));
+}
+
+#[test]
+fn test_encode_decode_generic() {
+    let mut buff = [0; 16];
+    let mut decoded = c::reading_i16::default();
+    let reading = c::reading_i16 { at: 7, value: -2 };
+    // Each instance of a generic rule is a struct of its own, named after its arguments
+    let cap = c::len(c::KEY::READING_I16, &reading as *const _ as *const c_void);
+    let ret_encode = c::encode(
+        buff.as_mut_ptr(),
+        cap,
+        c::KEY::READING_I16,
+        &reading as *const _ as *const c_void,
+    );
+    assert_eq!(cap as i32, ret_encode);
+    assert_eq!(&buff[0..cap as usize], &[0x82, 0x07, 0x21]);
+    assert_eq!(cap as i32, c::decode_reading_i16(&mut decoded, buff.as_ptr(), cap));
+    assert_eq!(decoded, reading);
+    let samples = c::samples::default();
+    assert_eq!(samples.level, c::reading_u8::default());
+}
+
+#[test]
+fn test_encode_decode_tag() {
+    use seedle_extra::optional::Optional;
+    let mut buff = [0; 64];
+    let mut decoded = c::event::default();
+    let event = c::event {
+        created: 7,
+        id: [0xAA; 16],
+        cause: Optional::some(c::mode::MODE_STATIC),
+    };
+    let cap = c::len_event(&event);
+    assert_eq!(cap as i32, c::encode_event(buff.as_mut_ptr(), cap, &event));
+    // created is wrapped in tag 1, and the id in tag 37
+    assert_eq!(&buff[0..5], &[0xa3, 0x00, 0xc1, 0x07, 0x01]);
+    assert_eq!(&buff[5..8], &[0xd8, 0x25, 0x50]);
+    assert_eq!(cap as i32, c::decode_event(&mut decoded, buff.as_ptr(), cap));
+    assert_eq!(decoded, event);
+    // A value with another tag is rejected
+    buff[2] = 0xc2;
+    assert_eq!(-1, c::decode_event(&mut decoded, buff.as_ptr(), cap));
+    let err = minicbor::decode::<c::event>(&buff[0..cap as usize]).unwrap_err();
+    assert!(err.to_string().contains(seedle_extra::tag::UNEXPECTED_TAG));
+}
+
+#[test]
+fn test_default_value() {
+    let settings = c::settings::default();
+    assert_eq!(settings.port, 80);
+    assert_eq!(settings.name, make_byte_str("node"));
+    assert_eq!(settings.limit, 20);
+    assert!(settings.enabled);
+    assert_eq!(settings.addressing, c::mode::MODE_STATIC);
+}
+
+#[test]
+fn test_validate() {
+    use seedle_extra::optional::Optional;
+    use seedle_extra::validate::{Invalid, VALID};
+    // Members start with the value closest to zero that satisfies their constraints
+    let mut limits = c::limits::default();
+    assert_eq!(limits.ratio, 1.0);
+    assert_eq!(limits.window.high, 1);
+    assert_eq!(limits.validate(), Ok(()));
+    assert_eq!(c::validate_limits(&limits), VALID);
+    limits.percent = 101;
+    assert_eq!(limits.validate(), Err(Invalid::new(0, "percent")));
+    assert_eq!(c::validate_limits(&limits), 1);
+    limits.percent = 100;
+    limits.floor = Optional::some(0);
+    assert_eq!(limits.validate(), Err(Invalid::new(1, "floor")));
+    limits.floor = Optional::some(-40);
+    limits.steps.push(0).unwrap();
+    assert_eq!(limits.validate(), Err(Invalid::new(3, "steps")));
+    assert_eq!(c::validate_limits(&limits), 4);
+    limits.steps.clear();
+    // A member holding a struct is invalid when any member of the struct is
+    limits.window.low = 1000;
+    assert_eq!(limits.validate(), Err(Invalid::new(4, "window")));
+    assert_eq!(c::validate_window(&limits.window), 1);
+}
+
+#[test]
+fn test_encode_decode_regexp() {
+    use seedle_extra::optional::Optional;
+    let mut buff = [0; 64];
+    let mut decoded = c::device::default();
+    let mut device = c::device {
+        host: make_byte_str("node-1.local"),
+        serial: Optional::some(make_byte_str("1234-5678")),
+        ..Default::default()
+    };
+    let cap = c::len_device(&device);
+    assert_eq!(cap as i32, c::encode_device(buff.as_mut_ptr(), cap, &device));
+    assert_eq!(cap as i32, c::decode_device(&mut decoded, buff.as_ptr(), cap));
+    assert_eq!(decoded, device);
+    // Text that does not match its pattern is rejected when it is decoded
+    device.serial = Optional::some(make_byte_str("1234-567x"));
+    assert_eq!(device.validate(), Err(seedle_extra::validate::Invalid::new(1, "serial")));
+    let cap = c::len_device(&device);
+    assert_eq!(cap as i32, c::encode_device(buff.as_mut_ptr(), cap, &device));
+    assert_eq!(-1, c::decode_device(&mut decoded, buff.as_ptr(), cap));
+    let err = minicbor::decode::<c::device>(&buff[0..cap as usize]).unwrap_err();
+    assert!(err.to_string().contains(seedle_extra::regexp::NO_MATCH));
+    // A choice matches the pattern of its variant
+    let target = c::target::HOSTNAME(make_byte_str("Node"));
+    let bytes = minicbor::to_vec(&target).unwrap();
+    assert!(minicbor::decode::<c::target>(&bytes).is_err());
+}
+
+#[test]
+fn test_bits() {
+    let mut device = c::device::default();
+    assert!(device.mode_manual());
+    assert!(!device.mode_locked());
+    device.set_status_ready(true);
+    device.set_status_busy(true);
+    assert_eq!(device.status, 0b1000_0001);
+    assert!(device.status_busy());
+    assert!(!device.status_fault());
+    device.set_status_ready(false);
+    assert_eq!(device.status, 0b1000_0000);
+    c::set_device_mode_locked(&mut device, true);
+    assert_eq!(device.mode, 0x8008);
+    assert!(c::get_device_mode_locked(&device));
+}
+
+#[test]
+fn test_encode_decode_nested_array() {
+    let mut buff = [0; 4096];
+    let mut calibration = c::calibration::default();
+    assert_eq!(calibration.gains, [[0; 4]; 4]);
+    assert_eq!(calibration.limits, [[-40; 2]; 2]);
+    calibration.gains[1][2] = -300;
+    calibration.offsets[1][2] = 0.5;
+    calibration.labels[0][1] = make_byte_str("abcd");
+    let cap = c::len_calibration(&calibration);
+    assert_eq!(cap as i32, c::encode_calibration(buff.as_mut_ptr(), cap, &calibration));
+    let mut decoded = c::calibration::default();
+    assert_eq!(cap as i32, c::decode_calibration(&mut decoded, buff.as_ptr(), cap));
+    assert_eq!(decoded, calibration);
+    let calibration = c::calibration {
+        limits: [[0, 0], [0, 126]],
+        ..Default::default()
+    };
+    let cap = c::len_calibration(&calibration);
+    assert_eq!(cap as i32, c::encode_calibration(buff.as_mut_ptr(), cap, &calibration));
+    assert_eq!(-2, c::decode_calibration(&mut decoded, buff.as_ptr(), cap));
+}
+
+#[test]
+fn test_encode_decode_inline_struct() {
+    let mut buff = [0; 4096];
+    let mut gateway = c::gateway::default();
+    gateway.net = c::gateway_net {
+        dhcp: true,
+        ip: make_byte_str("192.168.168.1"),
+    };
+    gateway.routes[1].metric = 7;
+    let cap = c::len(c::KEY::GATEWAY, &gateway as *const c::gateway as *const c_void);
+    assert_eq!(cap as i32, c::encode_gateway(buff.as_mut_ptr(), cap, &gateway));
+    let mut decoded = c::gateway::default();
+    assert_eq!(cap as i32, c::decode_gateway(&mut decoded, buff.as_ptr(), cap));
+    assert_eq!(decoded, gateway);
+    let route = c::gateway_routes {
+        dst: make_byte_str("10.0.0.0"),
+        metric: 1,
+    };
+    let cap = c::len_gateway_routes(&route);
+    let ret = c::encode(
+        buff.as_mut_ptr(),
+        cap,
+        c::KEY::GATEWAY_ROUTES,
+        &route as *const c::gateway_routes as *const c_void,
+    );
+    assert_eq!(cap as i32, ret);
+}
+
+#[test]
+fn test_encode_decode_any_and_nil() {
+    use seedle_extra::any::Any;
+    use seedle_extra::optional::Optional;
+    let mut buff = [0; 4096];
+    let payload = Any::from_value(&(0x42u8, "vendor")).unwrap();
+    let vendor = c::vendor {
+        id: 7,
+        payload,
+        mask: Optional::some(0xf0),
+        gw: Optional::some(make_byte_str("192.168.168.1")),
+        reply: c::vendor_reply::NIL,
+    };
+    let cap = c::len(c::KEY::VENDOR, &vendor as *const c::vendor as *const c_void);
+    assert_eq!(cap as i32, c::encode_vendor(buff.as_mut_ptr(), cap, &vendor));
+    let mut decoded = c::vendor::default();
+    assert_eq!(cap as i32, c::decode_vendor(&mut decoded, buff.as_ptr(), cap));
+    assert_eq!(decoded, vendor);
+    assert_eq!(decoded.payload.to_value::<(u8, &str)>().unwrap(), (0x42, "vendor"));
+
+    // The payload is written as is, and not wrapped in a byte string
+    let mut d = minicbor::Decoder::new(&buff[..cap as usize]);
+    d.map().unwrap();
+    while d.u32().unwrap() != 1 {
+        d.skip().unwrap();
+    }
+    assert_eq!(d.decode::<(u8, &str)>().unwrap(), (0x42, "vendor"));
+
+    // Nullable members default to absent and choices to their first variant
+    let vendor = c::vendor::default();
+    assert_eq!(vendor.gw, Optional::none());
+    assert_eq!(vendor.reply, c::vendor_reply::VENDOR_PING(Default::default()));
+    assert_eq!(vendor.payload.as_slice(), [0xf6]);
+}
@@ -1,7 +1,14 @@
 mod c;
+mod rs;
 #[test]
 fn test_derive(){
     let t = trybuild::TestCases::new();
     t.pass("examples/c.rs");
     t.pass("examples/ts.rs");
+    t.pass("examples/rs.rs");
+    t.pass("examples/unbounded_rs.rs");
+    t.pass("examples/unbounded_ts.rs");
+    t.compile_fail("tests/ui/errors.rs");
+    t.compile_fail("tests/ui/warnings.rs");
+    t.compile_fail("tests/ui/recursive.rs");
 }
@@ -0,0 +1,168 @@
+use seedle_derive::seedle;
+
+#[seedle(file = "examples/unbounded.cddl", language = "rust")]
+pub mod rs {}
+
+#[test]
+fn test_encode_decode_unbounded() {
+    let note = rs::Note {
+        title: "hello".to_string(),
+        body: vec![0xde, 0xad, 0xbe, 0xef],
+        tags: ["a".to_string(), "much longer tag".to_string()],
+        author: Some("seedle".to_string()),
+        ..Default::default()
+    };
+    let cbor = minicbor::to_vec(&note).unwrap();
+    assert_eq!(cbor.len(), minicbor::len(&note));
+    let decoded: rs::Note = minicbor::decode(&cbor).unwrap();
+    assert_eq!(decoded.title, "hello");
+    assert_eq!(decoded.body, vec![0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(decoded.tags[1], "much longer tag");
+    assert_eq!(decoded.author.as_deref(), Some("seedle"));
+    assert_eq!(decoded.signature, None);
+
+    // The body is a byte string and not an array of integers
+    let mut d = minicbor::Decoder::new(&cbor);
+    d.map().unwrap();
+    while d.u32().unwrap() != 1 {
+        d.skip().unwrap();
+    }
+    assert_eq!(d.bytes().unwrap(), &[0xde, 0xad, 0xbe, 0xef]);
+}
+
+#[test]
+fn test_json_unbounded() {
+    let reply = rs::Post::Reply(rs::Reply {
+        text: "thanks".to_string(),
+        to: 3,
+    });
+    let json = serde_json::to_string(&reply).unwrap();
+    let decoded: rs::Post = serde_json::from_str(&json).unwrap();
+    match decoded {
+        rs::Post::Reply(reply) => assert_eq!(reply.text, "thanks"),
+        _ => panic!("expected a reply"),
+    }
+}
+
+#[test]
+fn test_encode_decode_discriminated_choice_unbounded() {
+    // post = note / reply, a reply = { text: "thanks", to: 3 } is written after its position
+    let bytes = [0x82, 0x01, 0x82, 0x66, b't', b'h', b'a', b'n', b'k', b's', 0x03];
+    let decoded: rs::Post = minicbor::decode(&bytes).unwrap();
+    match &decoded {
+        rs::Post::Reply(reply) => assert_eq!((reply.text.as_str(), reply.to), ("thanks", 3)),
+        _ => panic!("expected a reply"),
+    }
+    assert_eq!(minicbor::to_vec(&decoded).unwrap(), bytes);
+    assert_eq!(minicbor::len(&decoded), bytes.len());
+}
+
+#[test]
+fn test_encode_decode_enumeration_unbounded() {
+    // quota = 0 / 18446744073709551615, the largest value is not read as -1
+    let quota = rs::Quota::Quota18446744073709551615;
+    assert_eq!(quota as u64, u64::MAX);
+    let cbor = minicbor::to_vec(quota).unwrap();
+    assert_eq!(minicbor::decode::<u64>(&cbor).unwrap(), u64::MAX);
+    assert_eq!(minicbor::decode::<rs::Quota>(&cbor).unwrap(), quota);
+    let json = serde_json::to_string(&quota).unwrap();
+    assert_eq!(json, "18446744073709551615");
+    assert_eq!(serde_json::from_str::<rs::Quota>(&json).unwrap(), quota);
+}
+
+#[test]
+fn test_encode_decode_bounded_array_unbounded() {
+    // names = { list: [0*4 tstr] }, a bounded array of text that is not Copy
+    let mut names = rs::Names::default();
+    names.list.push("ada".to_string()).unwrap();
+    names.list.push("grace".to_string()).unwrap();
+    let cbor = minicbor::to_vec(&names).unwrap();
+    assert_eq!(cbor.len(), minicbor::len(&names));
+    let decoded: rs::Names = minicbor::decode(&cbor).unwrap();
+    assert_eq!(decoded.list.as_slice(), ["ada", "grace"]);
+    let json = serde_json::to_string(&names).unwrap();
+    let decoded: rs::Names = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.list, names.list);
+}
+
+#[test]
+fn test_decode_regexp_unbounded() {
+    let mut profile = rs::Profile {
+        handle: "@seedle".to_string(),
+        site: Some("https://example.com".to_string()),
+    };
+    let cbor = minicbor::to_vec(&profile).unwrap();
+    let decoded: rs::Profile = minicbor::decode(&cbor).unwrap();
+    assert_eq!(decoded.handle, "@seedle");
+    assert_eq!(decoded.site.as_deref(), Some("https://example.com"));
+    profile.site = Some("ftp://example.com".to_string());
+    assert!(profile.validate().is_err());
+    let cbor = minicbor::to_vec(&profile).unwrap();
+    let err = minicbor::decode::<rs::Profile>(&cbor).unwrap_err();
+    assert!(err.to_string().contains(seedle_extra::regexp::NO_MATCH));
+    profile.site = None;
+    let cbor = minicbor::to_vec(&profile).unwrap();
+    assert!(minicbor::decode::<rs::Profile>(&cbor).is_ok());
+}
+
+#[test]
+fn test_encode_decode_any_unbounded() {
+    use seedle_extra::any::AnyBuf;
+    let forward = rs::Forward {
+        to: 3,
+        payload: AnyBuf::from_value(&["a", "b", "c"]),
+        via: Some("relay".to_string()),
+    };
+    let cbor = minicbor::to_vec(&forward).unwrap();
+    assert_eq!(cbor.len(), minicbor::len(&forward));
+    let decoded: rs::Forward = minicbor::decode(&cbor).unwrap();
+    assert_eq!(decoded.payload, forward.payload);
+    assert_eq!(decoded.payload.to_value::<[&str; 3]>().unwrap(), ["a", "b", "c"]);
+    assert_eq!(decoded.via.as_deref(), Some("relay"));
+    let json = serde_json::to_string(&forward).unwrap();
+    let decoded: rs::Forward = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.payload, forward.payload);
+}
+
+#[test]
+fn test_literals_unbounded() {
+    assert_eq!(rs::Greeting, "hello world");
+}
+
+#[test]
+fn test_encode_decode_recursive_unbounded() {
+    // branch = { left: tree, right: tree, ? parent: branch }, members that hold the branch
+    // itself are boxed
+    let leaf = |value| Box::new(rs::Tree::Leaf(rs::Leaf { value }));
+    let inner = rs::Branch {
+        left: leaf(1),
+        right: leaf(2),
+        parent: None,
+    };
+    let outer = rs::Branch {
+        left: leaf(3),
+        right: Box::new(rs::Tree::Branch(inner)),
+        parent: Some(Box::new(rs::Branch::default())),
+    };
+    let cbor = minicbor::to_vec(&outer).unwrap();
+    assert_eq!(cbor.len(), minicbor::len(&outer));
+    let decoded: rs::Branch = minicbor::decode(&cbor).unwrap();
+    match (decoded.left.as_ref(), decoded.right.as_ref()) {
+        (rs::Tree::Leaf(leaf), rs::Tree::Branch(inner)) => {
+            assert_eq!(leaf.value, 3);
+            assert!(matches!(
+                inner.right.as_ref(),
+                rs::Tree::Leaf(rs::Leaf { value: 2 })
+            ));
+            assert!(inner.parent.is_none());
+        }
+        _ => panic!("expected a leaf and a branch"),
+    }
+    assert!(decoded.parent.is_some());
+    let json = serde_json::to_string(&outer).unwrap();
+    let decoded: rs::Branch = serde_json::from_str(&json).unwrap();
+    assert!(matches!(
+        decoded.left.as_ref(),
+        rs::Tree::Leaf(rs::Leaf { value: 3 })
+    ));
+}
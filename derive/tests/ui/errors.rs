@@ -0,0 +1,6 @@
+use seedle_derive::seedle;
+
+#[seedle(file = "tests/ui/errors.cddl", language = "rust")]
+pub mod errors {}
+
+fn main() {}
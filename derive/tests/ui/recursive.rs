@@ -0,0 +1,6 @@
+use seedle_derive::seedle;
+
+#[seedle(file = "tests/ui/recursive.cddl", language = "c")]
+pub mod recursive {}
+
+fn main() {}
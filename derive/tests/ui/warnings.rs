@@ -0,0 +1,7 @@
+#![deny(deprecated)]
+use seedle_derive::seedle;
+
+#[seedle(file = "tests/ui/warnings.cddl", language = "rust")]
+pub mod warnings {}
+
+fn main() {}
@@ -0,0 +1,289 @@
+use core::fmt;
+use minicbor::decode::{self, Decode, Decoder};
+use minicbor::encode::{self, CborLen, Encode, Encoder, Write};
+
+/// The message of a decode error caused by an item larger than the size of its CDDL type
+pub const TOO_LARGE: &str = "item larger than its size";
+
+/// An any holds the CBOR null until it is given an item
+const NULL: u8 = 0xf6;
+
+/// A C compatible buffer holding a single CBOR item of at most N bytes. IE: any .size 64
+/// IE: struct { size_t len; uint8_t bytes[N]; }
+///
+/// The item is kept encoded, it is written as is when encoded and copied as is when decoded, so
+/// that payloads which are not modelled by the schema may be forwarded.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Any<const N: usize> {
+    pub len: usize,
+    pub bytes: [u8; N],
+}
+
+impl<const N: usize> Any<N> {
+    /// None when the item is larger than N bytes. The item must be a single well formed CBOR
+    /// item, which is not checked
+    pub fn from_slice(item: &[u8]) -> Option<Self> {
+        match item.len() <= N {
+            true => {
+                let mut any = Any {
+                    len: item.len(),
+                    bytes: [0; N],
+                };
+                any.bytes[..item.len()].copy_from_slice(item);
+                Some(any)
+            }
+            false => None,
+        }
+    }
+
+    /// The item of an encoded value, None when the value is larger than N bytes
+    pub fn from_value<T: Encode<()> + CborLen<()>>(value: &T) -> Option<Self> {
+        let len = minicbor::len(value);
+        let mut any = Any { len, bytes: [0; N] };
+        match len <= N {
+            true => minicbor::encode(value, any.bytes.as_mut())
+                .ok()
+                .map(|_| any),
+            false => None,
+        }
+    }
+
+    /// Decode the item as a value
+    pub fn to_value<'b, T: Decode<'b, ()>>(&'b self) -> Result<T, decode::Error> {
+        minicbor::decode(self.as_slice())
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..core::cmp::min(self.len, N)]
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize> Default for Any<N> {
+    fn default() -> Self {
+        Self::from_slice(&[NULL]).unwrap_or(Any {
+            len: 0,
+            bytes: [0; N],
+        })
+    }
+}
+
+impl<const N: usize> PartialEq for Any<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> fmt::Debug for Any<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl<C, const N: usize> Encode<C> for Any<N> {
+    fn encode<W: Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _: &mut C,
+    ) -> Result<(), encode::Error<W::Error>> {
+        write_item(e, self.as_slice())
+    }
+}
+
+impl<'b, C, const N: usize> Decode<'b, C> for Any<N> {
+    fn decode(d: &mut Decoder<'b>, _: &mut C) -> Result<Self, decode::Error> {
+        let p = d.position();
+        let item = read_item(d)?;
+        Self::from_slice(item).ok_or_else(|| decode::Error::message(TOO_LARGE).at(p))
+    }
+}
+
+impl<C, const N: usize> CborLen<C> for Any<N> {
+    fn cbor_len(&self, _: &mut C) -> usize {
+        self.as_slice().len()
+    }
+}
+
+/// A single CBOR item of any size, kept encoded. IE: any
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnyBuf(pub Vec<u8>);
+
+#[cfg(feature = "alloc")]
+impl AnyBuf {
+    /// The item of an encoded value
+    pub fn from_value<T: Encode<()>>(value: &T) -> Self {
+        AnyBuf(minicbor::to_vec(value).unwrap_or_else(|_| vec![NULL]))
+    }
+
+    /// Decode the item as a value
+    pub fn to_value<'b, T: Decode<'b, ()>>(&'b self) -> Result<T, decode::Error> {
+        minicbor::decode(&self.0)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Default for AnyBuf {
+    fn default() -> Self {
+        AnyBuf(vec![NULL])
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C> Encode<C> for AnyBuf {
+    fn encode<W: Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _: &mut C,
+    ) -> Result<(), encode::Error<W::Error>> {
+        write_item(e, &self.0)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'b, C> Decode<'b, C> for AnyBuf {
+    fn decode(d: &mut Decoder<'b>, _: &mut C) -> Result<Self, decode::Error> {
+        read_item(d).map(|item| AnyBuf(item.to_vec()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C> CborLen<C> for AnyBuf {
+    fn cbor_len(&self, _: &mut C) -> usize {
+        self.0.len()
+    }
+}
+
+fn write_item<W: Write>(e: &mut Encoder<W>, item: &[u8]) -> Result<(), encode::Error<W::Error>> {
+    e.writer_mut().write_all(item).map_err(encode::Error::write)
+}
+
+/// The encoded bytes of the item at the position of the decoder
+fn read_item<'b>(d: &mut Decoder<'b>) -> Result<&'b [u8], decode::Error> {
+    let start = d.position();
+    d.skip()?;
+    Ok(&d.input()[start..d.position()])
+}
+
+/// The item is serialized as the bytes of its encoding
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Any<N> {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_bytes(self.as_slice())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for Any<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        struct Visitor<const N: usize>;
+        impl<'de, const N: usize> serde::de::Visitor<'de> for Visitor<N> {
+            type Value = Any<N>;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "the bytes of a CBOR item of at most {} bytes", N)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Any<N>, E> {
+                Any::from_slice(v).ok_or_else(|| E::custom(TOO_LARGE))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Any<N>, A::Error> {
+                let mut any = Any {
+                    len: 0,
+                    bytes: [0; N],
+                };
+                while let Some(byte) = seq.next_element()? {
+                    match any.bytes.get_mut(any.len) {
+                        Some(slot) => *slot = byte,
+                        None => return Err(serde::de::Error::custom(TOO_LARGE)),
+                    }
+                    any.len += 1;
+                }
+                Ok(any)
+            }
+        }
+        de.deserialize_bytes(Visitor)
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "alloc"))]
+impl serde::Serialize for AnyBuf {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_bytes(&self.0)
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "alloc"))]
+impl<'de> serde::Deserialize<'de> for AnyBuf {
+    fn deserialize<D: serde::Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = AnyBuf;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "the bytes of a CBOR item")
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<AnyBuf, E> {
+                Ok(AnyBuf(v.to_vec()))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<AnyBuf, A::Error> {
+                let mut item = Vec::new();
+                while let Some(byte) = seq.next_element()? {
+                    item.push(byte);
+                }
+                Ok(AnyBuf(item))
+            }
+        }
+        de.deserialize_bytes(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_any_encode_decode() {
+        let mut buff = [0; 16];
+        let any = Any::<8>::from_value(&(1u8, "ab")).unwrap();
+        assert_eq!(any.as_slice(), [0x82, 0x01, 0x62, b'a', b'b']);
+        minicbor::encode((7u8, any), buff.as_mut()).unwrap();
+        let (n, decoded): (u8, Any<8>) = minicbor::decode(&buff).unwrap();
+        assert_eq!((n, decoded), (7, any));
+        assert_eq!(decoded.to_value::<(u8, &str)>().unwrap(), (1, "ab"));
+        assert_eq!(minicbor::len(any), 5);
+        let err = minicbor::decode::<(u8, Any<4>)>(&buff).unwrap_err();
+        assert!(err.to_string().contains(TOO_LARGE));
+        assert!(Any::<4>::from_value(&(1u8, "ab")).is_none());
+    }
+
+    #[test]
+    fn test_any_default() {
+        let any = Any::<4>::default();
+        assert_eq!(any.as_slice(), [NULL]);
+        assert_eq!(any.to_value::<Option<u8>>().unwrap(), None);
+        assert!(Any::<0>::default().as_slice().is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_any_buf_encode_decode() {
+        let any = AnyBuf::from_value(&[1u16, 2, 300]);
+        let encoded = minicbor::to_vec((any.clone(), true)).unwrap();
+        let (decoded, flag): (AnyBuf, bool) = minicbor::decode(&encoded).unwrap();
+        assert_eq!((decoded, flag), (any.clone(), true));
+        assert_eq!(any.to_value::<[u16; 3]>().unwrap(), [1, 2, 300]);
+        assert_eq!(AnyBuf::default().0, [NULL]);
+    }
+}
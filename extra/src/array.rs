@@ -0,0 +1,322 @@
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use minicbor::bytes::{CborLenBytes, DecodeBytes, EncodeBytes};
+use minicbor::decode::{self, Decode, Decoder};
+use minicbor::encode::{self, CborLen, Encode, Encoder, Write};
+
+/// The message of a decode error caused by an array with too few or too many members
+pub const OUT_OF_BOUNDS: &str = "array length out of bounds";
+
+/// A C compatible array with a fixed capacity that tracks how many members it holds.
+/// IE: struct { size_t len; T items[MAX]; }
+///
+/// Only the first len items are encoded and decoded, the others hold the default of T. Decoding
+/// rejects arrays with less than MIN or more than MAX members.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct BoundedArray<T, const MIN: usize, const MAX: usize> {
+    pub len: usize,
+    pub items: [T; MAX],
+}
+
+impl<T: Default, const MIN: usize, const MAX: usize> BoundedArray<T, MIN, MAX> {
+    pub fn new() -> Self {
+        BoundedArray {
+            len: 0,
+            items: core::array::from_fn(|_| T::default()),
+        }
+    }
+
+    /// The smallest valid array, IE: MIN copies of value
+    pub fn filled(value: T) -> Self
+    where
+        T: Clone,
+    {
+        let mut arr = Self::new();
+        arr.items[0..MIN].fill(value);
+        arr.len = MIN;
+        arr
+    }
+
+    /// None when the slice has less than MIN or more than MAX members
+    pub fn from_slice(slice: &[T]) -> Option<Self>
+    where
+        T: Clone,
+    {
+        match (MIN..=MAX).contains(&slice.len()) {
+            true => {
+                let mut arr = Self::new();
+                arr.items[..slice.len()].clone_from_slice(slice);
+                arr.len = slice.len();
+                Some(arr)
+            }
+            false => None,
+        }
+    }
+
+    /// Returns the value when the array is full
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        match self.len < MAX {
+            true => {
+                self.items[self.len] = value;
+                self.len += 1;
+                Ok(())
+            }
+            false => Err(value),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        match self.len {
+            0 => None,
+            _ => {
+                self.len -= 1;
+                Some(core::mem::take(&mut self.items[self.len]))
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.as_mut_slice().fill_with(T::default);
+        self.len = 0;
+    }
+}
+
+impl<T, const MIN: usize, const MAX: usize> BoundedArray<T, MIN, MAX> {
+    pub const fn capacity(&self) -> usize {
+        MAX
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.items[..core::cmp::min(self.len, MAX)]
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.items[..core::cmp::min(self.len, MAX)]
+    }
+
+    /// True when the number of members is within the bounds of the array
+    pub fn is_valid(&self) -> bool {
+        (MIN..=MAX).contains(&self.len)
+    }
+
+    fn validate(self) -> Result<Self, decode::Error> {
+        match self.is_valid() {
+            true => Ok(self),
+            false => Err(decode::Error::message(OUT_OF_BOUNDS)),
+        }
+    }
+}
+
+impl<T, const MIN: usize, const MAX: usize> Deref for BoundedArray<T, MIN, MAX> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const MIN: usize, const MAX: usize> DerefMut for BoundedArray<T, MIN, MAX> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Clone + Default, const MIN: usize, const MAX: usize> Default for BoundedArray<T, MIN, MAX> {
+    fn default() -> Self {
+        Self::filled(T::default())
+    }
+}
+
+impl<T: PartialEq, const MIN: usize, const MAX: usize> PartialEq for BoundedArray<T, MIN, MAX> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: fmt::Debug, const MIN: usize, const MAX: usize> fmt::Debug for BoundedArray<T, MIN, MAX> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl<C, T, const MIN: usize, const MAX: usize> Encode<C> for BoundedArray<T, MIN, MAX>
+where
+    T: Encode<C>,
+{
+    fn encode<W: Write>(
+        &self,
+        e: &mut Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.array(self.len() as u64)?;
+        for item in self.iter() {
+            item.encode(e, ctx)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'b, C, T, const MIN: usize, const MAX: usize> Decode<'b, C> for BoundedArray<T, MIN, MAX>
+where
+    T: Default + Decode<'b, C>,
+{
+    fn decode(d: &mut Decoder<'b>, ctx: &mut C) -> Result<Self, decode::Error> {
+        let mut arr = Self::new();
+        for item in d.array_iter_with::<C, T>(ctx)? {
+            arr.push(item?)
+                .map_err(|_| decode::Error::message(OUT_OF_BOUNDS))?;
+        }
+        arr.validate()
+    }
+}
+
+impl<C, T, const MIN: usize, const MAX: usize> CborLen<C> for BoundedArray<T, MIN, MAX>
+where
+    T: CborLen<C>,
+{
+    fn cbor_len(&self, ctx: &mut C) -> usize {
+        self.len().cbor_len(ctx) + self.iter().map(|item| item.cbor_len(ctx)).sum::<usize>()
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> EncodeBytes<C> for BoundedArray<u8, MIN, MAX> {
+    fn encode_bytes<W: Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _: &mut C,
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.bytes(self.as_slice())?.ok()
+    }
+}
+
+impl<'b, C, const MIN: usize, const MAX: usize> DecodeBytes<'b, C> for BoundedArray<u8, MIN, MAX> {
+    fn decode_bytes(d: &mut Decoder<'b>, _: &mut C) -> Result<Self, decode::Error> {
+        Self::from_slice(d.bytes()?).ok_or_else(|| decode::Error::message(OUT_OF_BOUNDS))
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> CborLenBytes<C> for BoundedArray<u8, MIN, MAX> {
+    fn cbor_len(&self, ctx: &mut C) -> usize {
+        self.len().cbor_len(ctx) + self.len()
+    }
+}
+
+#[cfg(feature = "range")]
+impl<T, const MIN: usize, const MAX: usize> crate::range::Bounded for BoundedArray<T, MIN, MAX>
+where
+    T: crate::range::Bounded,
+{
+    fn within(&self, min: i128, max: i128) -> bool {
+        self.iter().all(|value| value.within(min, max))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, const MIN: usize, const MAX: usize> serde::Serialize for BoundedArray<T, MIN, MAX>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, const MIN: usize, const MAX: usize> serde::Deserialize<'de>
+    for BoundedArray<T, MIN, MAX>
+where
+    T: Default + serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        struct Visitor<T, const MIN: usize, const MAX: usize>(core::marker::PhantomData<T>);
+        impl<'de, T, const MIN: usize, const MAX: usize> serde::de::Visitor<'de> for Visitor<T, MIN, MAX>
+        where
+            T: Default + serde::Deserialize<'de>,
+        {
+            type Value = BoundedArray<T, MIN, MAX>;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an array of {} to {} members", MIN, MAX)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut arr = BoundedArray::new();
+                while let Some(item) = seq.next_element()? {
+                    arr.push(item)
+                        .map_err(|_| serde::de::Error::custom(OUT_OF_BOUNDS))?;
+                }
+                match arr.is_valid() {
+                    true => Ok(arr),
+                    false => Err(serde::de::Error::custom(OUT_OF_BOUNDS)),
+                }
+            }
+        }
+        de.deserialize_seq(Visitor::<T, MIN, MAX>(core::marker::PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_array_encode_decode() {
+        let mut buff = [0; 16];
+        let arr = BoundedArray::<u16, 1, 4>::from_slice(&[1, 2, 3]).unwrap();
+        minicbor::encode(arr, buff.as_mut()).unwrap();
+        assert_eq!(minicbor::decode::<[u16; 3]>(&buff).unwrap(), [1, 2, 3]);
+        assert_eq!(minicbor::len(arr), minicbor::len([1u16, 2, 3]));
+        assert_eq!(
+            minicbor::decode::<BoundedArray<u16, 1, 4>>(&buff).unwrap(),
+            arr
+        );
+        assert!(minicbor::decode::<BoundedArray<u16, 1, 2>>(&buff).is_err());
+        assert!(minicbor::decode::<BoundedArray<u16, 4, 4>>(&buff).is_err());
+    }
+
+    #[test]
+    fn test_bounded_array_bytes() {
+        let mut buff = [0; 16];
+        let arr = BoundedArray::<u8, 0, 8>::from_slice(b"abc").unwrap();
+        let mut e = Encoder::new(buff.as_mut());
+        minicbor::bytes::encode(&arr, &mut e, &mut ()).unwrap();
+        assert_eq!(
+            minicbor::decode::<&minicbor::bytes::ByteSlice>(&buff)
+                .unwrap()
+                .as_ref(),
+            b"abc"
+        );
+        let mut d = Decoder::new(&buff);
+        let decoded: BoundedArray<u8, 0, 8> = minicbor::bytes::decode(&mut d, &mut ()).unwrap();
+        assert_eq!(decoded, arr);
+        let mut d = Decoder::new(&buff);
+        let err = minicbor::bytes::decode::<(), BoundedArray<u8, 0, 2>>(&mut d, &mut ());
+        assert!(err.is_err());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_bounded_array_clone() {
+        let mut arr = BoundedArray::<String, 0, 4>::new();
+        arr.push("a".to_string()).unwrap();
+        arr.push("much longer".to_string()).unwrap();
+        let cbor = minicbor::to_vec(arr.clone()).unwrap();
+        let decoded: BoundedArray<String, 0, 4> = minicbor::decode(&cbor).unwrap();
+        assert_eq!(decoded, arr);
+        assert_eq!(arr.pop().as_deref(), Some("much longer"));
+        arr.clear();
+        assert!(arr.is_empty());
+        assert_eq!(decoded.as_slice(), ["a", "much longer"]);
+    }
+
+    #[test]
+    fn test_bounded_array_default() {
+        let arr = BoundedArray::<u8, 2, 4>::default();
+        assert_eq!(arr.as_slice(), &[0, 0]);
+        assert_eq!(arr.capacity(), 4);
+        assert!(BoundedArray::<u8, 2, 4>::new().as_slice().is_empty());
+        assert!(BoundedArray::<u8, 2, 4>::from_slice(&[1, 2, 3, 4, 5]).is_none());
+    }
+}
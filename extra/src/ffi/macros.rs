@@ -207,17 +207,43 @@ macro_rules! define_decode_num_ts {
     };
 }
 
+macro_rules! define_encode_float_ts {
+    ($name:expr) => {
+        #[wasm_bindgen(js_name = $name)]
+        pub fn minicbor_encode_float_ts(f: f64) -> Vec<u8> {
+            let len = minicbor::encode::CborLen::cbor_len(&f, &mut ());
+            let mut enc =
+                minicbor::Encoder::new(crate::infallible_encoder::InfallibleEncoder::new(len));
+            enc.f64(f).expect("infallible!");
+            enc.into_writer().into_inner()
+        }
+    };
+}
+
+macro_rules! define_decode_float_ts {
+    ($name:expr) => {
+        #[wasm_bindgen(js_name = $name)]
+        pub fn minicbor_decode_float_ts(cbor: &[u8]) -> Result<f64, JsValue> {
+            let mut dec = minicbor::Decoder::new(cbor);
+            dec.f64().map_err(|e| JsValue::from(e.to_string()))
+        }
+    };
+}
+
 macro_rules! extra {
     ("C", $prefix:expr) => {
         paste::paste! {
             crate::ffi::macros::define_encode_len!([<$prefix _len_num>], i64);
             crate::ffi::macros::define_encode_len!([<$prefix _len_bool>], bool);
+            crate::ffi::macros::define_encode_len!([<$prefix _len_float>], f64);
             crate::ffi::macros::define_encode!([<$prefix _encode_num>], i64, i64);
             crate::ffi::macros::define_encode!([<$prefix _encode_bool>], bool, bool);
+            crate::ffi::macros::define_encode!([<$prefix _encode_float>], f64, f64);
             crate::ffi::macros::define_encode!([<$prefix _encode_fixed_array>], array, u32);
             crate::ffi::macros::define_encode!([<$prefix _encode_map>], map, u32);
             crate::ffi::macros::define_decode!([<$prefix _decode_num>], i64, i64);
             crate::ffi::macros::define_decode!([<$prefix _decode_bool>], bool, bool);
+            crate::ffi::macros::define_decode!([<$prefix _decode_float>], f64, f64);
             crate::ffi::macros::define_decode_group!([<$prefix _decode_fixed_array>], array);
             crate::ffi::macros::define_decode_group!([<$prefix _decode_map>], map);
             crate::ffi::macros::define_encode_str!([<$prefix _encode_str>]);
@@ -234,6 +260,8 @@ macro_rules! extra {
             crate::ffi::macros::define_decode_str_ts!([<$prefix DecodeStr>]);
             crate::ffi::macros::define_encode_num_ts!([<$prefix EncodeNum>]);
             crate::ffi::macros::define_decode_num_ts!([<$prefix DecodeNum>]);
+            crate::ffi::macros::define_encode_float_ts!([<$prefix EncodeFloat>]);
+            crate::ffi::macros::define_decode_float_ts!([<$prefix DecodeFloat>]);
         }
     };
 }
@@ -249,6 +277,8 @@ pub(crate) use define_encode_str;
 pub(crate) use define_encode_str_len;
 pub(crate) use define_encode_num_ts;
 pub(crate) use define_decode_num_ts;
+pub(crate) use define_encode_float_ts;
+pub(crate) use define_decode_float_ts;
 pub(crate) use define_decode_str_ts;
 pub(crate) use define_encode_str_ts;
 pub(crate) use extra;
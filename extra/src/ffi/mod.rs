@@ -53,6 +53,12 @@ mod tests {
             let hello = minicbor_encode_num_ts(42);
             assert_eq!(42, minicbor_decode_num_ts(&hello).unwrap());
         }
+
+        #[test]
+        fn test_mcbor_encode_float_ts() {
+            let reading = minicbor_encode_float_ts(21.5);
+            assert_eq!(21.5, minicbor_decode_float_ts(&reading).unwrap());
+        }
     }
 
     #[cfg(feature = "ffi_c")]
@@ -148,6 +154,23 @@ mod tests {
             assert_eq!(false, uut);
         }
 
+        #[test]
+        fn test_mcbor_decode_float() {
+            let mut buf = [0; 9];
+            let mut uut = 0.0;
+            let ret = cbor_encode_float(buf.as_mut_ptr(), 9, 21.5);
+            assert_eq!(cbor_len_float(21.5) as i32, ret);
+            let ret = cbor_decode_float(&mut uut as *mut f64, buf.as_mut_ptr(), 9);
+            assert_eq!(9, ret);
+            assert_eq!(21.5, uut);
+
+            let mut encoder = Encoder::new(buf.as_mut());
+            encoder.f16(-0.5).unwrap();
+            let ret = cbor_decode_float(&mut uut as *mut f64, buf.as_mut_ptr(), 3);
+            assert_eq!(3, ret);
+            assert_eq!(-0.5, uut);
+        }
+
         #[test]
         fn test_mcbor_decode_map() {
             let mut buf = [0; 1];
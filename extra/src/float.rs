@@ -0,0 +1,162 @@
+use minicbor::data::Type;
+use minicbor::decode::{self, Decoder};
+use minicbor::encode::{self, CborLen, Encoder, Write};
+
+/// A value that contains float16 members. Rust has no half precision float, so a float16 is
+/// stored as an f32 and converted when encoded and decoded.
+pub trait Half: Sized {
+    fn encode_half<W: Write>(&self, e: &mut Encoder<W>) -> Result<(), encode::Error<W::Error>>;
+    fn decode_half(d: &mut Decoder<'_>) -> Result<Self, decode::Error>;
+    fn half_len(&self) -> usize;
+}
+
+impl Half for f32 {
+    fn encode_half<W: Write>(&self, e: &mut Encoder<W>) -> Result<(), encode::Error<W::Error>> {
+        e.f16(*self)?.ok()
+    }
+
+    fn decode_half(d: &mut Decoder<'_>) -> Result<Self, decode::Error> {
+        d.f16()
+    }
+
+    fn half_len(&self) -> usize {
+        3
+    }
+}
+
+impl<T: Half + Copy + Default, const N: usize> Half for [T; N] {
+    fn encode_half<W: Write>(&self, e: &mut Encoder<W>) -> Result<(), encode::Error<W::Error>> {
+        e.array(N as u64)?;
+        self.iter().try_for_each(|value| value.encode_half(e))
+    }
+
+    fn decode_half(d: &mut Decoder<'_>) -> Result<Self, decode::Error> {
+        let mut arr = [T::default(); N];
+        match d.array()? {
+            Some(len) if len == N as u64 => {
+                for value in arr.iter_mut() {
+                    *value = T::decode_half(d)?;
+                }
+                Ok(arr)
+            }
+            _ => Err(decode::Error::message("expected fixed length array")),
+        }
+    }
+
+    fn half_len(&self) -> usize {
+        N.cbor_len(&mut ()) + self.iter().map(Half::half_len).sum::<usize>()
+    }
+}
+
+impl<T: Half> Half for Option<T> {
+    fn encode_half<W: Write>(&self, e: &mut Encoder<W>) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            Some(value) => value.encode_half(e),
+            None => e.null()?.ok(),
+        }
+    }
+
+    fn decode_half(d: &mut Decoder<'_>) -> Result<Self, decode::Error> {
+        match d.datatype()? {
+            Type::Null => d.null().map(|_| None),
+            _ => T::decode_half(d).map(Some),
+        }
+    }
+
+    fn half_len(&self) -> usize {
+        self.as_ref().map_or(1, Half::half_len)
+    }
+}
+
+#[cfg(feature = "optional")]
+impl<T: Half + Copy> Half for crate::optional::Optional<T> {
+    fn encode_half<W: Write>(&self, e: &mut Encoder<W>) -> Result<(), encode::Error<W::Error>> {
+        self.get().copied().encode_half(e)
+    }
+
+    fn decode_half(d: &mut Decoder<'_>) -> Result<Self, decode::Error> {
+        Option::<T>::decode_half(d).map(Self::from)
+    }
+
+    fn half_len(&self) -> usize {
+        self.get().copied().half_len()
+    }
+}
+
+#[cfg(feature = "array")]
+impl<T, const MIN: usize, const MAX: usize> Half for crate::array::BoundedArray<T, MIN, MAX>
+where
+    T: Half + Default,
+{
+    fn encode_half<W: Write>(&self, e: &mut Encoder<W>) -> Result<(), encode::Error<W::Error>> {
+        e.array(self.len() as u64)?;
+        self.iter().try_for_each(|value| value.encode_half(e))
+    }
+
+    fn decode_half(d: &mut Decoder<'_>) -> Result<Self, decode::Error> {
+        let len = d
+            .array()?
+            .ok_or_else(|| decode::Error::message("expected fixed length array"))?;
+        let mut arr = Self::new();
+        for _ in 0..len {
+            arr.push(T::decode_half(d)?)
+                .map_err(|_| decode::Error::message(crate::array::OUT_OF_BOUNDS))?;
+        }
+        match arr.is_valid() {
+            true => Ok(arr),
+            false => Err(decode::Error::message(crate::array::OUT_OF_BOUNDS)),
+        }
+    }
+
+    fn half_len(&self) -> usize {
+        self.len().cbor_len(&mut ()) + self.iter().map(Half::half_len).sum::<usize>()
+    }
+}
+
+/// Encode the float16 members of a value as half precision floats
+pub fn encode<C, T: Half, W: Write>(
+    value: &T,
+    e: &mut Encoder<W>,
+    _: &mut C,
+) -> Result<(), encode::Error<W::Error>> {
+    value.encode_half(e)
+}
+
+/// Decode the float16 members of a value from half precision floats
+pub fn decode<'b, C, T: Half>(d: &mut Decoder<'b>, _: &mut C) -> Result<T, decode::Error> {
+    T::decode_half(d)
+}
+
+pub fn cbor_len<C, T: Half>(value: &T, _: &mut C) -> usize {
+    value.half_len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_encode_decode() {
+        let mut buff = [0; 16];
+        let mut e = Encoder::new(buff.as_mut());
+        encode(&[1.5f32, -0.25], &mut e, &mut ()).unwrap();
+        assert_eq!(cbor_len(&[1.5f32, -0.25], &mut ()), 7);
+        assert_eq!(buff[0..7], [0x82, 0xf9, 0x3e, 0x00, 0xf9, 0xb4, 0x00]);
+        let decoded: [f32; 2] = decode(&mut Decoder::new(&buff), &mut ()).unwrap();
+        assert_eq!(decoded, [1.5, -0.25]);
+    }
+
+    #[test]
+    fn test_half_option() {
+        let mut buff = [0; 4];
+        let mut e = Encoder::new(buff.as_mut());
+        encode(&None::<f32>, &mut e, &mut ()).unwrap();
+        assert_eq!(cbor_len(&None::<f32>, &mut ()), 1);
+        let decoded: Option<f32> = decode(&mut Decoder::new(&buff), &mut ()).unwrap();
+        assert_eq!(decoded, None);
+        let mut e = Encoder::new(buff.as_mut());
+        encode(&Some(2.0f32), &mut e, &mut ()).unwrap();
+        let decoded: Option<f32> = decode(&mut Decoder::new(&buff), &mut ()).unwrap();
+        assert_eq!(decoded, Some(2.0));
+    }
+}
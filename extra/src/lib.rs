@@ -21,4 +21,28 @@ pub mod ffi;
 #[cfg(feature = "cast")]
 pub mod cast;
 
+#[cfg(feature = "range")]
+pub mod range;
+
+#[cfg(feature = "optional")]
+pub mod optional;
+
+#[cfg(feature = "array")]
+pub mod array;
+
+#[cfg(feature = "float")]
+pub mod float;
+
+#[cfg(feature = "tag")]
+pub mod tag;
+
+#[cfg(feature = "regexp")]
+pub mod regexp;
+
+#[cfg(feature = "validate")]
+pub mod validate;
+
+#[cfg(feature = "any")]
+pub mod any;
+
 pub use seedle_derive::seedle;
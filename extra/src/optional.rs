@@ -0,0 +1,222 @@
+use core::fmt;
+use core::mem::MaybeUninit;
+use minicbor::bytes::{CborLenBytes, DecodeBytes, EncodeBytes};
+use minicbor::data::Type;
+use minicbor::decode::{self, Decode, Decoder};
+use minicbor::encode::{self, CborLen, Encode, Encoder, Write};
+
+/// A C compatible Option. IE: struct { bool present; T value; }
+///
+/// The value is only initialized when present is true. Absent values are skipped when encoded
+/// as a map and accepted as missing when decoded.
+#[repr(C)]
+pub struct Optional<T: Copy> {
+    pub present: bool,
+    pub value: MaybeUninit<T>,
+}
+
+impl<T: Copy> Optional<T> {
+    pub const fn none() -> Optional<T> {
+        Optional {
+            present: false,
+            value: MaybeUninit::uninit(),
+        }
+    }
+
+    pub const fn some(value: T) -> Optional<T> {
+        Optional {
+            present: true,
+            value: MaybeUninit::new(value),
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        match self.present {
+            true => Some(unsafe { self.value.assume_init_ref() }),
+            false => None,
+        }
+    }
+
+    pub fn set(&mut self, value: Option<T>) {
+        *self = Optional::from(value);
+    }
+}
+
+impl<T: Copy> Copy for Optional<T> {}
+impl<T: Copy> Clone for Optional<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Copy> Default for Optional<T> {
+    fn default() -> Self {
+        Optional::none()
+    }
+}
+
+impl<T: Copy + PartialEq> PartialEq for Optional<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for Optional<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl<T: Copy> From<Option<T>> for Optional<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => Optional::some(value),
+            None => Optional::none(),
+        }
+    }
+}
+
+impl<T: Copy> From<Optional<T>> for Option<T> {
+    fn from(value: Optional<T>) -> Self {
+        value.get().copied()
+    }
+}
+
+impl<C, T: Copy + Encode<C>> Encode<C> for Optional<T> {
+    fn encode<W: Write>(
+        &self,
+        e: &mut Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self.get() {
+            Some(value) => value.encode(e, ctx),
+            None => e.null()?.ok(),
+        }
+    }
+
+    fn is_nil(&self) -> bool {
+        !self.present
+    }
+}
+
+impl<'b, C, T: Copy + Decode<'b, C>> Decode<'b, C> for Optional<T> {
+    fn decode(d: &mut Decoder<'b>, ctx: &mut C) -> Result<Self, decode::Error> {
+        if Type::Null == d.datatype()? {
+            d.skip()?;
+            return Ok(Optional::none());
+        }
+        T::decode(d, ctx).map(Optional::some)
+    }
+
+    fn nil() -> Option<Self> {
+        Some(Optional::none())
+    }
+}
+
+impl<C, T: Copy + CborLen<C>> CborLen<C> for Optional<T> {
+    fn cbor_len(&self, ctx: &mut C) -> usize {
+        match self.get() {
+            Some(value) => value.cbor_len(ctx),
+            None => 1,
+        }
+    }
+}
+
+impl<C, T: Copy + EncodeBytes<C>> EncodeBytes<C> for Optional<T> {
+    fn encode_bytes<W: Write>(
+        &self,
+        e: &mut Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self.get() {
+            Some(value) => value.encode_bytes(e, ctx),
+            None => e.null()?.ok(),
+        }
+    }
+
+    fn is_nil(&self) -> bool {
+        !self.present
+    }
+}
+
+impl<'b, C, T: Copy + DecodeBytes<'b, C>> DecodeBytes<'b, C> for Optional<T> {
+    fn decode_bytes(d: &mut Decoder<'b>, ctx: &mut C) -> Result<Self, decode::Error> {
+        if Type::Null == d.datatype()? {
+            d.skip()?;
+            return Ok(Optional::none());
+        }
+        T::decode_bytes(d, ctx).map(Optional::some)
+    }
+
+    fn nil() -> Option<Self> {
+        Some(Optional::none())
+    }
+}
+
+impl<C, T: Copy + CborLenBytes<C>> CborLenBytes<C> for Optional<T> {
+    fn cbor_len(&self, ctx: &mut C) -> usize {
+        match self.get() {
+            Some(value) => value.cbor_len(ctx),
+            None => 1,
+        }
+    }
+}
+
+/// The nil of an optional member with a custom codec. IE: nil="seedle_extra::optional::nil::<T>"
+///
+/// minicbor calls nil without a receiver, so the type is named by the caller
+pub fn nil<'b, T: Decode<'b, ()>>() -> Option<T> {
+    T::nil()
+}
+
+/// Encode an optional member with its own Encode impl. Pairs with is_nil when only the
+/// decoder of a member is custom, IE: an optional range
+pub fn encode<C, T: Encode<C>, W: Write>(
+    xs: &T,
+    e: &mut Encoder<W>,
+    ctx: &mut C,
+) -> Result<(), encode::Error<W::Error>> {
+    xs.encode(e, ctx)
+}
+
+/// True when an optional member with a custom codec is absent
+pub fn is_nil<T: Encode<()>>(xs: &T) -> bool {
+    xs.is_nil()
+}
+
+#[cfg(feature = "range")]
+impl<T: Copy + crate::range::Bounded> crate::range::Bounded for Optional<T> {
+    fn within(&self, min: i128, max: i128) -> bool {
+        self.get().iter().all(|value| value.within(min, max))
+    }
+}
+
+#[cfg(feature = "regexp")]
+impl<T: Copy + crate::regexp::Matches> crate::regexp::Matches for Optional<T> {
+    fn matches(&self, pattern: &str) -> bool {
+        self.get().iter().all(|value| value.matches(pattern))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optional_encode_decode() {
+        let mut buff = [0; 8];
+        minicbor::encode(Optional::some(42u16), buff.as_mut()).unwrap();
+        assert_eq!(minicbor::decode::<u16>(&buff).unwrap(), 42);
+        assert_eq!(
+            minicbor::decode::<Optional<u16>>(&buff).unwrap(),
+            Optional::some(42)
+        );
+        minicbor::encode(Optional::<u16>::none(), buff.as_mut()).unwrap();
+        assert_eq!(
+            minicbor::decode::<Optional<u16>>(&buff).unwrap().get(),
+            None
+        );
+        assert_eq!(Optional::<u16>::default(), Optional::none());
+        assert_eq!(Option::from(Optional::some(3u8)), Some(3));
+    }
+}
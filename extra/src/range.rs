@@ -0,0 +1,128 @@
+use minicbor::decode::{Decode, Decoder, Error};
+
+/// The message of a decode error caused by a value outside of its CDDL range
+pub const OUT_OF_RANGE: &str = "value out of range";
+
+/// The return code of the C API when a decoded value is outside of its CDDL range
+pub const ERROR_OUT_OF_RANGE: i32 = -2;
+
+/// A value that can be checked against the bounds of a range
+pub trait Bounded {
+    fn within(&self, min: i128, max: i128) -> bool;
+}
+
+macro_rules! bounded {
+    ($($ty:ty),*) => {
+        $(
+            impl Bounded for $ty {
+                fn within(&self, min: i128, max: i128) -> bool {
+                    min <= *self as i128 && *self as i128 <= max
+                }
+            }
+        )*
+    };
+}
+bounded!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl<T: Bounded, const N: usize> Bounded for [T; N] {
+    fn within(&self, min: i128, max: i128) -> bool {
+        self.iter().all(|value| value.within(min, max))
+    }
+}
+
+impl<T: Bounded> Bounded for Option<T> {
+    fn within(&self, min: i128, max: i128) -> bool {
+        self.iter().all(|value| value.within(min, max))
+    }
+}
+
+/// Decode a value and reject it unless MIN <= value <= MAX
+pub fn decode<'b, Ctx, T, const MIN: i128, const MAX: i128>(
+    d: &mut Decoder<'b>,
+    ctx: &mut Ctx,
+) -> Result<T, Error>
+where
+    T: Decode<'b, Ctx> + Bounded,
+{
+    let value = T::decode(d, ctx)?;
+    match value.within(MIN, MAX) {
+        true => Ok(value),
+        false => Err(Error::message(OUT_OF_RANGE)),
+    }
+}
+
+/// True when a decode error was caused by a value outside of its range
+pub fn is_out_of_range(e: &Error) -> bool {
+    use core::fmt::Write;
+    let mut search = Search {
+        needle: OUT_OF_RANGE.as_bytes(),
+        matched: 0,
+        found: false,
+    };
+    write!(&mut search, "{}", e).map_or(false, |_| search.found)
+}
+
+/// The return code of the C API for a decode error
+pub fn error_code(e: &Error) -> i32 {
+    match is_out_of_range(e) {
+        true => ERROR_OUT_OF_RANGE,
+        false => -1,
+    }
+}
+
+/// Look for a message in the display output of an error without allocating
+struct Search<'a> {
+    needle: &'a [u8],
+    matched: usize,
+    found: bool,
+}
+impl<'a> core::fmt::Write for Search<'a> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for b in s.bytes() {
+            if self.found {
+                break;
+            }
+            self.matched = match b == self.needle[self.matched] {
+                true => self.matched + 1,
+                false => (b == self.needle[0]) as usize,
+            };
+            self.found = self.matched == self.needle.len();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_channel(buff: &[u8]) -> Result<u8, Error> {
+        decode::<_, u8, 1, 14>(&mut Decoder::new(buff), &mut ())
+    }
+
+    #[test]
+    fn test_decode_range() {
+        let mut buff = [0; 4];
+        minicbor::encode(14u8, buff.as_mut()).unwrap();
+        assert_eq!(decode_channel(&buff).unwrap(), 14);
+        minicbor::encode(15u8, buff.as_mut()).unwrap();
+        let err = decode_channel(&buff).unwrap_err();
+        assert!(is_out_of_range(&err));
+        assert_eq!(error_code(&err), ERROR_OUT_OF_RANGE);
+        minicbor::encode("x", buff.as_mut()).unwrap();
+        let err = decode_channel(&buff).unwrap_err();
+        assert!(!is_out_of_range(&err));
+        assert_eq!(error_code(&err), -1);
+    }
+
+    #[test]
+    fn test_decode_range_array() {
+        let mut buff = [0; 8];
+        minicbor::encode([-40i8, 125], buff.as_mut()).unwrap();
+        let arr = decode::<_, [i8; 2], -40, 125>(&mut Decoder::new(&buff), &mut ()).unwrap();
+        assert_eq!(arr, [-40, 125]);
+        minicbor::encode([-41i8, 0], buff.as_mut()).unwrap();
+        let err = decode::<_, [i8; 2], -40, 125>(&mut Decoder::new(&buff), &mut ()).unwrap_err();
+        assert!(is_out_of_range(&err));
+    }
+}
@@ -0,0 +1,99 @@
+use minicbor::bytes::DecodeBytes;
+use minicbor::decode::{Decode, Decoder, Error};
+pub use seedle_regexp::{is_match, is_valid, MAX_STATES};
+
+/// The message of a decode error caused by text that does not match the pattern of its CDDL type
+pub const NO_MATCH: &str = "value does not match pattern";
+
+/// A value that holds text to match against a pattern
+pub trait Matches {
+    fn matches(&self, pattern: &str) -> bool;
+}
+
+/// The text of a string of bounded size ends at its first zero
+impl<const N: usize> Matches for [u8; N] {
+    fn matches(&self, pattern: &str) -> bool {
+        let len = self.iter().position(|b| *b == 0).unwrap_or(N);
+        core::str::from_utf8(&self[..len]).is_ok_and(|text| is_match(pattern, text))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Matches for String {
+    fn matches(&self, pattern: &str) -> bool {
+        is_match(pattern, self)
+    }
+}
+
+impl<T: Matches> Matches for Option<T> {
+    fn matches(&self, pattern: &str) -> bool {
+        self.iter().all(|value| value.matches(pattern))
+    }
+}
+
+/// Decode a value and reject it unless it matches a pattern
+pub fn decode<'b, Ctx, T>(d: &mut Decoder<'b>, ctx: &mut Ctx, pattern: &str) -> Result<T, Error>
+where
+    T: Decode<'b, Ctx> + Matches,
+{
+    let p = d.position();
+    let value = T::decode(d, ctx)?;
+    match value.matches(pattern) {
+        true => Ok(value),
+        false => Err(Error::message(NO_MATCH).at(p)),
+    }
+}
+
+/// The pattern codec of text of bounded size, which is encoded as a byte string
+pub mod bytes {
+    use super::*;
+
+    pub fn decode<'b, Ctx, T>(d: &mut Decoder<'b>, ctx: &mut Ctx, pattern: &str) -> Result<T, Error>
+    where
+        T: DecodeBytes<'b, Ctx> + Matches,
+    {
+        let p = d.position();
+        let value = T::decode_bytes(d, ctx)?;
+        match value.matches(pattern) {
+            true => Ok(value),
+            false => Err(Error::message(NO_MATCH).at(p)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minicbor::Encoder;
+
+    #[test]
+    fn test_matches() {
+        assert!(b"abc\0\0".matches("[a-c]+"));
+        assert!(!b"abcd\0".matches("[a-c]+"));
+        assert!(None::<[u8; 4]>.matches("[a-c]+"));
+        assert!(!Some(*b"abcd").matches("[a-c]+"));
+    }
+
+    #[test]
+    fn test_regexp_decode() {
+        let mut buff = [0; 16];
+        let mut e = Encoder::new(buff.as_mut());
+        e.bytes(b"ab\0\0").unwrap();
+        let bytes: [u8; 4] = bytes::decode(&mut Decoder::new(&buff), &mut (), "a.").unwrap();
+        assert_eq!(bytes, *b"ab\0\0");
+        let err = bytes::decode::<_, [u8; 4]>(&mut Decoder::new(&buff), &mut (), "b.").unwrap_err();
+        assert!(err.to_string().contains(NO_MATCH));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_regexp_decode_string() {
+        let mut buff = [0; 16];
+        let mut e = Encoder::new(buff.as_mut());
+        e.str("ab").unwrap();
+        let text: String = decode(&mut Decoder::new(&buff), &mut (), "a.").unwrap();
+        assert_eq!(text, "ab");
+        let err = decode::<_, String>(&mut Decoder::new(&buff), &mut (), "b.").unwrap_err();
+        assert!(err.to_string().contains(NO_MATCH));
+    }
+}
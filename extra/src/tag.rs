@@ -0,0 +1,139 @@
+use minicbor::bytes::{CborLenBytes, DecodeBytes, EncodeBytes};
+use minicbor::data::Tag;
+use minicbor::decode::{self, Decode, Decoder};
+use minicbor::encode::{self, CborLen, Encode, Encoder, Write};
+
+/// The message of a decode error caused by a value with a different tag than its CDDL type
+pub const UNEXPECTED_TAG: &str = "unexpected tag";
+
+/// Encode a value wrapped in the tag N
+pub fn encode<Ctx, T, W, const N: u64>(
+    value: &T,
+    e: &mut Encoder<W>,
+    ctx: &mut Ctx,
+) -> Result<(), encode::Error<W::Error>>
+where
+    T: Encode<Ctx>,
+    W: Write,
+{
+    e.tag(Tag::Unassigned(N))?;
+    value.encode(e, ctx)
+}
+
+/// Decode a value and reject it unless it is wrapped in the tag N
+pub fn decode<'b, Ctx, T, const N: u64>(
+    d: &mut Decoder<'b>,
+    ctx: &mut Ctx,
+) -> Result<T, decode::Error>
+where
+    T: Decode<'b, Ctx>,
+{
+    expect::<N>(d)?;
+    T::decode(d, ctx)
+}
+
+pub fn cbor_len<Ctx, T, const N: u64>(value: &T, ctx: &mut Ctx) -> usize
+where
+    T: CborLen<Ctx>,
+{
+    N.cbor_len(ctx) + value.cbor_len(ctx)
+}
+
+/// The tag codec of values encoded as a byte string. IE: #6.37(bytes .size 16)
+pub mod bytes {
+    use super::*;
+
+    pub fn encode<Ctx, T, W, const N: u64>(
+        value: &T,
+        e: &mut Encoder<W>,
+        ctx: &mut Ctx,
+    ) -> Result<(), encode::Error<W::Error>>
+    where
+        T: EncodeBytes<Ctx>,
+        W: Write,
+    {
+        e.tag(Tag::Unassigned(N))?;
+        value.encode_bytes(e, ctx)
+    }
+
+    pub fn decode<'b, Ctx, T, const N: u64>(
+        d: &mut Decoder<'b>,
+        ctx: &mut Ctx,
+    ) -> Result<T, decode::Error>
+    where
+        T: DecodeBytes<'b, Ctx>,
+    {
+        expect::<N>(d)?;
+        T::decode_bytes(d, ctx)
+    }
+
+    pub fn cbor_len<Ctx, T, const N: u64>(value: &T, ctx: &mut Ctx) -> usize
+    where
+        T: CborLenBytes<Ctx>,
+    {
+        N.cbor_len(ctx) + value.cbor_len(ctx)
+    }
+}
+
+fn expect<const N: u64>(d: &mut Decoder<'_>) -> Result<(), decode::Error> {
+    let p = d.position();
+    match number(d.tag()?) {
+        n if n == N => Ok(()),
+        _ => Err(decode::Error::message(UNEXPECTED_TAG).at(p)),
+    }
+}
+
+/// minicbor names the tags it knows, so the number of a decoded tag is recovered here
+fn number(tag: Tag) -> u64 {
+    match tag {
+        Tag::DateTime => 0x00,
+        Tag::Timestamp => 0x01,
+        Tag::PosBignum => 0x02,
+        Tag::NegBignum => 0x03,
+        Tag::Decimal => 0x04,
+        Tag::Bigfloat => 0x05,
+        Tag::ToBase64Url => 0x15,
+        Tag::ToBase64 => 0x16,
+        Tag::ToBase16 => 0x17,
+        Tag::Cbor => 0x18,
+        Tag::Uri => 0x20,
+        Tag::Base64Url => 0x21,
+        Tag::Base64 => 0x22,
+        Tag::Regex => 0x23,
+        Tag::Mime => 0x24,
+        Tag::Unassigned(n) => n,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag() {
+        let mut buff = [0; 16];
+        let mut e = Encoder::new(buff.as_mut());
+        encode::<_, _, _, 1>(&7u64, &mut e, &mut ()).unwrap();
+        assert_eq!(buff[..2], [0xc1, 0x07]);
+        assert_eq!(cbor_len::<_, _, 1>(&7u64, &mut ()), 2);
+        assert_eq!(
+            decode::<_, u64, 1>(&mut Decoder::new(&buff), &mut ()).unwrap(),
+            7
+        );
+        let err = decode::<_, u64, 2>(&mut Decoder::new(&buff), &mut ()).unwrap_err();
+        assert!(err.to_string().contains(UNEXPECTED_TAG));
+        let err = decode::<_, u64, 1>(&mut Decoder::new(&[0x07]), &mut ()).unwrap_err();
+        assert!(!err.to_string().contains(UNEXPECTED_TAG));
+    }
+
+    #[test]
+    fn test_tag_bytes() {
+        let mut buff = [0; 16];
+        let mut e = Encoder::new(buff.as_mut());
+        bytes::encode::<_, _, _, 37>(&[1u8, 2], &mut e, &mut ()).unwrap();
+        assert_eq!(buff[..5], [0xd8, 0x25, 0x42, 0x01, 0x02]);
+        assert_eq!(bytes::cbor_len::<_, _, 37>(&[1u8, 2], &mut ()), 5);
+        let arr = bytes::decode::<_, [u8; 2], 37>(&mut Decoder::new(&buff), &mut ()).unwrap();
+        assert_eq!(arr, [1, 2]);
+    }
+}
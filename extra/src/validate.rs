@@ -0,0 +1,48 @@
+use core::fmt;
+
+/// The return code of the C API when every member satisfies the constraints of its CDDL type
+pub const VALID: i32 = 0;
+
+/// The first member of a struct holding a value that does not satisfy the constraints of its
+/// CDDL type. IE: percent: uint .size 1 .le 100
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Invalid {
+    /// The position of the member in the struct, starting at 0
+    pub member: usize,
+    /// The name of the member in the CDDL
+    pub key: &'static str,
+}
+
+impl Invalid {
+    pub fn new(member: usize, key: &'static str) -> Invalid {
+        Invalid { member, key }
+    }
+
+    /// The return code of the C API, which is the position of the member starting at 1
+    pub fn code(&self) -> i32 {
+        self.member as i32 + 1
+    }
+}
+
+impl fmt::Display for Invalid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid value for [{}]", self.key)
+    }
+}
+
+/// The return code of the C API for the result of a validator
+pub fn error_code(result: Result<(), Invalid>) -> i32 {
+    result.map_or_else(|invalid| invalid.code(), |_| VALID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(error_code(Ok(())), VALID);
+        assert_eq!(error_code(Err(Invalid::new(0, "percent"))), 1);
+        assert_eq!(error_code(Err(Invalid::new(3, "floor"))), 4);
+    }
+}
@@ -1,4 +1,5 @@
 use super::error::invalid_fmt;
+use super::field::OptionalFormatter;
 use crate::language::Language;
 use liquid_core::{
     Display_filter, Filter, FilterParameters, FilterReflection, FromFilterParameters, ParseFilter,
@@ -17,7 +18,7 @@ macro_rules! render {
 
 #[derive(Deserialize)]
 pub(crate) struct AttrFieldJsonArgs {
-    index: u8,
+    index: u32,
     language: Language,
     required: bool,
 }
@@ -68,29 +69,154 @@ pub struct AttrFieldFilter {
 impl Filter for AttrFieldFilter {
     fn evaluate(&self, input: &dyn ValueView, runtime: &dyn Runtime) -> Result<Value> {
         let json = self.args.evaluate(runtime)?.json;
-        let args = serde_json::from_str::<AttrFieldJsonArgs>(json.to_kstr().as_str())
+        let mut args = serde_json::from_str::<AttrFieldJsonArgs>(json.to_kstr().as_str())
             .map_err(|e| Error::with_msg(e.to_string()))?;
         let input = LinkedKeyVal::try_from(input.to_value())
             .map_err(|e| Error::with_msg("invalid argument").cause(e))?;
+        // Members with a declared integer key are encoded with it instead of their position
+        if let Some(index) = input.index() {
+            args.index = index;
+        }
+        let optional = input.is_optional();
+        // Constraints are checked by the validator and are encoded as the number they constrain
+        let node = input.val().unconstrained().into_owned();
         match args.language {
-            Language::C => attr_field_c(input.into_val(), args),
-            _ => attr_field_typescript(input.into_val(), args),
+            Language::C => attr_field_c(node, optional, args),
+            _ => attr_field_typescript(node, optional, args),
+        }
+    }
+}
+/// Optional members are skipped when absent, so their codecs must name the type of nil
+fn nil_args(input: &LinkedNode, optional: bool, language: &Language) -> (String, String) {
+    let bytes = codec_args("minicbor::bytes", input, optional, language);
+    match optional {
+        true => {
+            let range = format!(
+                ", encode_with=\"seedle_extra::optional::encode\", \
+                 is_nil=\"seedle_extra::optional::is_nil\", {}",
+                nil_arg(input, language)
+            );
+            (range, bytes)
+        }
+        false => (String::new(), bytes),
+    }
+}
+
+fn nil_arg(input: &LinkedNode, language: &Language) -> String {
+    format!(
+        "nil=\"seedle_extra::optional::nil::<{}>\"",
+        OptionalFormatter {
+            language,
+            node: input
+        }
+    )
+}
+
+/// The arguments of a custom codec of a field, IE: minicbor::bytes
+fn codec_args(codec: &str, input: &LinkedNode, optional: bool, language: &Language) -> String {
+    match optional {
+        true => format!(
+            "encode_with=\"{codec}::encode\", \
+             decode_with=\"{codec}::decode\", \
+             cbor_len=\"{codec}::cbor_len\", \
+             is_nil=\"seedle_extra::optional::is_nil\", {}",
+            nil_arg(input, language),
+            codec = codec
+        ),
+        false => format!("with=\"{}\"", codec),
+    }
+}
+
+/// float16 members are stored as an f32 and need a codec to encode them as half floats
+fn half_codec(input: &LinkedNode, optional: bool, language: &Language) -> Option<String> {
+    match input {
+        LinkedNode::Primative(ConstrainedPrimative::F16) => {
+            Some(codec_args("seedle_extra::float", input, optional, language))
+        }
+        // The codec converts each member of nested arrays
+        LinkedNode::Array(LinkedArray { ty, .. }) => half_codec(ty, false, language)
+            .map(|_| codec_args("seedle_extra::float", input, optional, language)),
+        _ => None,
+    }
+}
+
+/// Tagged members are written after their tag, and rejected when decoded with another tag
+fn tag_codec(input: &LinkedNode, optional: bool, language: &Language) -> Option<String> {
+    let LinkedTag { tag, ty } = match input {
+        LinkedNode::Tag(tag) => tag,
+        _ => return None,
+    };
+    let codec = match ty.as_ref() {
+        LinkedNode::Primative(ConstrainedPrimative::Str(_))
+        | LinkedNode::Primative(ConstrainedPrimative::Bytes(_))
+        | LinkedNode::Primative(ConstrainedPrimative::UnboundedBytes) => "seedle_extra::tag::bytes",
+        _ => "seedle_extra::tag",
+    };
+    let args = format!(
+        "encode_with=\"{codec}::encode::<_, _, _, {{{tag}}}>\", \
+         decode_with=\"{codec}::decode::<_, _, {{{tag}}}>\", \
+         cbor_len=\"{codec}::cbor_len::<_, _, {{{tag}}}>\"",
+        codec = codec,
+        tag = tag
+    );
+    match optional {
+        true => Some(format!(
+            "{}, is_nil=\"seedle_extra::optional::is_nil\", {}",
+            args,
+            nil_arg(ty, language)
+        )),
+        false => Some(args),
+    }
+}
+
+/// Ranges are decoded with a helper that rejects values outside of the range
+fn range_decoder(input: &LinkedNode) -> Option<String> {
+    match input {
+        LinkedNode::Range(range) => {
+            let (min, max) = range.bounds();
+            Some(format!(
+                "seedle_extra::range::decode::<_, _, {{{}}}, {{{}}}>",
+                min, max
+            ))
         }
+        // The decoder checks each member of nested arrays
+        LinkedNode::Array(LinkedArray { ty, .. }) => range_decoder(ty),
+        _ => None,
     }
 }
+
 #[inline]
-fn attr_field_c(input: LinkedNode, args: AttrFieldJsonArgs) -> Result<Value> {
+fn attr_field_c(input: LinkedNode, optional: bool, args: AttrFieldJsonArgs) -> Result<Value> {
     let mut ret = String::new();
     let index = args.index;
+    let (nil, bytes) = nil_args(&input, optional, &args.language);
+    if let Some(decoder) = range_decoder(&input) {
+        render!(
+            ret,
+            "#[cbor(n({}), decode_with=\"{}\"{})]",
+            index,
+            decoder,
+            nil
+        )?;
+        return Ok(Value::Scalar(ret.into()));
+    }
+    if let Some(half) = half_codec(&input, optional, &args.language) {
+        render!(ret, "#[cbor(n({}), {})]", index, half)?;
+        return Ok(Value::Scalar(ret.into()));
+    }
+    if let Some(tag) = tag_codec(&input, optional, &args.language) {
+        render!(ret, "#[cbor(n({}), {})]", index, tag)?;
+        return Ok(Value::Scalar(ret.into()));
+    }
     match input {
         LinkedNode::Array(LinkedArray { ty, .. }) => match ty.as_ref() {
             LinkedNode::Primative(ConstrainedPrimative::U8) => {
-                render!(ret, "#[cbor(n({}), with=\"minicbor::bytes\")]", index)
+                render!(ret, "#[cbor(n({}), {})]", index, bytes)
             }
             _ => render!(ret, "#[n({})]", index),
         },
         LinkedNode::Primative(ConstrainedPrimative::Str(_)) => {
-            render!(ret, "#[cbor(n({}), with=\"minicbor::bytes\")]\n", index)
+            render!(ret, "#[cbor(n({}), {})]\n", index, bytes)
         }
         _ => render!(ret, "#[n({})]", args.index),
     }?;
@@ -98,26 +224,61 @@ fn attr_field_c(input: LinkedNode, args: AttrFieldJsonArgs) -> Result<Value> {
 }
 
 #[inline]
-fn attr_field_typescript(input: LinkedNode, args: AttrFieldJsonArgs) -> Result<Value> {
+fn attr_field_typescript(
+    input: LinkedNode,
+    optional: bool,
+    args: AttrFieldJsonArgs,
+) -> Result<Value> {
     let index = args.index;
-    let converters = MinicborConverters::new(!args.required);
+    let converters = MinicborConverters::new(!args.required || optional);
     let deserialize = converters.deserialize;
     let default = converters.default;
+    let (nil, bytes) = nil_args(&input, optional, &args.language);
     let mut ret = String::new();
+    if let Some(decoder) = range_decoder(&input) {
+        render!(
+            ret,
+            "#[cbor(n({}), decode_with=\"{}\"{})]",
+            index,
+            decoder,
+            nil
+        )?;
+        return Ok(Value::Scalar(ret.into()));
+    }
+    if let Some(half) = half_codec(&input, optional, &args.language) {
+        render!(ret, "#[cbor(n({}), {})]", index, half)?;
+        return Ok(Value::Scalar(ret.into()));
+    }
+    if let Some(tag) = tag_codec(&input, !args.required || optional, &args.language) {
+        render!(ret, "#[cbor(n({}), {})]", index, tag)?;
+        if let LinkedNode::Tag(LinkedTag { ty, .. }) = &input {
+            if let LinkedNode::Primative(ConstrainedPrimative::Str(_)) = ty.as_ref() {
+                render!(ret, "\n#[serde(default=\"{}\")]\n", default)?;
+                render!(ret, "#[serde(serde_serialize_with=\"ser_bytes_as_str\")]\n")?;
+                render!(ret, "#[serde(serde_deserialize_with=\"{}\")]", deserialize)?;
+            }
+        }
+        return Ok(Value::Scalar(ret.into()));
+    }
     match input {
-        LinkedNode::Array(LinkedArray { ty, len }) => match ty.as_ref() {
-            LinkedNode::Primative(ConstrainedPrimative::U8) if len < 32 => {
-                render!(ret, "#[cbor(n({}), with=\"minicbor::bytes\")]", index)
+        LinkedNode::Array(array) => match array.ty.as_ref() {
+            LinkedNode::Primative(ConstrainedPrimative::U8)
+                if array.len < 32 || !array.is_fixed() =>
+            {
+                render!(ret, "#[cbor(n({}), {})]", index, bytes)
             }
             LinkedNode::Primative(ConstrainedPrimative::U8) => {
-                render!(ret, "#[cbor(n({}), with=\"minicbor::bytes\")]\n", index)?;
+                render!(ret, "#[cbor(n({}), {})]\n", index, bytes)?;
                 render!(ret, "#[serde(serde_serialize_with=\"ser_bytes_as_str\")]\n")?;
                 render!(ret, "#[serde(serde_deserialize_with=\"{}\")]", deserialize)
             }
             _ => render!(ret, "#[n({})]", index),
         },
+        LinkedNode::Primative(ConstrainedPrimative::UnboundedBytes) => {
+            render!(ret, "#[cbor(n({}), {})]", index, bytes)
+        }
         LinkedNode::Primative(ConstrainedPrimative::Str(_)) => {
-            render!(ret, "#[cbor(n({}), with=\"minicbor::bytes\")]\n", index)?;
+            render!(ret, "#[cbor(n({}), {})]\n", index, bytes)?;
             render!(ret, "#[serde(default=\"{}\")]\n", default)?;
             render!(ret, "#[serde(serde_serialize_with=\"ser_bytes_as_str\")]\n")?;
             render!(ret, "#[serde(serde_deserialize_with=\"{}\")]", deserialize)
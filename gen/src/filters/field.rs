@@ -1,3 +1,4 @@
+use super::error::invalid_fmt;
 use crate::language::Language;
 use liquid_core::Error;
 use liquid_core::{
@@ -7,7 +8,7 @@ use liquid_core::{Expression, Result, Runtime};
 use liquid_core::{Value, ValueView};
 use seedle_parser::*;
 use serde::Deserialize;
-use std::fmt;
+use std::fmt::{self, Write};
 
 #[derive(Deserialize)]
 pub(crate) struct FieldJsonArgs {
@@ -50,7 +51,9 @@ impl Filter for FieldFilter {
             public: args.public,
             node: &node,
         };
-        Ok(Value::Scalar(format!("{}", field).into()))
+        let mut ret = String::new();
+        write!(ret, "{}", field).map_err(invalid_fmt)?;
+        Ok(Value::Scalar(ret.into()))
     }
 }
 
@@ -70,9 +73,28 @@ impl<'s> fmt::Display for FieldFormatter<'s> {
         } else {
             write!(f, "{}: ", key)?;
         }
-        match self.required {
-            true => write!(f, "{}", NodeFormatter { language, node }),
-            false => write!(f, "Option<{}>", NodeFormatter { language, node }),
+        match (self.required, self.node.is_optional()) {
+            (true, false) => write!(f, "{}", NodeFormatter { language, node }),
+            (true, true) => write!(f, "{}", OptionalFormatter { language, node }),
+            (false, _) => write!(f, "Option<{}>", NodeFormatter { language, node }),
+        }
+    }
+}
+
+/// Optional members are a C compatible Optional<T> in C, and an Option<T> otherwise
+pub(crate) struct OptionalFormatter<'s> {
+    pub(crate) language: &'s Language,
+    pub(crate) node: &'s LinkedNode,
+}
+impl<'s> fmt::Display for OptionalFormatter<'s> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let formatter = NodeFormatter {
+            language: self.language,
+            node: self.node,
+        };
+        match self.language {
+            Language::C => write!(f, "seedle_extra::optional::Optional<{}>", formatter),
+            _ => write!(f, "Option<{}>", formatter),
         }
     }
 }
@@ -85,9 +107,31 @@ impl<'s> fmt::Display for NodeFormatter<'s> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let language = self.language;
         match self.node {
+            // C has no heap, so strings, bytes and any must be constrained with .size
+            LinkedNode::Primative(ConstrainedPrimative::UnboundedStr)
+            | LinkedNode::Primative(ConstrainedPrimative::UnboundedBytes)
+            | LinkedNode::Primative(ConstrainedPrimative::UnboundedAny)
+                if *language == Language::C =>
+            {
+                Err(fmt::Error)
+            }
             LinkedNode::Primative(p) => PrimativeFormatter(p).fmt(f),
+            LinkedNode::Range(r) => PrimativeFormatter(&r.ty).fmt(f),
+            LinkedNode::Constrained(c) => PrimativeFormatter(&c.ty).fmt(f),
+            LinkedNode::Regexp(_) | LinkedNode::Bits(_) => NodeFormatter {
+                language,
+                node: &self.node.unconstrained(),
+            }
+            .fmt(f),
             LinkedNode::Array(a) => ArrayFormatter { language, node: a }.fmt(f),
-            LinkedNode::ForeignStruct(s) => StructFormatter { language, node: s }.fmt(f),
+            LinkedNode::Tag(t) => NodeFormatter {
+                language,
+                node: t.ty.as_ref(),
+            }
+            .fmt(f),
+            LinkedNode::ForeignStruct(s)
+            | LinkedNode::ForeignChoice(s)
+            | LinkedNode::ForeignEnumeration(s) => StructFormatter { language, node: s }.fmt(f),
             _ => Err(fmt::Error),
         }
     }
@@ -106,8 +150,14 @@ impl<'s> fmt::Display for PrimativeFormatter<'s> {
             ConstrainedPrimative::I32 => write!(f, "i32"),
             ConstrainedPrimative::I64 => write!(f, "i64"),
             ConstrainedPrimative::Bool => write!(f, "bool"),
+            ConstrainedPrimative::F16 | ConstrainedPrimative::F32 => write!(f, "f32"),
+            ConstrainedPrimative::F64 => write!(f, "f64"),
             ConstrainedPrimative::Str(n) => write!(f, "[u8; {}]", n),
             ConstrainedPrimative::Bytes(n) => write!(f, "[u8; {}]", n),
+            ConstrainedPrimative::Any(n) => write!(f, "seedle_extra::any::Any<{}>", n),
+            ConstrainedPrimative::UnboundedStr => write!(f, "String"),
+            ConstrainedPrimative::UnboundedBytes => write!(f, "Vec<u8>"),
+            ConstrainedPrimative::UnboundedAny => write!(f, "seedle_extra::any::AnyBuf"),
         }
     }
 }
@@ -132,7 +182,14 @@ impl<'s> fmt::Display for ArrayFormatter<'s> {
             language: self.language,
             node: self.node.ty.as_ref(),
         };
-        write!(f, "[{}; {}]", formatter, self.node.len)
+        match self.node.is_fixed() {
+            true => write!(f, "[{}; {}]", formatter, self.node.len),
+            false => write!(
+                f,
+                "seedle_extra::array::BoundedArray<{}, {}, {}>",
+                formatter, self.node.min, self.node.len
+            ),
+        }
     }
 }
 
@@ -141,8 +198,22 @@ impl<'s> fmt::Display for FieldDefaultFormatter<'s> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.0 {
             LinkedNode::Primative(ConstrainedPrimative::Str(n)) => write!(f, "[0; {}]", n),
-            LinkedNode::Array(LinkedArray { ty, len }) => match ty.as_ref() {
-                LinkedNode::Primative(ConstrainedPrimative::U8)
+            LinkedNode::Range(r) => write!(f, "{}", r.bounds().0),
+            LinkedNode::Constrained(c) => InitialFormatter(c).fmt(f),
+            LinkedNode::Regexp(_) | LinkedNode::Bits(_) => {
+                FieldDefaultFormatter(&self.0.unconstrained()).fmt(f)
+            }
+            LinkedNode::Tag(t) => FieldDefaultFormatter(&t.ty).fmt(f),
+            LinkedNode::Array(array) if !array.is_fixed() => write!(
+                f,
+                "seedle_extra::array::BoundedArray::filled({})",
+                FieldDefaultFormatter(&array.ty)
+            ),
+            LinkedNode::Array(LinkedArray { ty, len, .. }) => match ty.as_ref() {
+                LinkedNode::Range(r) => write!(f, "[{}; {}]", r.bounds().0, len),
+                LinkedNode::Constrained(c) => write!(f, "[{}; {}]", InitialFormatter(c), len),
+                LinkedNode::Bits(_)
+                | LinkedNode::Primative(ConstrainedPrimative::U8)
                 | LinkedNode::Primative(ConstrainedPrimative::U16)
                 | LinkedNode::Primative(ConstrainedPrimative::U32)
                 | LinkedNode::Primative(ConstrainedPrimative::U64)
@@ -150,7 +221,19 @@ impl<'s> fmt::Display for FieldDefaultFormatter<'s> {
                 | LinkedNode::Primative(ConstrainedPrimative::I16)
                 | LinkedNode::Primative(ConstrainedPrimative::I32)
                 | LinkedNode::Primative(ConstrainedPrimative::I64) => write!(f, "[0; {}]", len),
-                LinkedNode::ForeignStruct(_) => write!(f, "[Default::default(); {}]", len),
+                LinkedNode::Primative(ConstrainedPrimative::F16)
+                | LinkedNode::Primative(ConstrainedPrimative::F32)
+                | LinkedNode::Primative(ConstrainedPrimative::F64) => {
+                    write!(f, "[0.0; {}]", len)
+                }
+                LinkedNode::ForeignStruct(_)
+                | LinkedNode::ForeignChoice(_)
+                | LinkedNode::ForeignEnumeration(_)
+                | LinkedNode::Primative(ConstrainedPrimative::Any(_)) => {
+                    write!(f, "[Default::default(); {}]", len)
+                }
+                // Nested arrays repeat the initializer of their inner array. IE: [[0; 4]; 4]
+                LinkedNode::Array(_) => write!(f, "[{}; {}]", FieldDefaultFormatter(ty), len),
                 _ => Err(fmt::Error),
             },
             _ => write!(f, "Default::default()"),
@@ -158,6 +241,78 @@ impl<'s> fmt::Display for FieldDefaultFormatter<'s> {
     }
 }
 
+/// A constrained number starts with the value closest to zero that satisfies its constraints
+struct InitialFormatter<'s>(&'s Constrained);
+impl<'s> fmt::Display for InitialFormatter<'s> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n = self.0.initial().unwrap_or_default();
+        NumberFormatter { ty: &self.0.ty, n }.fmt(f)
+    }
+}
+
+/// A number literal of a primative. IE: -40 or 0.0
+struct NumberFormatter<'s> {
+    ty: &'s ConstrainedPrimative,
+    n: i128,
+}
+impl<'s> fmt::Display for NumberFormatter<'s> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.ty {
+            ConstrainedPrimative::F16 | ConstrainedPrimative::F32 | ConstrainedPrimative::F64 => {
+                write!(f, "{:?}", self.n as f64)
+            }
+            _ => write!(f, "{}", self.n),
+        }
+    }
+}
+
+/// The default of a field given by the schema. IE: port: u16 .default 80
+struct DefaultValueFormatter<'s> {
+    node: &'s LinkedNode,
+    value: &'s Literal,
+}
+impl<'s> fmt::Display for DefaultValueFormatter<'s> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let node = match self.node {
+            LinkedNode::Tag(LinkedTag { ty, .. }) => ty.unconstrained(),
+            node => node.unconstrained(),
+        };
+        let node = node.as_ref();
+        let text = match self.value {
+            Literal::Str(s) => Some(s.clone()),
+            Literal::Char(c) => Some(c.to_string()),
+            _ => None,
+        };
+        match (node, self.value, text) {
+            (LinkedNode::Primative(ConstrainedPrimative::Str(n)), _, Some(text)) => {
+                let mut bytes = text.into_bytes();
+                bytes.resize(*n as usize, 0);
+                let escaped = bytes
+                    .into_iter()
+                    .flat_map(std::ascii::escape_default)
+                    .map(char::from)
+                    .collect::<String>();
+                write!(f, "*b\"{}\"", escaped)
+            }
+            (_, _, Some(text)) => write!(f, "String::from({:?})", text),
+            (LinkedNode::Primative(ConstrainedPrimative::F16), Literal::Int(n), _)
+            | (LinkedNode::Primative(ConstrainedPrimative::F32), Literal::Int(n), _)
+            | (LinkedNode::Primative(ConstrainedPrimative::F64), Literal::Int(n), _) => {
+                write!(f, "{:?}", *n as f64)
+            }
+            (LinkedNode::Primative(ConstrainedPrimative::F16), Literal::UInt(n), _)
+            | (LinkedNode::Primative(ConstrainedPrimative::F32), Literal::UInt(n), _)
+            | (LinkedNode::Primative(ConstrainedPrimative::F64), Literal::UInt(n), _) => {
+                write!(f, "{:?}", *n as f64)
+            }
+            (_, Literal::Int(n), _) => write!(f, "{}", n),
+            (_, Literal::UInt(n), _) => write!(f, "{}", n),
+            (_, Literal::Bool(b), _) => write!(f, "{}", b),
+            _ => FieldDefaultFormatter(self.node).fmt(f),
+        }
+    }
+}
+
 #[derive(Clone, ParseFilter, FilterReflection)]
 #[filter(
     name = "field_default",
@@ -173,8 +328,146 @@ impl Filter for FieldDefaultFilter {
     fn evaluate(&self, input: &dyn ValueView, _: &dyn Runtime) -> Result<Value> {
         let node = LinkedKeyVal::try_from(input.to_value())
             .map_err(|e| Error::with_msg("invalid argument").cause(e))?;
-        Ok(Value::Scalar(
-            format!("{}: {}", node.key(), FieldDefaultFormatter(node.val())).into(),
-        ))
+        match (node.is_optional(), node.default_value()) {
+            (true, _) => Ok(Value::Scalar(
+                format!("{}: Default::default()", node.key()).into(),
+            )),
+            (false, Some(value)) => Ok(Value::Scalar(
+                format!(
+                    "{}: {}",
+                    node.key(),
+                    DefaultValueFormatter {
+                        node: node.val(),
+                        value
+                    }
+                )
+                .into(),
+            )),
+            (false, None) => Ok(Value::Scalar(
+                format!("{}: {}", node.key(), FieldDefaultFormatter(node.val())).into(),
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct FieldValidateJsonArgs {
+    language: Language,
+    index: usize,
+}
+
+#[derive(Debug, FilterParameters)]
+struct FieldValidateArgs {
+    #[parameter(description = "JSON: language:str, index:int")]
+    json: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "field_validate",
+    description = "Render the check of a field against the constraints of its type",
+    parameters(FieldValidateArgs),
+    parsed(FieldValidateFilter)
+)]
+pub struct FieldValidate;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "field_validate"]
+pub struct FieldValidateFilter {
+    #[parameters]
+    args: FieldValidateArgs,
+}
+impl Filter for FieldValidateFilter {
+    fn evaluate(&self, input: &dyn ValueView, runtime: &dyn Runtime) -> Result<Value> {
+        let json = self.args.evaluate(runtime)?.json;
+        let args = serde_json::from_str::<FieldValidateJsonArgs>(json.to_kstr().as_str())
+            .map_err(|e| Error::with_msg(e.to_string()))?;
+        let node = LinkedKeyVal::try_from(input.to_value())
+            .map_err(|e| Error::with_msg("invalid argument").cause(e))?;
+        let valid = match ValidFormatter::new(node.val()) {
+            Some(valid) => valid,
+            None => return Ok(Value::Scalar("".into())),
+        };
+        let member = args.language.fieldify(node.key());
+        let mut ret = String::new();
+        match (args.language, node.is_optional()) {
+            (_, false) => write!(ret, "if !{{ let value = &self.{}; {} }} ", member, valid),
+            (Language::C, true) => {
+                write!(ret, "if !self.{}.get().map_or(true, |value| {}) ", member, valid)
+            }
+            (_, true) => {
+                write!(ret, "if !self.{}.as_ref().map_or(true, |value| {}) ", member, valid)
+            }
+        }
+        .map_err(invalid_fmt)?;
+        write!(
+            ret,
+            "{{ return Err(seedle_extra::validate::Invalid::new({}, {:?})); }}",
+            args.index,
+            node.key()
+        )
+        .map_err(invalid_fmt)?;
+        Ok(Value::Scalar(ret.into()))
+    }
+}
+
+/// An expression that is true when the value of a node satisfies its constraints. IE:
+/// (*value <= 100) for percent: uint .size 1 .le 100
+struct ValidFormatter<'s>(&'s LinkedNode);
+impl<'s> ValidFormatter<'s> {
+    /// None when the node has nothing to check
+    fn new(node: &'s LinkedNode) -> Option<ValidFormatter<'s>> {
+        match node {
+            LinkedNode::Constrained(_) | LinkedNode::Regexp(_) | LinkedNode::ForeignStruct(_) => {
+                Some(ValidFormatter(node))
+            }
+            LinkedNode::Tag(LinkedTag { ty, .. }) => ValidFormatter::new(ty),
+            LinkedNode::Array(LinkedArray { ty, .. }) => {
+                ValidFormatter::new(ty).map(|_| ValidFormatter(node))
+            }
+            _ => None,
+        }
+    }
+}
+impl<'s> fmt::Display for ValidFormatter<'s> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            LinkedNode::Constrained(Constrained { ty, constraints }) => {
+                let checks = constraints
+                    .iter()
+                    .map(|Constraint { op, value }| {
+                        let n = match value {
+                            Literal::Int(i) => *i as i128,
+                            Literal::UInt(u) => *u as i128,
+                            _ => 0,
+                        };
+                        let op = match op {
+                            Comparison::Lt => "<",
+                            Comparison::Le => "<=",
+                            Comparison::Gt => ">",
+                            Comparison::Ge => ">=",
+                            Comparison::Ne => "!=",
+                        };
+                        format!("*value {} {}", op, NumberFormatter { ty, n })
+                    })
+                    .collect::<Vec<String>>();
+                write!(f, "({})", checks.join(" && "))
+            }
+            LinkedNode::Array(LinkedArray { ty, .. }) => match ValidFormatter::new(ty) {
+                Some(valid) => write!(f, "value.iter().all(|value| {})", valid),
+                None => Err(fmt::Error),
+            },
+            LinkedNode::Tag(LinkedTag { ty, .. }) => match ValidFormatter::new(ty) {
+                Some(valid) => valid.fmt(f),
+                None => Err(fmt::Error),
+            },
+            LinkedNode::Regexp(LinkedRegexp { pattern, .. }) => write!(
+                f,
+                "seedle_extra::regexp::Matches::matches(value, {:?})",
+                pattern
+            ),
+            LinkedNode::ForeignStruct(_) => write!(f, "value.validate().is_ok()"),
+            _ => Err(fmt::Error),
+        }
     }
 }
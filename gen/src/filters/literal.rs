@@ -154,7 +154,23 @@ impl<'s> fmt::Display for LiteralFormatter<'s> {
                 ty = "char",
                 val = v
             ),
-            (_, Literal::Bytes(_)) => Err(fmt::Error),
+            (Language::Typescript, Literal::Bytes(v)) => writedoc! {
+                f,
+                r#"
+                    pub const {name}: [u8; {len}] = {val:?};
+                    #[wasm_bindgen(typescript_custom_section)]
+                    const TS_{name}: &'static str = "export type {name} = Uint8Array";"#,
+                name = name,
+                len = v.len(),
+                val = v
+            },
+            (_, Literal::Bytes(v)) => write!(
+                f,
+                "pub const {name}: [u8; {len}] = {val:?};",
+                name = name,
+                len = v.len(),
+                val = v
+            ),
         }
     }
 }
@@ -28,3 +28,127 @@ fn expect_attr_u8_arr() {
         Value::Scalar("#[cbor(n(3), with=\"minicbor::bytes\")]".into())
     );
 }
+
+#[test]
+fn expect_attr_range() {
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        LinkedNode::Range(Range::new(-40, 125).unwrap()),
+    ));
+    let args = r#"{"language":"c", "index": 3, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(AttrField, field, args).unwrap(),
+        Value::Scalar(
+            "#[cbor(n(3), decode_with=\"seedle_extra::range::decode::<_, _, {-40}, {125}>\")]"
+                .into()
+        )
+    );
+}
+
+#[test]
+fn expect_attr_nested_range() {
+    let row = LinkedArray::new(LinkedNode::Range(Range::new(-40, 125).unwrap()), 2);
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        LinkedArray::new(row.into(), 2).into(),
+    ));
+    let args = r#"{"language":"c", "index": 3, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(AttrField, field, args).unwrap(),
+        Value::Scalar(
+            "#[cbor(n(3), decode_with=\"seedle_extra::range::decode::<_, _, {-40}, {125}>\")]"
+                .into()
+        )
+    );
+}
+
+#[test]
+fn expect_attr_optional_range() {
+    let field = Value::from(LinkedKeyVal::optional(
+        "field",
+        LinkedNode::Range(Range::new(1, 14).unwrap()),
+    ));
+    let args = r#"{"language":"c", "index": 3, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(AttrField, field, args).unwrap(),
+        Value::Scalar(
+            "#[cbor(n(3), decode_with=\"seedle_extra::range::decode::<_, _, {1}, {14}>\", \
+             encode_with=\"seedle_extra::optional::encode\", \
+             is_nil=\"seedle_extra::optional::is_nil\", \
+             nil=\"seedle_extra::optional::nil::<seedle_extra::optional::Optional<u8>>\")]"
+                .into()
+        )
+    );
+}
+
+#[test]
+fn expect_attr_half() {
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        LinkedArray::new(ConstrainedPrimative::F16.into(), 2).into(),
+    ));
+    let args = r#"{"language":"c", "index": 1, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(AttrField, field, args).unwrap(),
+        Value::Scalar("#[cbor(n(1), with=\"seedle_extra::float\")]".into())
+    );
+    let field = Value::from(LinkedKeyVal::optional(
+        "field",
+        ConstrainedPrimative::F16.into(),
+    ));
+    assert_eq!(
+        liquid_core::call_filter!(AttrField, field, args).unwrap(),
+        Value::Scalar(
+            "#[cbor(n(1), encode_with=\"seedle_extra::float::encode\", \
+             decode_with=\"seedle_extra::float::decode\", \
+             cbor_len=\"seedle_extra::float::cbor_len\", \
+             is_nil=\"seedle_extra::optional::is_nil\", \
+             nil=\"seedle_extra::optional::nil::<seedle_extra::optional::Optional<f32>>\")]"
+                .into()
+        )
+    );
+}
+
+#[test]
+fn expect_attr_indexed() {
+    let field =
+        Value::from(LinkedKeyVal::new("field_7", ConstrainedPrimative::U8.into()).indexed(7));
+    let args = r#"{"language":"c", "index": 0, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(AttrField, field, args).unwrap(),
+        Value::Scalar("#[n(7)]".into())
+    );
+}
+
+#[test]
+fn expect_attr_tag() {
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        LinkedTag::new(ConstrainedPrimative::U64.into(), 1).into(),
+    ));
+    let args = r#"{"language":"c", "index": 0, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(AttrField, field, args).unwrap(),
+        Value::Scalar(
+            "#[cbor(n(0), encode_with=\"seedle_extra::tag::encode::<_, _, _, {1}>\", \
+             decode_with=\"seedle_extra::tag::decode::<_, _, {1}>\", \
+             cbor_len=\"seedle_extra::tag::cbor_len::<_, _, {1}>\")]"
+                .into()
+        )
+    );
+    let field = Value::from(LinkedKeyVal::optional(
+        "field",
+        LinkedTag::new(ConstrainedPrimative::Bytes(16).into(), 37).into(),
+    ));
+    assert_eq!(
+        liquid_core::call_filter!(AttrField, field, args).unwrap(),
+        Value::Scalar(
+            "#[cbor(n(0), encode_with=\"seedle_extra::tag::bytes::encode::<_, _, _, {37}>\", \
+             decode_with=\"seedle_extra::tag::bytes::decode::<_, _, {37}>\", \
+             cbor_len=\"seedle_extra::tag::bytes::cbor_len::<_, _, {37}>\", \
+             is_nil=\"seedle_extra::optional::is_nil\", \
+             nil=\"seedle_extra::optional::nil::<seedle_extra::optional::Optional<[u8; 16]>>\")]"
+                .into()
+        )
+    );
+}
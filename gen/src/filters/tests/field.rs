@@ -1,4 +1,4 @@
-use crate::filters::field::Field;
+use crate::filters::field::{Field, FieldDefault, FieldValidate};
 use liquid_core::Value;
 use seedle_parser::*;
 
@@ -110,3 +110,272 @@ fn expect_field_struct() {
         Value::Scalar("pub fieldFoo: Option<FooBar>".into())
     );
 }
+
+#[test]
+fn expect_field_choice() {
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        LinkedNode::ForeignChoice("foo_bar".into()),
+    ));
+    let args = r#"{"language": "c", "public": false, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field, args).unwrap(),
+        Value::Scalar("field: foo_bar".into())
+    );
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        LinkedArray::new(LinkedNode::ForeignChoice("foo_bar".into()), 3).into(),
+    ));
+    let args = r#"{"language": "typescript", "public": true, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field, args).unwrap(),
+        Value::Scalar("pub field: [FooBar; 3]".into())
+    );
+}
+
+#[test]
+fn expect_field_enumeration() {
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        LinkedNode::ForeignEnumeration("foo_bar".into()),
+    ));
+    let args = r#"{"language": "rust", "public": true, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field, args).unwrap(),
+        Value::Scalar("pub field: FooBar".into())
+    );
+}
+
+#[test]
+fn expect_field_range() {
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        LinkedNode::Range(Range::new(1, 14).unwrap()),
+    ));
+    let args = r#"{"language": "c", "public": false, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field, args).unwrap(),
+        Value::Scalar("field: u8".into())
+    );
+}
+
+#[test]
+fn expect_field_optional() {
+    let field = Value::from(LinkedKeyVal::optional(
+        "field",
+        ConstrainedPrimative::U16.into(),
+    ));
+    let args = r#"{"language": "c", "public": false, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field, args).unwrap(),
+        Value::Scalar("field: seedle_extra::optional::Optional<u16>".into())
+    );
+    let field = Value::from(LinkedKeyVal::optional(
+        "field",
+        ConstrainedPrimative::U16.into(),
+    ));
+    let args = r#"{"language": "rust", "public": true, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field, args).unwrap(),
+        Value::Scalar("pub field: Option<u16>".into())
+    );
+    let field = Value::from(LinkedKeyVal::optional(
+        "field",
+        ConstrainedPrimative::U16.into(),
+    ));
+    let args = r#"{"language": "rust", "public": true, "required": false}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field, args).unwrap(),
+        Value::Scalar("pub field: Option<u16>".into())
+    );
+}
+
+#[test]
+fn expect_field_bounded_array() {
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        LinkedArray::bounded(ConstrainedPrimative::U16.into(), 0, 4).into(),
+    ));
+    let args = r#"{"language": "c", "public": false, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field, args).unwrap(),
+        Value::Scalar("field: seedle_extra::array::BoundedArray<u16, 0, 4>".into())
+    );
+}
+
+#[test]
+fn expect_field_nested_array() {
+    let row = LinkedArray::new(ConstrainedPrimative::I16.into(), 4);
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        LinkedArray::new(row.into(), 2).into(),
+    ));
+    let args = r#"{"language": "c", "public": false, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field.clone(), args).unwrap(),
+        Value::Scalar("field: [[i16; 4]; 2]".into())
+    );
+    assert_eq!(
+        liquid_core::call_filter!(FieldDefault, field).unwrap(),
+        Value::Scalar("field: [[0; 4]; 2]".into())
+    );
+}
+
+#[test]
+fn expect_field_unbounded() {
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        ConstrainedPrimative::UnboundedStr.into(),
+    ));
+    let args = r#"{"language": "typescript", "public": false, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field, args).unwrap(),
+        Value::Scalar("field: String".into())
+    );
+    let field = Value::from(LinkedKeyVal::new(
+        "field",
+        ConstrainedPrimative::UnboundedBytes.into(),
+    ));
+    assert_eq!(
+        liquid_core::call_filter!(Field, field, args).unwrap(),
+        Value::Scalar("field: Vec<u8>".into())
+    );
+    let args = r#"{"language": "c", "public": false, "required": true}"#;
+    assert!(liquid_core::call_filter!(Field, field, args).is_err());
+}
+
+#[test]
+fn expect_field_any() {
+    let field = Value::from(LinkedKeyVal::new("payload", ConstrainedPrimative::Any(32).into()));
+    let args = r#"{"language": "c", "public": false, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field.clone(), args).unwrap(),
+        Value::Scalar("payload: seedle_extra::any::Any<32>".into())
+    );
+    assert_eq!(
+        liquid_core::call_filter!(FieldDefault, field).unwrap(),
+        Value::Scalar("payload: Default::default()".into())
+    );
+    let field = Value::from(LinkedKeyVal::new(
+        "payload",
+        ConstrainedPrimative::UnboundedAny.into(),
+    ));
+    assert!(liquid_core::call_filter!(Field, field.clone(), args).is_err());
+    let args = r#"{"language": "typescript", "public": false, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field, args).unwrap(),
+        Value::Scalar("payload: seedle_extra::any::AnyBuf".into())
+    );
+}
+
+#[test]
+fn expect_field_default_value() {
+    let field = Value::from(
+        LinkedKeyVal::new("port", ConstrainedPrimative::U16.into()).with_default(80u16.into()),
+    );
+    assert_eq!(
+        liquid_core::call_filter!(FieldDefault, field).unwrap(),
+        Value::Scalar("port: 80".into())
+    );
+    let field = Value::from(
+        LinkedKeyVal::new("name", ConstrainedPrimative::Str(6).into())
+            .with_default(Literal::from("node".to_string())),
+    );
+    assert_eq!(
+        liquid_core::call_filter!(FieldDefault, field).unwrap(),
+        Value::Scalar("name: *b\"node\\x00\\x00\"".into())
+    );
+    let field = Value::from(
+        LinkedKeyVal::new("gain", ConstrainedPrimative::F32.into()).with_default(2u8.into()),
+    );
+    assert_eq!(
+        liquid_core::call_filter!(FieldDefault, field).unwrap(),
+        Value::Scalar("gain: 2.0".into())
+    );
+}
+
+#[test]
+fn expect_field_validate() {
+    let percent = Constrained::new(
+        ConstrainedPrimative::U8,
+        vec![Constraint::new(Comparison::Le, 100u8.into())],
+    );
+    let field = Value::from(LinkedKeyVal::new("percent", percent.clone().into()));
+    let args = r#"{"language": "c", "index": 0}"#;
+    assert_eq!(
+        liquid_core::call_filter!(FieldValidate, field, args).unwrap(),
+        Value::Scalar(
+            "if !{ let value = &self.percent; (*value <= 100) } \
+             { return Err(seedle_extra::validate::Invalid::new(0, \"percent\")); }"
+                .into()
+        )
+    );
+    let floor = Constrained::new(
+        ConstrainedPrimative::F32,
+        vec![
+            Constraint::new(Comparison::Ge, Literal::Int(-40)),
+            Constraint::new(Comparison::Ne, 0u8.into()),
+        ],
+    );
+    let field = Value::from(LinkedKeyVal::optional("floor", floor.into()));
+    let args = r#"{"language": "c", "index": 2}"#;
+    assert_eq!(
+        liquid_core::call_filter!(FieldValidate, field, args).unwrap(),
+        Value::Scalar(
+            "if !self.floor.get().map_or(true, |value| (*value >= -40.0 && *value != 0.0)) \
+             { return Err(seedle_extra::validate::Invalid::new(2, \"floor\")); }"
+                .into()
+        )
+    );
+    let field = Value::from(LinkedKeyVal::new(
+        "steps",
+        LinkedArray::bounded(percent.into(), 0, 4).into(),
+    ));
+    let args = r#"{"language": "c", "index": 1}"#;
+    assert_eq!(
+        liquid_core::call_filter!(FieldValidate, field, args).unwrap(),
+        Value::Scalar(
+            "if !{ let value = &self.steps; value.iter().all(|value| (*value <= 100)) } \
+             { return Err(seedle_extra::validate::Invalid::new(1, \"steps\")); }"
+                .into()
+        )
+    );
+    // Members without constraints are not checked
+    let field = Value::from(LinkedKeyVal::new("port", ConstrainedPrimative::U16.into()));
+    assert_eq!(
+        liquid_core::call_filter!(FieldValidate, field, args).unwrap(),
+        Value::Scalar("".into())
+    );
+}
+
+#[test]
+fn expect_field_regexp_and_bits() {
+    let host = LinkedRegexp::new(ConstrainedPrimative::Str(16), "[a-z]+\\.local");
+    let field = Value::from(LinkedKeyVal::new("host", host.into()));
+    let args = r#"{"language": "c", "public": true, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field.clone(), args).unwrap(),
+        Value::Scalar("pub host: [u8; 16]".into())
+    );
+    let args = r#"{"language": "c", "index": 0}"#;
+    assert_eq!(
+        liquid_core::call_filter!(FieldValidate, field, args).unwrap(),
+        Value::Scalar(
+            "if !{ let value = &self.host; \
+             seedle_extra::regexp::Matches::matches(value, \"[a-z]+\\\\.local\") } \
+             { return Err(seedle_extra::validate::Invalid::new(0, \"host\")); }"
+                .into()
+        )
+    );
+    let status = LinkedBits::new(ConstrainedPrimative::U8, vec![Bit::new("ready", 0)]);
+    let field = Value::from(LinkedKeyVal::new("status", status.into()));
+    let args = r#"{"language": "c", "public": true, "required": true}"#;
+    assert_eq!(
+        liquid_core::call_filter!(Field, field.clone(), args).unwrap(),
+        Value::Scalar("pub status: u8".into())
+    );
+    assert_eq!(
+        liquid_core::call_filter!(FieldDefault, field).unwrap(),
+        Value::Scalar("status: Default::default()".into())
+    );
+}
@@ -5,15 +5,15 @@ use seedle_parser::{LinkedNode, Literal};
 #[test]
 fn expect_collect_group_literals() {
     const TEST_DATA: &'static str = r#"
-        litchar = 'a'
+        litchar = "a"
         litbool = true
         litstr = "hello"
         litnum = 3
-        foo_char = 'a'
+        foo_char = "a"
         foo_bool = true
         foo_str = "hello"
         foo_num = 3
-        bar_char = 'a'
+        bar_char = "a"
         bar_bool = true
         bar_str = "hello"
         bar_num = 3
@@ -49,3 +49,21 @@ fn expect_literal_char() {
         Value::Scalar("pub const FOO: char = 'a';".into())
     );
 }
+
+#[test]
+fn expect_literal_bytes() {
+    let literal = Value::from(seedle_parser::Literal::Bytes(vec![0xde, 0xad, 0xbe, 0xef]));
+    assert_eq!(
+        liquid_core::call_filter!(Literal, literal.clone(), "c", "foo").unwrap(),
+        Value::Scalar("pub const FOO: [u8; 4] = [222, 173, 190, 239];".into())
+    );
+    let expect = concat!(
+        "pub const FOO: [u8; 4] = [222, 173, 190, 239];\n",
+        "#[wasm_bindgen(typescript_custom_section)]\n",
+        "const TS_FOO: &'static str = \"export type FOO = Uint8Array\";"
+    );
+    assert_eq!(
+        liquid_core::call_filter!(Literal, literal, "typescript", "foo").unwrap(),
+        Value::Scalar(expect.into())
+    );
+}
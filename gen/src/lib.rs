@@ -5,8 +5,9 @@ pub mod templates;
 use liquid::Parser as LiquidParser;
 use liquid_core::model::KString;
 use liquid_core::{Object, Value, ValueView};
-use seedle_parser::FlattenError;
+use seedle_parser::{FlattenError, Sources};
 use std::collections::BTreeMap;
+use std::path::Path;
 
 pub struct Parser {
     context: Object,
@@ -23,6 +24,7 @@ impl Parser {
             .filter(crate::filters::collect::Collect)
             .filter(crate::filters::field::Field)
             .filter(crate::filters::field::FieldDefault)
+            .filter(crate::filters::field::FieldValidate)
             .filter(crate::filters::attrs::AttrField)
             .build()
             .map(|parser| Parser { parser, context })
@@ -37,6 +39,21 @@ impl Parser {
         Ok(())
     }
 
+    /// Load a CDDL file merged with the files it includes
+    pub fn load_cddl_file<K: Into<KString>, P: AsRef<Path>>(
+        &mut self,
+        key: K,
+        path: P,
+    ) -> Result<(), FlattenError> {
+        let nodes = Sources::read(path)?
+            .parse()?
+            .into_iter()
+            .map(|(k, v)| (k, Value::from(v)))
+            .collect::<BTreeMap<_, Value>>();
+        self.context.insert(key.into(), nodes.to_value());
+        Ok(())
+    }
+
     pub fn render(&self, text: &str) -> liquid_core::Result<String> {
         self.parser.parse(text)?.render(&self.context)
     }
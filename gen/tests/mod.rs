@@ -88,7 +88,12 @@ i64 = int .size 8
 ip-addr = tstr .size 16
 port = { 
     http: u16, 
-    label: tstr .size 32 
+    label: tstr .size 32,
+    gain: float32,
+    drift: float64,
+    calib: [ 2*2 float16 ],
+    ? offset: float16,
+    ? 32: u8
 }
 ints = (
     a: u8,
@@ -106,7 +111,11 @@ network = {
     sn: ip-addr,
     gw: ip-addr,
     mac: [ 6*6 u8 ],
-    ids: [ 2*2 u32 ]
+    ids: [ 2*2 u32 ],
+    ? dns: ip-addr,
+    ? mtu: u16,
+    vlans: [ 0*4 u16 ],
+    key: [ 1*16 u8 ]
 }
 thing = {
     ints,
@@ -114,4 +123,24 @@ thing = {
     net: network,
     ports: [ 4*4 port ]
 }
+sample = [ at: u32, value: i16, ? unit: u8 ]
+event = {
+    created: #6.1(u64),
+    id: #6.37(bytes .size 16),
+    ? expires: #6.1(u64)
+}
+settings = {
+    port: u16 .default 80,
+    name: tstr .size 16 .default "node",
+    gain: float32 .default 2,
+    limit: i8 .default -40
+}
+window = [ low: u16 .lt 1000, high: u16 .gt 0 ]
+limits = {
+    percent: u8 .le 100,
+    ? floor: i16 .ge -40 .lt 0,
+    ratio: float32 .gt 0,
+    steps: [ 0*4 u8 .ne 0 ],
+    window: window
+}
 "#;
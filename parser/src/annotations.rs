@@ -0,0 +1,129 @@
+use super::error::*;
+use std::collections::BTreeMap;
+
+/// Key numbers pinned to rules with an annotation in the comments directly above them, so that
+/// the wire ids of the rules stay stable as rules are added or renamed. IE:
+///
+/// ```cddl
+/// ; @key 4
+/// alarm = { ... }
+/// ```
+pub fn pinned_keys(cddl: &str) -> FlattenResult<BTreeMap<String, u8>> {
+    let mut keys = BTreeMap::new();
+    let mut pinned: BTreeMap<u8, String> = BTreeMap::new();
+    let mut pending = None;
+    for line in cddl.lines() {
+        let line = line.trim();
+        if let Some(comment) = line.strip_prefix(';') {
+            if let Some(key) = comment.trim().strip_prefix("@key") {
+                pending = Some(key.trim().to_string());
+            }
+        } else if let Some(rule) = rule_name(line) {
+            if let Some(key) = pending.take() {
+                let key = key.parse::<u8>().map_err(|_| {
+                    FlattenError::InvalidKeyAnnotation(key)
+                        .in_rule(rule)
+                        .locate(cddl)
+                })?;
+                if pinned.insert(key, rule.to_string()).is_some() {
+                    let e = FlattenError::DuplicateKeyAnnotation(key).in_rule(rule);
+                    return Err(e.locate(cddl));
+                }
+                keys.insert(rule.to_string(), key);
+            }
+        } else if !line.is_empty() {
+            pending = None;
+        }
+    }
+    Ok(keys)
+}
+
+/// Names given to integer keyed members by the comment that ends their line, so that their
+/// fields are not named field_N. Only the members of the map a rule declares are named. IE:
+///
+/// ```cddl
+/// reading = {
+///     1: uint .size 2, ; celsius
+///     2: uint .size 4, ; at
+/// }
+/// ```
+pub(crate) fn member_names(cddl: &str) -> BTreeMap<String, BTreeMap<u32, String>> {
+    let mut names: BTreeMap<String, BTreeMap<u32, String>> = BTreeMap::new();
+    let mut rule = None;
+    for line in cddl.lines() {
+        if let Some(name) = rule_name(line) {
+            rule = Some(name);
+        }
+        let comment = line.match_indices(';').find(|(n, _)| !in_text(&line[..*n]));
+        let (code, comment) = match comment {
+            Some((n, _)) => (&line[..n], line[n + 1..].trim()),
+            None => continue,
+        };
+        let is_name = |c: char| c.is_alphanumeric() || matches!(c, '-' | '_');
+        if comment.is_empty() || !comment.chars().all(is_name) {
+            continue;
+        }
+        let member = code.rsplit(',').map(str::trim).find(|m| !m.is_empty());
+        if let (Some(rule), Some(index)) = (rule, member.and_then(member_index)) {
+            let members = names.entry(rule.to_string()).or_default();
+            members.insert(index, comment.to_string());
+        }
+    }
+    names
+}
+
+/// True when the end of a line of CDDL is inside a text literal
+fn in_text(code: &str) -> bool {
+    let mut quoted = false;
+    let mut escaped = false;
+    for c in code.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = quoted,
+            '"' => quoted = !quoted,
+            _ => {}
+        }
+    }
+    quoted
+}
+
+/// The integer key of a member. IE: 1 of ? 1: uint or 1 => uint
+fn member_index(member: &str) -> Option<u32> {
+    let starts = |n: usize| n == 0 || member[..n].ends_with(|c: char| "{([?*+ \t".contains(c));
+    member
+        .char_indices()
+        .filter(|(n, c)| c.is_ascii_digit() && starts(*n))
+        .find_map(|(n, _)| {
+            let digits = member[n..].find(|c: char| !c.is_ascii_digit());
+            let (index, rest) = member[n..].split_at(digits.unwrap_or(member.len() - n));
+            let rest = rest.trim_start();
+            match rest.starts_with(':') || rest.starts_with("=>") {
+                true => index.parse().ok(),
+                false => None,
+            }
+        })
+}
+
+/// Files named by include directives in the comments of a schema. IE: ; @include "common.cddl"
+pub(crate) fn includes(cddl: &str) -> Vec<&str> {
+    cddl.lines()
+        .filter_map(|line| line.trim().strip_prefix(';'))
+        .filter_map(|comment| comment.trim().strip_prefix("@include"))
+        .map(|file| file.trim().trim_matches('"'))
+        .collect()
+}
+
+/// The name of the rule a line of CDDL declares, if the line starts a rule. IE: foo = ...
+pub(crate) fn rule_name(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let is_name = |c: char| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '@' | '$');
+    let name = &line[..line.find(|c| !is_name(c)).unwrap_or(line.len())];
+    let rest = line[name.len()..].trim_start();
+    let assigns = (rest.starts_with('=') && !rest.starts_with("=>"))
+        || rest.starts_with("/=")
+        || rest.starts_with('<');
+    match !name.is_empty() && assigns {
+        true => Some(name),
+        false => None,
+    }
+}
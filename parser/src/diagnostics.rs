@@ -0,0 +1,113 @@
+use super::{error::*, flatten, generics, hoist, link, node::*, rules::Rules};
+use std::collections::BTreeSet;
+
+/// Every problem found in a schema. Errors prevent a rule from being linked, warnings do not
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    pub errors: Vec<FlattenError>,
+    pub warnings: Vec<FlattenError>,
+}
+
+impl Diagnostics {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Parse a schema and report every error and warning instead of stopping on the first error.
+/// The rules that linked without error are returned with the diagnostics
+pub fn parse_diagnostics(cddl: &str) -> (Rules<LinkedNode>, Diagnostics) {
+    let mut diagnostics = Diagnostics::default();
+    let rules = match flatten::flatten_rules(cddl) {
+        Ok(rules) => rules,
+        Err(e) => {
+            diagnostics.errors.push(e.locate(cddl));
+            return (Rules::new(), diagnostics);
+        }
+    };
+
+    // Rules that failed to flatten are remembered so that references to them are not reported
+    let mut failed = BTreeSet::new();
+    let mut nodes = Rules::new();
+    for rule in rules {
+        match rule {
+            Ok((key, node)) => {
+                nodes.insert(key, node);
+            }
+            Err(e) => {
+                failed.extend(e.rule().map(str::to_string));
+                diagnostics.errors.push(e.locate(cddl));
+            }
+        }
+    }
+
+    // A rule whose instances or inline structs can not be expanded is dropped, the rest are linked
+    let (nodes, mut errors) = generics::monomorphize_each(nodes, &mut failed);
+    let (nodes, hoisted) = hoist::hoist_each(nodes, &mut failed);
+    errors.extend(hoisted);
+    for e in errors {
+        diagnostics.errors.push(e.locate(cddl));
+    }
+
+    // An error inside a referenced rule is reported when that rule is linked, not by every rule
+    // that references it
+    let mut linked = Rules::new();
+    for (key, node) in nodes.clone() {
+        match link::link_rule(key.clone(), node, &nodes) {
+            Ok((key, node)) => {
+                linked.insert(key, node);
+            }
+            Err(e) if e.rule() != Some(&key) => {}
+            // A cycle that is only reached through this rule is reported by the rules of the cycle
+            Err(e) if matches!(e.kind(), FlattenError::Cycle(path) if !path.contains(&key)) => {}
+            Err(e) => match e.kind() {
+                FlattenError::ForeignKey(foreign) if failed.contains(foreign) => {}
+                _ => diagnostics.errors.push(e.locate(cddl)),
+            },
+        }
+    }
+
+    diagnostics.warnings = unused_groups(&nodes)
+        .into_iter()
+        .map(|key| FlattenError::UnusedGroup.in_rule(&key).locate(cddl))
+        .collect();
+    (linked, diagnostics)
+}
+
+/// Groups are only emitted as part of a struct, so a group nothing refers to is likely a mistake
+fn unused_groups(nodes: &Rules<Node>) -> Vec<String> {
+    let mut used = BTreeSet::new();
+    nodes.values().for_each(|node| references(node, &mut used));
+    nodes
+        .iter()
+        .filter(|(key, node)| matches!(node, Node::Group(_)) && !used.contains(*key))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+fn references(node: &Node, found: &mut BTreeSet<String>) {
+    match node {
+        Node::Foreign(key) => {
+            found.insert(key.clone());
+        }
+        Node::Array(array) => references(&array.ty, found),
+        Node::Tag(tag) => references(&tag.ty, found),
+        Node::Default(default) => references(&default.ty, found),
+        Node::Compare(compare) => references(&compare.ty, found),
+        Node::Regexp(regexp) => references(&regexp.ty, found),
+        Node::Bits(bits) => {
+            references(&bits.names, found);
+            references(&bits.ty, found)
+        }
+        Node::Optional(node) | Node::Unwrap(node) | Node::Nullable(node) => references(node, found),
+        Node::KeyVal(KeyVal(_, node, ..)) => references(node, found),
+        Node::Group(Group { members })
+        | Node::Map(Group { members })
+        | Node::Record(Group { members })
+        | Node::Names(Group { members })
+        | Node::Choice(Choice { members }) => {
+            members.iter().for_each(|node| references(node, found))
+        }
+        _ => {}
+    }
+}
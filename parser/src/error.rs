@@ -1,10 +1,14 @@
+use super::{annotations::rule_name, rewrite::rewrite};
 use cddl_cat::parser::ParseError;
-use std::{error, fmt};
+use std::{error, fmt, io};
 
 pub type FlattenResult<T> = std::result::Result<T, FlattenError>;
 
 #[derive(Debug)]
 pub enum FlattenError {
+    /// The CDDL could not be parsed. The error is located in the rule its context is found in, and
+    /// its context quotes the source as it was rewritten before it was parsed, IE: with controls
+    /// wrapped in parentheses
     Parser(ParseError),
     InvalidEnum0,
     InvalidUnconstrainedPrimative,
@@ -12,43 +16,404 @@ pub enum FlattenError {
     InvalidControl,
     InvalidControlArg,
     InvalidGroupMissingKey,
+    InvalidMemberKey,
+    /// The key, the key of the member that reuses it as it is written, and how many members before
+    /// it are written with the same key
+    DuplicateMemberKey(u32, String, usize),
     InvalidType,
+    InvalidGroupMember(String),
+    InvalidUnwrap(String),
     InvalidSizeConstraint(String, u64),
     InvalidArray,
     InvalidArraySize,
-    TodoEnums,
-    NotSupportedRange,
+    InvalidRecordMember,
+    InvalidChoiceMember,
+    InvalidEnumeration,
+    DuplicateEnumerationValue(String),
+    EnumerationNameClash(String, String),
+    InvalidRange,
     NotSupportedChoice,
     NotSupportedGenerics,
+    InvalidGenericArgs(String),
+    InvalidGenericArg,
+    InvalidTag,
+    InvalidTagArg,
+    InvalidDefault(String),
+    InvalidDefaultArg,
+    InvalidComparison,
+    InvalidRegexp,
+    InvalidRegexpArg,
+    InvalidBits,
+    InvalidBitsArg,
+    InvalidNil,
     NotSupportedControl(String),
     NotSupportedGroupname(String),
     ForeignKey(String),
+    Cycle(Vec<String>),
+    InvalidKeyAnnotation(String),
+    DuplicateKeyAnnotation(u8),
+    DuplicateRule(Location),
+    HoistedRule(String),
+    InstancedRule(String, String),
+    InstanceClash(String, String),
+    Include(String, io::Error),
+    UnusedGroup,
     Infallible,
+    /// An error raised while flattening or linking a rule
+    Rule(Box<RuleError>),
+}
+
+/// The rule an error was raised in, and where the rule is defined once known
+#[derive(Debug)]
+pub struct RuleError {
+    pub rule: String,
+    pub location: Option<Location>,
+    pub error: FlattenError,
+}
+
+/// A line and column (starting at 1) in the CDDL source, and the line of source it points into.
+/// The file is known when the source was merged from several files
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub snippet: String,
+}
+
+impl FlattenError {
+    /// Note the rule an error was raised in. The innermost rule is kept when rules are nested
+    pub(crate) fn in_rule(self, rule: &str) -> FlattenError {
+        match self {
+            FlattenError::Rule(e) => FlattenError::Rule(e),
+            error => FlattenError::Rule(Box::new(RuleError {
+                rule: rule.to_string(),
+                location: None,
+                error,
+            })),
+        }
+    }
+
+    /// Find where the rule of an error is defined in the CDDL source, or the offending name inside
+    /// the rule when the error has one
+    pub(crate) fn locate(self, cddl: &str) -> FlattenError {
+        match self {
+            FlattenError::Rule(mut e) => {
+                e.location = find_rule(cddl, &e.rule).map(|start| {
+                    let at = e
+                        .error
+                        .find_offending(&cddl[start..])
+                        .map_or(start, |offset| start + offset);
+                    Location::new(cddl, at)
+                });
+                FlattenError::Rule(e)
+            }
+            FlattenError::Parser(e) => match find_context(cddl, &e.ctx) {
+                Some((rule, at)) => FlattenError::Rule(Box::new(RuleError {
+                    rule: rule.to_string(),
+                    location: Some(Location::new(cddl, at)),
+                    error: FlattenError::Parser(e),
+                })),
+                None => FlattenError::Parser(e),
+            },
+            error => error,
+        }
+    }
+
+    /// The error without the rule it was raised in
+    pub fn kind(&self) -> &FlattenError {
+        match self {
+            FlattenError::Rule(e) => &e.error,
+            error => error,
+        }
+    }
+
+    pub fn into_kind(self) -> FlattenError {
+        match self {
+            FlattenError::Rule(e) => e.error,
+            error => error,
+        }
+    }
+
+    pub fn rule(&self) -> Option<&str> {
+        match self {
+            FlattenError::Rule(e) => Some(&e.rule),
+            _ => None,
+        }
+    }
+
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            FlattenError::Rule(e) => e.location.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The offset of the name an error is about in the source of its rule
+    fn find_offending(&self, text: &str) -> Option<usize> {
+        match self {
+            // The type of a member, not a member named like it
+            FlattenError::ForeignKey(name)
+            | FlattenError::InvalidGroupMember(name)
+            | FlattenError::InvalidUnwrap(name) => {
+                find_words(text, name).find(|n| !is_member_key(&text[n + name.len()..]))
+            }
+            FlattenError::DuplicateMemberKey(_, key, nth) => find_words(text, key)
+                .filter(|n| is_member_key(&text[n + key.len()..]))
+                .nth(*nth),
+            error => find_words(text, error.offending_name()?).next(),
+        }
+    }
+
+    fn offending_name(&self) -> Option<&str> {
+        match self {
+            FlattenError::NotSupportedGroupname(name)
+            | FlattenError::ForeignKey(name)
+            | FlattenError::InvalidGenericArgs(name)
+            | FlattenError::InvalidDefault(name)
+            | FlattenError::DuplicateEnumerationValue(name)
+            | FlattenError::EnumerationNameClash(name, _)
+            | FlattenError::InstancedRule(name, _)
+            | FlattenError::InstanceClash(name, _) => Some(name),
+            FlattenError::NotSupportedControl(ctrl) => Some(ctrl),
+            _ => None,
+        }
+    }
+}
+
+impl Location {
+    pub(crate) fn new(cddl: &str, offset: usize) -> Location {
+        let start = cddl[..offset].rfind('\n').map_or(0, |n| n + 1);
+        let end = cddl[offset..].find('\n').map_or(cddl.len(), |n| offset + n);
+        Location {
+            file: None,
+            line: cddl[..offset].matches('\n').count() + 1,
+            column: cddl[start..offset].chars().count() + 1,
+            snippet: cddl[start..end].trim_end().to_string(),
+        }
+    }
+}
+
+/// The offset of the line that declares a rule
+fn find_rule(cddl: &str, rule: &str) -> Option<usize> {
+    let mut offset = 0;
+    for line in cddl.split_inclusive('\n') {
+        if rule_name(line) == Some(rule) {
+            return Some(offset + line.len() - line.trim_start().len());
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// The rule a parser error was raised in, and the offset of its context. The context is the source
+/// that was left to parse, or the literal that could not be read, in the source as it was
+/// rewritten. The rewrite leaves each line where it was, so the context is found on the same line
+/// of the source, where the rest of its line is, or where it was on the rewritten line
+fn find_context<'a>(cddl: &'a str, ctx: &str) -> Option<(&'a str, usize)> {
+    if ctx.is_empty() {
+        return None;
+    }
+    let rewritten = rewrite(cddl);
+    let found = match rewritten.ends_with(ctx) {
+        true => rewritten.len() - ctx.len(),
+        false => rewritten.find(ctx)?,
+    };
+    let line = rewritten[..found].matches('\n').count();
+    let column = found - rewritten[..found].rfind('\n').map_or(0, |n| n + 1);
+    let rest = ctx.lines().next().unwrap_or_default();
+
+    let lines = cddl.split_inclusive('\n').collect::<Vec<&str>>();
+    let text = lines.get(line)?.trim_end_matches(['\n', '\r']);
+    let rule = lines[..=line]
+        .iter()
+        .rev()
+        .find_map(|text| rule_name(text))?;
+    let start = lines[..line].iter().map(|text| text.len()).sum::<usize>();
+    let column = match text.ends_with(rest) {
+        true => text.len() - rest.len(),
+        false => text.find(rest).unwrap_or(column.min(text.len())),
+    };
+    let column = match text.is_char_boundary(column) {
+        true => column,
+        false => 0,
+    };
+    Some((rule, start + column))
+}
+
+/// The offsets of a name where it is not part of a longer name
+fn find_words<'a>(text: &'a str, word: &'a str) -> impl Iterator<Item = usize> + 'a {
+    let is_name = |c: char| c.is_alphanumeric() || c == '-' || c == '_' || c == '.';
+    text.match_indices(word).map(|(n, _)| n).filter(move |n| {
+        let before = text[..*n].chars().next_back();
+        let after = text[n + word.len()..].chars().next();
+        !before.is_some_and(is_name) && !after.is_some_and(is_name)
+    })
+}
+
+/// Whether the text after a name makes it the key of a member, IE: on: bool or "on" => bool
+fn is_member_key(rest: &str) -> bool {
+    let rest = rest.strip_prefix('"').unwrap_or(rest).trim_start();
+    rest.starts_with(':') || rest.starts_with("=>")
 }
 
 impl fmt::Display for FlattenError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use FlattenError::*;
         match self {
-            Parser(e) => e.fmt(f),
+            Parser(e) => match e.ctx.lines().next() {
+                Some(ctx) => write!(f, "failed to parse [{}] ({:?})", ctx.trim(), e.kind),
+                None => write!(f, "failed to parse ({:?})", e.kind),
+            },
             InvalidEnum0 => write!(f, "Enum type with 0 members unsupported"),
             InvalidUnconstrainedPrimative => write!(f, "type must be constrained"),
             InvalidLiteral => write!(f, "invalid literal"),
             InvalidControl => write!(f, "size control only supported on primative types"),
             InvalidControlArg => write!(f, "only integers supported for control args"),
             InvalidGroupMissingKey => write!(f, "all group members must have a key"),
+            InvalidMemberKey => write!(f, "member keys must be names or unsigned integers"),
+            DuplicateMemberKey(n, key, _) if *key == n.to_string() => {
+                write!(f, "duplicate member key [{}]", n)
+            }
+            DuplicateMemberKey(n, key, _) => {
+                write!(
+                    f,
+                    "member [{}] is keyed by its position [{}], a duplicate key",
+                    key, n
+                )
+            }
             InvalidType => write!(f, "invalid type"),
+            InvalidGroupMember(key) => {
+                write!(
+                    f,
+                    "[{}] is not a group and can not be a member of a struct",
+                    key
+                )
+            }
+            InvalidUnwrap(key) => write!(
+                f,
+                "only maps, records and groups can be unwrapped [~{}]",
+                key
+            ),
             InvalidSizeConstraint(p, s) => write!(f, "invalid size constraint {} for {}", s, p),
             InvalidArray => write!(f, "invalid array"),
             InvalidArraySize => write!(f, "invalid array size"),
-            TodoEnums => write!(f, "enums not supported"),
-            NotSupportedRange => write!(f, "ranges are not supported"),
+            InvalidRecordMember => write!(f, "record members must be named, optional at the end"),
+            InvalidChoiceMember => write!(f, "choice members must be named types"),
+            InvalidEnumeration => write!(
+                f,
+                "enumerations must be all text, or integers that all fit an i64 or a u64"
+            ),
+            DuplicateEnumerationValue(value) => {
+                write!(f, "value [{}] is in the enumeration more than once", value)
+            }
+            EnumerationNameClash(value, other) => write!(
+                f,
+                "values [{}] and [{}] name the same variant of the enumeration",
+                value, other
+            ),
+            InvalidRange => write!(f, "ranges must be bound by integers and not empty"),
             NotSupportedChoice => write!(f, "choices are not supported"),
             NotSupportedGenerics => write!(f, "generics are not supported"),
+            InvalidGenericArgs(name) => {
+                write!(f, "wrong number of generic arguments for [{}]", name)
+            }
+            InvalidGenericArg => write!(f, "generic arguments must be named or primative types"),
+            InvalidTag => write!(f, "tags must hold a primative or named type"),
+            InvalidTagArg => write!(
+                f,
+                "tag control takes a type and an unsigned tag number, IE: #6.1(uint .size 8)"
+            ),
+            InvalidDefault(key) => write!(f, "default of [{}] does not match its type", key),
+            InvalidDefaultArg => write!(
+                f,
+                "default control takes a literal value, IE: .default 80 or .default \"node\""
+            ),
+            InvalidComparison => write!(
+                f,
+                "comparisons must compare a number with a value of its type"
+            ),
+            InvalidRegexp => write!(f, "patterns must be well formed and matched against a tstr"),
+            InvalidRegexpArg => write!(
+                f,
+                "regexp control takes a text pattern, IE: .regexp \"[a-z]+\""
+            ),
+            InvalidBits => write!(f, "bits must be distinct named positions of a uint"),
+            InvalidBitsArg => write!(
+                f,
+                "bits control takes a group or a rule of named bits, IE: .bits &( ready: 0 )"
+            ),
+            InvalidNil => write!(
+                f,
+                "nil must be a variant of a choice or make a type nullable"
+            ),
             NotSupportedControl(ctrl) => write!(f, "control [{}] not supported", ctrl),
             NotSupportedGroupname(name) => write!(f, "group names not supported, found [{}]", name),
             ForeignKey(key) => write!(f, "foreign key not defined [{}]", key),
+            Cycle(path) => write!(f, "type references form a cycle [{}]", path.join(" -> ")),
+            InvalidKeyAnnotation(key) => write!(f, "invalid key annotation [{}]", key),
+            DuplicateKeyAnnotation(key) => write!(f, "key [{}] is pinned more than once", key),
+            DuplicateRule(first) => write!(f, "rule is already declared at {}", first),
+            HoistedRule(key) => {
+                write!(f, "inline type is named [{}], which is already a rule", key)
+            }
+            InstancedRule(generic, key) => write!(
+                f,
+                "instance of [{}] is named [{}], which is already a rule",
+                generic, key
+            ),
+            InstanceClash(generic, key) => write!(
+                f,
+                "instance of [{}] is named [{}], which names an instance with other arguments",
+                generic, key
+            ),
+            Include(file, e) => write!(f, "failed to include [{}]: {}", file, e),
+            UnusedGroup => write!(f, "group is never used"),
             Infallible => write!(f, "infallible"),
+            Rule(e) => e.fmt(f),
+        }
+    }
+}
+
+/// Render the rule of an error with a snippet of the source, IE:
+///
+/// ```text
+/// foreign key not defined [temperature]
+///   --> rule [reading] at 12:30
+///    |
+/// 12 | reading = { at: bool, value: temperature }
+///    |                              ^
+/// ```
+///
+/// Only errors that carry the name they reject, IE: a foreign key or a control, point at the
+/// name. Other errors point at the start of their rule.
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(at) => {
+                let gutter = " ".repeat(at.line.to_string().len());
+                let pad = at
+                    .snippet
+                    .chars()
+                    .take(at.column - 1)
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect::<String>();
+                writeln!(f, "{}", self.error)?;
+                writeln!(f, "{}--> rule [{}] at {}", gutter, self.rule, at)?;
+                writeln!(f, "{} |", gutter)?;
+                writeln!(f, "{} | {}", at.line, at.snippet)?;
+                write!(f, "{} | {}^", gutter, pad)
+            }
+            None => write!(f, "{} in rule [{}]", self.error, self.rule),
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}:{}", file, self.line, self.column),
+            None => write!(f, "{}:{}", self.line, self.column),
         }
     }
 }
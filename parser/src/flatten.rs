@@ -1,11 +1,63 @@
+use super::annotations::member_names;
 use super::error::*;
-use super::node::{Array, ConstrainedPrimative, Group, KeyVal, Literal, Node, Primative};
+use super::node::{
+    Array, Bits, Choice, Compare, Comparison, ConstrainedPrimative, Constraint, DefaultValue,
+    Enumeration, Generic, Group, Instance, KeyVal, Literal, Node, Primative, Range, Regexp, Tag,
+};
+use super::rewrite::rewrite;
+use super::rules::Rules;
 use cddl_cat::{self, ast};
 use std::collections::BTreeMap;
 
-pub(crate) fn flatten(cddl: &str) -> FlattenResult<BTreeMap<String, Node>> {
-    let ast = cddl_cat::parse_cddl(cddl).map_err(FlattenError::from)?;
-    ast.rules.into_iter().map(flatten_rule).collect()
+pub(crate) fn flatten(cddl: &str) -> FlattenResult<Rules<Node>> {
+    flatten_rules(cddl)?.into_iter().collect()
+}
+
+/// Flatten each rule on its own so that every bad rule can be reported
+pub(crate) fn flatten_rules(cddl: &str) -> FlattenResult<Vec<FlattenResult<(String, Node)>>> {
+    let ast = cddl_cat::parse_cddl(&rewrite(cddl)).map_err(FlattenError::from)?;
+    let names = member_names(cddl);
+    Ok(ast
+        .rules
+        .into_iter()
+        .map(|rule| {
+            let name = rule.name.clone();
+            flatten_rule(rule)
+                .map(|(key, node)| {
+                    let node = name_members(node, names.get(&key));
+                    (key, node)
+                })
+                .map_err(|e| e.in_rule(&name))
+        })
+        .collect())
+}
+
+/// Integer keyed members of a map are named by the comment on their line when there is one, and
+/// the name is not taken by another member. See [`member_names`]
+fn name_members(node: Node, names: Option<&BTreeMap<u32, String>>) -> Node {
+    match (node, names) {
+        (Node::Map(mut group), Some(names)) => {
+            let taken = |name: &String, group: &Group| {
+                group
+                    .members
+                    .iter()
+                    .any(|m| matches!(m, Node::KeyVal(kv) if kv.0 == *name))
+            };
+            for n in 0..group.members.len() {
+                let name = match &group.members[n] {
+                    Node::KeyVal(KeyVal(_, _, _, Some(index))) => names.get(index),
+                    _ => None,
+                };
+                if let Some(name) = name.filter(|name| !taken(name, &group)) {
+                    if let Node::KeyVal(keyval) = &mut group.members[n] {
+                        keyval.0 = name.clone();
+                    }
+                }
+            }
+            Node::Map(group)
+        }
+        (node, _) => node,
+    }
 }
 
 fn flatten_rule(rule: ast::Rule) -> FlattenResult<(String, Node)> {
@@ -13,7 +65,14 @@ fn flatten_rule(rule: ast::Rule) -> FlattenResult<(String, Node)> {
         ast::RuleVal::AssignType(t) => flatten_type(t)?,
         ast::RuleVal::AssignGroup(g) => flatten_groupentry(g)?,
     };
-    Ok((rule.name, node))
+    match rule.generic_parms.is_empty() {
+        true => Ok((rule.name, node)),
+        false => {
+            let params = rule.generic_parms;
+            let node = Box::new(node);
+            Ok((rule.name, Node::Generic(Generic { params, node })))
+        }
+    }
 }
 
 fn flatten_type(ty: ast::Type) -> FlattenResult<Node> {
@@ -21,17 +80,33 @@ fn flatten_type(ty: ast::Type) -> FlattenResult<Node> {
         ty.0.into_iter()
             .map(flatten_type1)
             .collect::<FlattenResult<Vec<Node>>>()?;
+    // A choice between a single type and nil is that type made nullable. IE: u8 / nil
+    let nil = choices.iter().filter(|n| matches!(n, Node::Nil)).count();
+    if choices.len() == 2 && nil == 1 {
+        let node = choices.into_iter().find(|n| !matches!(n, Node::Nil));
+        return Ok(Node::Nullable(Box::new(node.unwrap())));
+    }
     match choices.len() {
         0 => Err(FlattenError::InvalidEnum0),
         1 => Ok(choices.into_iter().next().unwrap()),
-        _ => Err(FlattenError::TodoEnums),
+        _ if choices.iter().all(|n| matches!(n, Node::Literal(_))) => {
+            let literals = choices
+                .into_iter()
+                .filter_map(|n| match n {
+                    Node::Literal(lit) => Some(lit),
+                    _ => None,
+                })
+                .collect::<Vec<Literal>>();
+            Enumeration::try_from(literals).map(Node::Enumeration)
+        }
+        _ => Ok(Node::Choice(Choice { members: choices })),
     }
 }
 
 fn flatten_type1(ty1: ast::Type1) -> FlattenResult<Node> {
     match ty1 {
         ast::Type1::Simple(ty2) => flatten_type2(ty2),
-        ast::Type1::Range(_) => Err(FlattenError::NotSupportedRange),
+        ast::Type1::Range(range) => flatten_range(range),
         ast::Type1::Control(ctrl) => flatten_control(ctrl),
     }
 }
@@ -44,11 +119,31 @@ fn flatten_type2(ty2: ast::Type2) -> FlattenResult<Node> {
         Type2::Parethesized(t) => flatten_type(t),
         Type2::Map(g) => flatten_map(g),
         Type2::Array(g) => flatten_array(g),
-        Type2::Unwrap(_) => Err(FlattenError::NotSupportedGenerics),
-        Type2::Choiceify(_) | Type2::ChoiceifyInline(_) => Err(FlattenError::NotSupportedChoice),
+        Type2::Unwrap(t) => flatten_typename(t).map(|node| Node::Unwrap(Box::new(node))),
+        Type2::ChoiceifyInline(g) => flatten_group(g).map(|members| Node::Names(Group { members })),
+        Type2::Choiceify(name) => flatten_typename(name).map(|node| {
+            let members = vec![node];
+            Node::Names(Group { members })
+        }),
     }
 }
 
+/// An inclusive range is stored as is, an exclusive range is stored without its upper bound
+fn flatten_range(range: ast::TypeRange) -> FlattenResult<Node> {
+    use ast::{Type2, Value};
+    let bound = |ty2: Type2| match ty2 {
+        Type2::Value(Value::Uint(n)) => Ok(n as i128),
+        Type2::Value(Value::Nint(n)) => Ok(n as i128),
+        _ => Err(FlattenError::InvalidRange),
+    };
+    let min = bound(range.start)?;
+    let max = match range.inclusive {
+        true => bound(range.end)?,
+        false => bound(range.end)? - 1,
+    };
+    Range::new(min, max).map(Node::Range)
+}
+
 // TODO flatten values into a Literal type instead of a constrained type
 fn flatten_value(val: ast::Value) -> FlattenResult<Node> {
     use ast::Value;
@@ -62,33 +157,100 @@ fn flatten_value(val: ast::Value) -> FlattenResult<Node> {
 }
 
 /// If we flatten a type2 typename we must do so via a control statement. Otherwize we assume we
-/// are an unresolved named type
+/// are an unresolved named type. A tstr, bstr or any without a size is unbounded, and it is up to
+/// the printer of each language to accept it or not. A name with generic arguments is an instance
+/// of a generic rule. nil, null and undefined are all read as nil
 fn flatten_typename(name: ast::NameGeneric) -> FlattenResult<Node> {
+    if !name.generic_args.is_empty() {
+        let args = name
+            .generic_args
+            .into_iter()
+            .map(flatten_type1)
+            .collect::<FlattenResult<Vec<Node>>>()?;
+        let name = name.name;
+        return Ok(Node::Instance(Instance { name, args }));
+    }
     match Primative::from(name.name) {
-        Primative::Int | Primative::UInt | Primative::TStr | Primative::BStr => {
-            Err(FlattenError::InvalidUnconstrainedPrimative)
-        }
+        Primative::Int | Primative::UInt => Err(FlattenError::InvalidUnconstrainedPrimative),
+        Primative::TStr => Ok(Node::Primative(ConstrainedPrimative::UnboundedStr)),
+        Primative::BStr => Ok(Node::Primative(ConstrainedPrimative::UnboundedBytes)),
         Primative::Bool => Ok(Node::Primative(ConstrainedPrimative::Bool)),
+        Primative::Float16 => Ok(Node::Primative(ConstrainedPrimative::F16)),
+        Primative::Float32 => Ok(Node::Primative(ConstrainedPrimative::F32)),
+        Primative::Float64 => Ok(Node::Primative(ConstrainedPrimative::F64)),
+        Primative::Any => Ok(Node::Primative(ConstrainedPrimative::UnboundedAny)),
         Primative::Unresolved(s) => match s.as_str() {
             "false" => Ok(Node::Literal(Literal::Bool(false))),
             "true" => Ok(Node::Literal(Literal::Bool(true))),
+            "nil" | "null" | "undefined" => Ok(Node::Nil),
             _ => Ok(Node::Foreign(s)),
         },
     }
 }
 
+/// A tag is read as a control on the type it holds. IE: #6.1(uint .size 8). The comparisons
+/// only compare with integers. IE: uint .size 1 .le 100. A pattern is a text string, and the
+/// names of bits are a group or a rule. IE: uint .size 1 .bits &( ready: 0, fault: 1 )
 fn flatten_control(ctl: ast::TypeControl) -> FlattenResult<Node> {
     use ast::{Type2, Value};
-    let size = match ctl.arg {
+    let uint = |arg: Type2, error: FlattenError| match arg {
         Type2::Value(Value::Uint(n)) => Ok(n),
-        _ => Err(FlattenError::InvalidControl),
-    }?;
+        _ => Err(error),
+    };
     match ctl.op.as_str() {
         "size" => match ctl.target {
-            Type2::Typename(s) => Primative::from(s.name).constrain(size).map(Node::Primative),
+            Type2::Typename(s) => Primative::from(s.name)
+                .constrain(uint(ctl.arg, FlattenError::InvalidControl)?)
+                .map(Node::Primative),
             _ => Err(FlattenError::InvalidControl),
         },
-        _ => Err(FlattenError::NotSupportedControl(ctl.op)),
+        "tag" => match ctl.target {
+            Type2::Parethesized(t) => {
+                let tag = uint(ctl.arg, FlattenError::InvalidTagArg)?;
+                Ok(Node::Tag(Tag::new(flatten_type(t)?, tag)))
+            }
+            _ => Err(FlattenError::InvalidTagArg),
+        },
+        "default" => match flatten_type2(ctl.arg)? {
+            Node::Literal(value) => flatten_type2(ctl.target).map(|node| {
+                let ty = Box::new(node);
+                Node::Default(DefaultValue { value, ty })
+            }),
+            _ => Err(FlattenError::InvalidDefaultArg),
+        },
+        "regexp" => match flatten_type2(ctl.arg)? {
+            Node::Literal(Literal::Str(pattern)) => flatten_type2(ctl.target).map(|node| {
+                let ty = Box::new(node);
+                Node::Regexp(Regexp { pattern, ty })
+            }),
+            Node::Literal(Literal::Char(c)) => flatten_type2(ctl.target).map(|node| {
+                let ty = Box::new(node);
+                let pattern = c.to_string();
+                Node::Regexp(Regexp { pattern, ty })
+            }),
+            _ => Err(FlattenError::InvalidRegexpArg),
+        },
+        "bits" => match flatten_type2(ctl.arg)? {
+            names @ (Node::Names(_) | Node::Foreign(_)) => flatten_type2(ctl.target).map(|node| {
+                let names = Box::new(names);
+                let ty = Box::new(node);
+                Node::Bits(Bits { names, ty })
+            }),
+            _ => Err(FlattenError::InvalidBitsArg),
+        },
+        op => match Comparison::from_op(op) {
+            Some(op) => match flatten_type2(ctl.arg)? {
+                Node::Literal(value @ (Literal::Int(_) | Literal::UInt(_))) => {
+                    flatten_type2(ctl.target).map(|node| {
+                        let constraint = Constraint::new(op, value);
+                        let ty = Box::new(node);
+                        Node::Compare(Compare { constraint, ty })
+                    })
+                }
+                _ => Err(FlattenError::InvalidControlArg),
+            },
+            None => Err(FlattenError::NotSupportedControl(ctl.op)),
+        },
     }
 }
 
@@ -99,20 +261,44 @@ fn flatten_map(group: ast::Group) -> FlattenResult<Node> {
 fn flatten_array(group: ast::Group) -> FlattenResult<Node> {
     use ast::Occur;
     get_group_entries(group).and_then(|mut entries| {
-        if entries.len() == 1 {
+        if entries.len() == 1 && !is_keyed(&entries[0]) {
             let entry = entries.pop().ok_or(FlattenError::Infallible)?;
-            match entry.occur {
-                Some(Occur::Numbered(a, len)) if a == len => {
-                    Ok(Array::new(flatten_groupentry(entry)?, len).into())
-                }
-                _ => Err(FlattenError::InvalidArraySize),
-            }
+            // Variable arrays must declare an upper bound. IE: [*4 u8] and not [* u8]
+            let (min, len) = match entry.occur {
+                Some(Occur::Numbered(a, len)) if a == len => (len, len),
+                Some(Occur::Numbered(min, len)) if min < len && len < usize::MAX => (min, len),
+                Some(Occur::Optional) => (0, 1),
+                // A single entry without an occurrence is a record. IE: [ header ]
+                None => return flatten_record(vec![entry]),
+                _ => return Err(FlattenError::InvalidArraySize),
+            };
+            let entry = ast::GrpEnt {
+                occur: None,
+                val: entry.val,
+            };
+            Ok(Array::bounded(flatten_groupentry(entry)?, min, len).into())
         } else {
-            Err(FlattenError::InvalidArray)
+            // An array of many entries is a record of positional members. IE: [ x: u8, y: u8 ]
+            flatten_record(entries)
         }
     })
 }
 
+fn flatten_record(entries: Vec<ast::GrpEnt>) -> FlattenResult<Node> {
+    entries
+        .into_iter()
+        .map(flatten_groupentry)
+        .collect::<FlattenResult<Vec<Node>>>()
+        .map(|members| Node::Record(Group { members }))
+}
+
+fn is_keyed(entry: &ast::GrpEnt) -> bool {
+    matches!(
+        &entry.val,
+        ast::GrpEntVal::Member(ast::Member { key: Some(_), .. })
+    )
+}
+
 fn flatten_group(group: ast::Group) -> FlattenResult<Vec<Node>> {
     get_group_entries(group)?
         .into_iter()
@@ -131,29 +317,64 @@ fn get_group_entries(mut group: ast::Group) -> FlattenResult<Vec<ast::GrpEnt>> {
 }
 
 fn flatten_groupentry(group_entry: ast::GrpEnt) -> FlattenResult<Node> {
-    use ast::GrpEntVal;
+    use ast::{GrpEntVal, Occur};
+    let optional = matches!(
+        group_entry.occur,
+        Some(Occur::Optional) | Some(Occur::Numbered(0, 1))
+    );
+    // An entry without a key includes the members of a group, which are all optional when the
+    // entry is. IE: ? footer
+    let included = |node: Node| match optional {
+        true => Node::Optional(Box::new(node)),
+        false => node,
+    };
     match group_entry.val {
-        GrpEntVal::Member(m) => flatten_group_member(m),
+        GrpEntVal::Member(m) if m.key.is_some() => flatten_group_member(m, optional),
+        GrpEntVal::Member(m) => flatten_type(m.value).map(included),
         GrpEntVal::Parenthesized(g) => {
-            flatten_group(g).map(|members| Node::Group(Group { members }))
+            flatten_group(g).map(|members| included(Node::Group(Group { members })))
         }
-        GrpEntVal::Groupname(name) => Err(FlattenError::NotSupportedGroupname(name)),
+        GrpEntVal::Groupname(name) => Ok(included(Node::Foreign(name))),
     }
 }
 
-fn flatten_group_member(member: ast::Member) -> FlattenResult<Node> {
-    use ast::MemberKeyVal;
+fn flatten_group_member(member: ast::Member, optional: bool) -> FlattenResult<Node> {
     match &member.key {
-        Some(key) => match &key.val {
-            MemberKeyVal::Bareword(s) => {
-                Ok(Node::KeyVal(KeyVal::new(s, flatten_type(member.value)?)))
+        Some(key) => {
+            let (key, index) = flatten_member_key(&key.val)?;
+            let value = flatten_type(member.value)?;
+            let keyval = match optional {
+                true => KeyVal::optional(key, value),
+                false => KeyVal::new(key, value),
+            };
+            match index {
+                Some(index) => Ok(Node::KeyVal(keyval.indexed(index))),
+                None => Ok(Node::KeyVal(keyval)),
             }
-            _ => Err(FlattenError::InvalidGroupMissingKey),
-        },
+        }
         None => flatten_type(member.value),
     }
 }
 
+/// Text keys name the field. Integer keys are encoded as is and the field is named field_N,
+/// unless a comment names it, see [`name_members`]
+fn flatten_member_key(key: &ast::MemberKeyVal) -> FlattenResult<(String, Option<u32>)> {
+    use ast::{MemberKeyVal, Type1, Type2, Value};
+    let value = match key {
+        MemberKeyVal::Bareword(s) => return Ok((s.clone(), None)),
+        MemberKeyVal::Value(value) => value,
+        MemberKeyVal::Type1(Type1::Simple(Type2::Value(value))) => value,
+        _ => return Err(FlattenError::InvalidMemberKey),
+    };
+    match value {
+        Value::Text(s) => Ok((s.clone(), None)),
+        Value::Uint(n) => u32::try_from(*n)
+            .map(|index| (format!("field_{}", index), Some(index)))
+            .map_err(|_| FlattenError::InvalidMemberKey),
+        _ => Err(FlattenError::InvalidMemberKey),
+    }
+}
+
 //fn assume_foreign_value(ty: ast::Type) -> FlattenResult<Node> {
 //    match flatten_type(ty) {
 //        Ok(Node::Foreign(s)) => Ok(Node::Foreign(s)),
@@ -0,0 +1,270 @@
+use super::{error::*, node::*, rules::Rules};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// Instances nested deeper than this are assumed to never end. IE: a<T> = { next: a<b<T>> }
+const MAX_DEPTH: usize = 16;
+
+/// An instance waiting to be made concrete, and the rules followed to reach it
+struct Pending {
+    key: String,
+    path: Vec<String>,
+    instance: Instance,
+}
+
+/// Replace every instance of a generic rule with a reference to a concrete rule named after the
+/// generic and its arguments, so that message<status> is linked as message_status. Generic rules
+/// are removed, and the concrete rules are added after the rules in the order they are found
+pub(crate) fn monomorphize(nodes: Rules<Node>) -> FlattenResult<Rules<Node>> {
+    let (concrete, errors) = monomorphize_each(nodes, &mut BTreeSet::new());
+    match errors.into_iter().next() {
+        Some(e) => Err(e),
+        None => Ok(concrete),
+    }
+}
+
+/// Monomorphize every rule that can be, and return the errors of those that can not. A rule that
+/// fails is dropped, along with the instances followed to reach the failure, and their names are
+/// added to failed
+pub(crate) fn monomorphize_each(
+    nodes: Rules<Node>,
+    failed: &mut BTreeSet<String>,
+) -> (Rules<Node>, Vec<FlattenError>) {
+    let mut generics = BTreeMap::new();
+    let mut rules = Rules::new();
+    for (key, node) in nodes {
+        match node {
+            Node::Generic(generic) => {
+                generics.insert(key, generic);
+            }
+            node => {
+                rules.insert(key, node);
+            }
+        }
+    }
+
+    let mut expander = Expander {
+        generics: &generics,
+        seen: BTreeMap::new(),
+        pending: VecDeque::new(),
+    };
+    let mut concrete = Rules::new();
+    let mut errors = Vec::new();
+    for (key, node) in rules {
+        let path = vec![key.clone()];
+        match expander.instantiate(node, &path) {
+            Ok(node) => {
+                concrete.insert(key, node);
+            }
+            Err(e) => {
+                errors.push(e.in_rule(&key));
+                failed.insert(key);
+            }
+        }
+    }
+    while let Some(Pending {
+        key,
+        path,
+        instance,
+    }) = expander.pending.pop_front()
+    {
+        if concrete.contains_key(&key) {
+            let error = FlattenError::InstancedRule(instance.name, key);
+            errors.push(error.in_rule(&path[0]));
+            failed.insert(path[0].clone());
+            continue;
+        }
+        match expander.expand(instance, &path) {
+            Ok(node) => {
+                concrete.insert(key, node);
+            }
+            Err(e) => {
+                errors.push(e.in_rule(&path[0]));
+                failed.extend(path);
+            }
+        }
+    }
+    let concrete = concrete
+        .into_iter()
+        .filter(|(key, _)| !failed.contains(key))
+        .collect();
+    (concrete, errors)
+}
+
+struct Expander<'a> {
+    generics: &'a BTreeMap<String, Generic>,
+    /// The instance each concrete rule was named after
+    seen: BTreeMap<String, Instance>,
+    pending: VecDeque<Pending>,
+}
+
+impl<'a> Expander<'a> {
+    /// Replace the instances inside a node with references to their concrete rules
+    fn instantiate(&mut self, node: Node, path: &[String]) -> FlattenResult<Node> {
+        match node {
+            Node::Instance(Instance { name, args }) => {
+                match self.generics.get(&name) {
+                    Some(generic) if generic.params.len() == args.len() => {}
+                    _ => return Err(FlattenError::InvalidGenericArgs(name)),
+                }
+                let args = args
+                    .into_iter()
+                    .map(|arg| self.instantiate(arg, path))
+                    .collect::<FlattenResult<Vec<Node>>>()?;
+                let key = instance_name(&name, &args)?;
+                let instance = Instance { name, args };
+                match self.seen.get(&key) {
+                    // IE: pair<a_b, c> and pair<a, b_c> are both named pair_a_b_c
+                    Some(seen) if *seen != instance => {
+                        return Err(FlattenError::InstanceClash(instance.name, key));
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.seen.insert(key.clone(), instance.clone());
+                        let mut path = path.to_vec();
+                        path.push(key.clone());
+                        self.pending.push_back(Pending {
+                            key: key.clone(),
+                            path,
+                            instance,
+                        });
+                    }
+                }
+                Ok(Node::Foreign(key))
+            }
+            Node::Foreign(name) if self.generics.contains_key(&name) => {
+                Err(FlattenError::InvalidGenericArgs(name))
+            }
+            node => map_children(node, &mut |child| self.instantiate(child, path)),
+        }
+    }
+
+    /// The body of a generic rule with its parameters replaced by the arguments of an instance
+    fn expand(&mut self, instance: Instance, path: &[String]) -> FlattenResult<Node> {
+        if path.len() > MAX_DEPTH {
+            return Err(FlattenError::Cycle(path.to_vec()));
+        }
+        let generic = &self.generics[&instance.name];
+        let args = generic
+            .params
+            .iter()
+            .map(String::as_str)
+            .zip(instance.args.iter())
+            .collect::<BTreeMap<&str, &Node>>();
+        let node = substitute(*generic.node.clone(), &args)?;
+        self.instantiate(node, path)
+    }
+}
+
+fn substitute(node: Node, args: &BTreeMap<&str, &Node>) -> FlattenResult<Node> {
+    match node {
+        Node::Foreign(name) => match args.get(name.as_str()) {
+            Some(arg) => Ok((*arg).clone()),
+            None => Ok(Node::Foreign(name)),
+        },
+        node => map_children(node, &mut |child| substitute(child, args)),
+    }
+}
+
+/// IE: message<status> is named message_status, and pair<u8, tstr .size 4> is pair_u8_tstr4
+fn instance_name(name: &str, args: &[Node]) -> FlattenResult<String> {
+    let args = args
+        .iter()
+        .map(|arg| match arg {
+            Node::Foreign(name) => Ok(name.clone()),
+            Node::Primative(ty) => Ok(primative_name(ty)),
+            _ => Err(FlattenError::InvalidGenericArg),
+        })
+        .collect::<FlattenResult<Vec<String>>>()?;
+    Ok(format!("{}_{}", name, args.join("_")))
+}
+
+fn primative_name(ty: &ConstrainedPrimative) -> String {
+    use ConstrainedPrimative::*;
+    match ty {
+        U8 => "u8".into(),
+        I8 => "i8".into(),
+        U16 => "u16".into(),
+        I16 => "i16".into(),
+        U32 => "u32".into(),
+        I32 => "i32".into(),
+        U64 => "u64".into(),
+        I64 => "i64".into(),
+        Bool => "bool".into(),
+        F16 => "f16".into(),
+        F32 => "f32".into(),
+        F64 => "f64".into(),
+        Str(n) => format!("tstr{}", n),
+        Bytes(n) => format!("bstr{}", n),
+        UnboundedStr => "tstr".into(),
+        UnboundedBytes => "bstr".into(),
+        Any(n) => format!("any{}", n),
+        UnboundedAny => "any".into(),
+    }
+}
+
+/// Rebuild a node with each of the nodes it holds replaced
+fn map_children<F>(node: Node, f: &mut F) -> FlattenResult<Node>
+where
+    F: FnMut(Node) -> FlattenResult<Node>,
+{
+    Ok(match node {
+        Node::Group(Group { members }) => Node::Group(Group {
+            members: map_all(members, f)?,
+        }),
+        Node::Map(Group { members }) => Node::Map(Group {
+            members: map_all(members, f)?,
+        }),
+        Node::Record(Group { members }) => Node::Record(Group {
+            members: map_all(members, f)?,
+        }),
+        Node::Choice(Choice { members }) => Node::Choice(Choice {
+            members: map_all(members, f)?,
+        }),
+        Node::Instance(Instance { name, args }) => Node::Instance(Instance {
+            name,
+            args: map_all(args, f)?,
+        }),
+        Node::Array(Array { len, min, ty }) => Node::Array(Array {
+            len,
+            min,
+            ty: Box::new(f(*ty)?),
+        }),
+        Node::Tag(Tag { tag, ty }) => Node::Tag(Tag {
+            tag,
+            ty: Box::new(f(*ty)?),
+        }),
+        Node::Default(DefaultValue { value, ty }) => Node::Default(DefaultValue {
+            value,
+            ty: Box::new(f(*ty)?),
+        }),
+        Node::Compare(Compare { constraint, ty }) => Node::Compare(Compare {
+            constraint,
+            ty: Box::new(f(*ty)?),
+        }),
+        Node::Regexp(Regexp { pattern, ty }) => Node::Regexp(Regexp {
+            pattern,
+            ty: Box::new(f(*ty)?),
+        }),
+        Node::Bits(Bits { names, ty }) => Node::Bits(Bits {
+            names: Box::new(f(*names)?),
+            ty: Box::new(f(*ty)?),
+        }),
+        Node::Names(Group { members }) => Node::Names(Group {
+            members: map_all(members, f)?,
+        }),
+        Node::KeyVal(KeyVal(key, node, optional, index)) => {
+            Node::KeyVal(KeyVal(key, Box::new(f(*node)?), optional, index))
+        }
+        Node::Optional(node) => Node::Optional(Box::new(f(*node)?)),
+        Node::Nullable(node) => Node::Nullable(Box::new(f(*node)?)),
+        Node::Unwrap(node) => Node::Unwrap(Box::new(f(*node)?)),
+        node => node,
+    })
+}
+
+fn map_all<F>(nodes: Vec<Node>, f: &mut F) -> FlattenResult<Vec<Node>>
+where
+    F: FnMut(Node) -> FlattenResult<Node>,
+{
+    nodes.into_iter().map(f).collect()
+}
@@ -0,0 +1,131 @@
+use super::{error::*, node::*, rules::Rules};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// A map or record declared inline, and the rule it was declared in
+struct Pending {
+    key: String,
+    rule: String,
+    node: Node,
+}
+
+/// Replace every map or record declared inline as the type of a member with a reference to a
+/// rule of its own, named after the rule and the member that declare it, so that the type of net
+/// in thing = { net: { dhcp: bool } } is linked as thing_net. The members of an array rule are
+/// named after the rule, so that the type of the members of xs = [ 2*2 { x: u8 } ] is xs_item.
+/// The new rules are added after the rules in the order they are found
+pub(crate) fn hoist(nodes: Rules<Node>) -> FlattenResult<Rules<Node>> {
+    let (rules, errors) = hoist_each(nodes, &mut BTreeSet::new());
+    match errors.into_iter().next() {
+        Some(e) => Err(e),
+        None => Ok(rules),
+    }
+}
+
+/// Hoist the members of every rule that can be, and return the errors of those that can not. A
+/// rule that fails is dropped, along with the rules hoisted out of it, and their names are added
+/// to failed
+pub(crate) fn hoist_each(
+    nodes: Rules<Node>,
+    failed: &mut BTreeSet<String>,
+) -> (Rules<Node>, Vec<FlattenError>) {
+    let mut pending = VecDeque::new();
+    let mut rules = Rules::new();
+    let mut errors = Vec::new();
+    for (key, node) in nodes {
+        let node = hoist_rule(&key, node, &key, &mut pending);
+        rules.insert(key, node);
+    }
+    // The rule each hoisted rule was declared in
+    let mut hoisted = BTreeMap::new();
+    while let Some(Pending { key, rule, node }) = pending.pop_front() {
+        if rules.contains_key(&key) {
+            errors.push(FlattenError::HoistedRule(key).in_rule(&rule));
+            failed.insert(rule);
+            continue;
+        }
+        let node = hoist_rule(&key, node, &rule, &mut pending);
+        hoisted.insert(key.clone(), rule);
+        rules.insert(key, node);
+    }
+    failed.extend(
+        hoisted
+            .into_iter()
+            .filter(|(_, rule)| failed.contains(rule))
+            .map(|(key, _)| key)
+            .collect::<Vec<String>>(),
+    );
+    let rules = rules
+        .into_iter()
+        .filter(|(key, _)| !failed.contains(key))
+        .collect();
+    (rules, errors)
+}
+
+fn hoist_rule(key: &str, node: Node, rule: &str, pending: &mut VecDeque<Pending>) -> Node {
+    match node {
+        Node::Map(Group { members }) => Node::Map(Group {
+            members: hoist_members(key, members, rule, pending),
+        }),
+        Node::Record(Group { members }) => Node::Record(Group {
+            members: hoist_members(key, members, rule, pending),
+        }),
+        Node::Group(Group { members }) => Node::Group(Group {
+            members: hoist_members(key, members, rule, pending),
+        }),
+        Node::Array(_) => hoist_type(&format!("{}_item", key), node, rule, pending),
+        node => node,
+    }
+}
+
+fn hoist_members(
+    key: &str,
+    members: Vec<Node>,
+    rule: &str,
+    pending: &mut VecDeque<Pending>,
+) -> Vec<Node> {
+    members
+        .into_iter()
+        .map(|member| hoist_member(key, member, rule, pending))
+        .collect()
+}
+
+fn hoist_member(key: &str, node: Node, rule: &str, pending: &mut VecDeque<Pending>) -> Node {
+    match node {
+        Node::KeyVal(KeyVal(k, v, optional, index)) => {
+            let name = format!("{}_{}", key, k);
+            let v = hoist_type(&name, *v, rule, pending);
+            Node::KeyVal(KeyVal(k, Box::new(v), optional, index))
+        }
+        Node::Group(Group { members }) => Node::Group(Group {
+            members: hoist_members(key, members, rule, pending),
+        }),
+        Node::Optional(node) => Node::Optional(Box::new(hoist_member(key, *node, rule, pending))),
+        node => node,
+    }
+}
+
+/// The type of a member, with an inline map or record replaced by a reference to its new rule.
+/// Arrays, tags and nullable types hold the type of the member, so their maps are named after it
+fn hoist_type(name: &str, node: Node, rule: &str, pending: &mut VecDeque<Pending>) -> Node {
+    match node {
+        Node::Map(_) | Node::Record(_) => {
+            pending.push_back(Pending {
+                key: name.to_string(),
+                rule: rule.to_string(),
+                node,
+            });
+            Node::Foreign(name.to_string())
+        }
+        Node::Array(Array { len, min, ty }) => Node::Array(Array {
+            len,
+            min,
+            ty: Box::new(hoist_type(name, *ty, rule, pending)),
+        }),
+        Node::Tag(Tag { tag, ty }) => Node::Tag(Tag {
+            tag,
+            ty: Box::new(hoist_type(name, *ty, rule, pending)),
+        }),
+        Node::Nullable(node) => Node::Nullable(Box::new(hoist_type(name, *node, rule, pending))),
+        node => node,
+    }
+}
@@ -1,6 +1,7 @@
-use crate::node::{Fields, LinkedNode, Literal};
+use crate::node::{ConstrainedPrimative, Enumeration, Fields, LinkedNode, Literal, Variants};
+use crate::rules::Rules;
 use std::borrow::Cow;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::hash::Hash;
 pub struct Context(BTreeMap<String, LinkedNode>);
 
@@ -16,6 +17,24 @@ pub fn structs_borrowed<'a, K: Hash>(kv: (K, &'a LinkedNode)) -> Option<(K, Cow<
     filter_structs((kv.0, Cow::Borrowed(kv.1)))
 }
 
+pub fn choices_owned<K: Hash>(kv: (K, LinkedNode)) -> Option<(K, Variants)> {
+    filter_choices((kv.0, Cow::Owned(kv.1))).map(|(k, v)| (k, v.into_owned()))
+}
+
+pub fn choices_borrowed<'a, K: Hash>(kv: (K, &'a LinkedNode)) -> Option<(K, Cow<'a, Variants>)> {
+    filter_choices((kv.0, Cow::Borrowed(kv.1)))
+}
+
+pub fn enumerations_owned<K: Hash>(kv: (K, LinkedNode)) -> Option<(K, Enumeration)> {
+    filter_enumerations((kv.0, Cow::Owned(kv.1))).map(|(k, v)| (k, v.into_owned()))
+}
+
+pub fn enumerations_borrowed<'a, K: Hash>(
+    kv: (K, &'a LinkedNode),
+) -> Option<(K, Cow<'a, Enumeration>)> {
+    filter_enumerations((kv.0, Cow::Borrowed(kv.1)))
+}
+
 pub fn literals_owned<K: Hash>(kv: (K, LinkedNode)) -> Option<(K, Literal)> {
     filter_literals((kv.0, Cow::Owned(kv.1))).map(|(k, v)| (k, v.into_owned()))
 }
@@ -24,6 +43,100 @@ pub fn literals_borrowed<'a, K: Hash>(kv: (K, &'a LinkedNode)) -> Option<(K, Cow
     filter_literals((kv.0, Cow::Borrowed(kv.1)))
 }
 
+/// The names of the types that hold an unbounded tstr or bstr, or a member held through a
+/// pointer, directly or through another type. These types own memory on the heap and can not be
+/// Copy
+pub fn unbounded_types(ctx: &Rules<LinkedNode>) -> BTreeSet<String> {
+    let mut found = recursive_members(ctx)
+        .into_keys()
+        .collect::<BTreeSet<String>>();
+    loop {
+        let more = ctx
+            .iter()
+            .filter(|(key, node)| !found.contains(*key) && is_unbounded(node, &found))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<String>>();
+        if more.is_empty() {
+            break found;
+        }
+        found.extend(more);
+    }
+}
+
+/// The members of each struct that hold the struct itself, directly or through other types. IE:
+/// parent in branch = { ? parent: branch }. A type can not hold itself inline, so these members
+/// are held through a pointer
+pub fn recursive_members(ctx: &Rules<LinkedNode>) -> BTreeMap<String, BTreeSet<String>> {
+    ctx.iter()
+        .filter_map(structs_borrowed)
+        .map(|(key, fields)| {
+            let members = fields
+                .members
+                .iter()
+                .filter(|kv| {
+                    let mut held = Vec::new();
+                    held_types(kv.val(), &mut held);
+                    held.iter()
+                        .any(|from| reaches(ctx, from, key, &mut BTreeSet::new()))
+                })
+                .map(|kv| kv.key().to_string())
+                .collect::<BTreeSet<String>>();
+            (key.clone(), members)
+        })
+        .filter(|(_, members)| !members.is_empty())
+        .collect()
+}
+
+/// True when a type holds the other type, directly or through other types
+fn reaches(ctx: &Rules<LinkedNode>, from: &str, to: &str, seen: &mut BTreeSet<String>) -> bool {
+    if from == to {
+        return true;
+    }
+    if !seen.insert(from.to_string()) {
+        return false;
+    }
+    let mut held = Vec::new();
+    if let Some(node) = ctx.get(from) {
+        held_types(node, &mut held);
+    }
+    held.iter().any(|key| reaches(ctx, key, to, seen))
+}
+
+/// The structs and choices a node holds by value
+fn held_types(node: &LinkedNode, held: &mut Vec<String>) {
+    match node {
+        LinkedNode::ForeignStruct(key) | LinkedNode::ForeignChoice(key) => held.push(key.clone()),
+        LinkedNode::Array(array) => held_types(&array.ty, held),
+        LinkedNode::Tag(tag) => held_types(&tag.ty, held),
+        LinkedNode::Fields(Fields { members })
+        | LinkedNode::Struct(Fields { members })
+        | LinkedNode::Record(Fields { members })
+        | LinkedNode::Choice(Variants { members }) => {
+            members.iter().for_each(|kv| held_types(kv.val(), held))
+        }
+        _ => {}
+    }
+}
+
+fn is_unbounded(node: &LinkedNode, found: &BTreeSet<String>) -> bool {
+    match node {
+        LinkedNode::Primative(ConstrainedPrimative::UnboundedStr)
+        | LinkedNode::Primative(ConstrainedPrimative::UnboundedBytes)
+        | LinkedNode::Primative(ConstrainedPrimative::UnboundedAny) => true,
+        LinkedNode::Array(array) => is_unbounded(&array.ty, found),
+        LinkedNode::Tag(tag) => is_unbounded(&tag.ty, found),
+        LinkedNode::Regexp(regexp) => regexp.ty == ConstrainedPrimative::UnboundedStr,
+        LinkedNode::Fields(Fields { members })
+        | LinkedNode::Struct(Fields { members })
+        | LinkedNode::Record(Fields { members })
+        | LinkedNode::Choice(Variants { members }) => {
+            members.iter().any(|kv| is_unbounded(kv.val(), found))
+        }
+        LinkedNode::ForeignStruct(key) | LinkedNode::ForeignChoice(key) => found.contains(key),
+        _ => false,
+    }
+}
+
 pub fn fold_group_owned<'a, K, T>(
     split: &'a str,
 ) -> impl Fn(GroupedCow<'a, T>, (K, T)) -> GroupedCow<'a, T>
@@ -87,8 +200,38 @@ where
 {
     let val = cow.into();
     match val {
-        Cow::Borrowed(LinkedNode::Struct(s)) => Some((key, Cow::Borrowed(s))),
-        Cow::Owned(LinkedNode::Struct(s)) => Some((key, Cow::Owned(s))),
+        Cow::Borrowed(LinkedNode::Struct(s) | LinkedNode::Record(s)) => {
+            Some((key, Cow::Borrowed(s)))
+        }
+        Cow::Owned(LinkedNode::Struct(s) | LinkedNode::Record(s)) => Some((key, Cow::Owned(s))),
+        _ => None,
+    }
+}
+
+#[inline]
+fn filter_choices<'a, K, V>((key, cow): (K, V)) -> Option<(K, Cow<'a, Variants>)>
+where
+    K: Hash,
+    V: Into<Cow<'a, LinkedNode>>,
+{
+    let val = cow.into();
+    match val {
+        Cow::Borrowed(LinkedNode::Choice(c)) => Some((key, Cow::Borrowed(c))),
+        Cow::Owned(LinkedNode::Choice(c)) => Some((key, Cow::Owned(c))),
+        _ => None,
+    }
+}
+
+#[inline]
+fn filter_enumerations<'a, K, V>((key, cow): (K, V)) -> Option<(K, Cow<'a, Enumeration>)>
+where
+    K: Hash,
+    V: Into<Cow<'a, LinkedNode>>,
+{
+    let val = cow.into();
+    match val {
+        Cow::Borrowed(LinkedNode::Enumeration(e)) => Some((key, Cow::Borrowed(e))),
+        Cow::Owned(LinkedNode::Enumeration(e)) => Some((key, Cow::Owned(e))),
         _ => None,
     }
 }
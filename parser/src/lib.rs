@@ -4,16 +4,29 @@ mod tests;
 #[cfg(feature = "liquid")]
 mod liquid;
 
+mod annotations;
+mod diagnostics;
 mod error;
 mod flatten;
+mod generics;
+mod hoist;
 mod link;
 mod node;
+mod rules;
+mod sources;
+mod rewrite;
 mod iters;
 pub use iters::*;
-pub use error::{FlattenError, FlattenResult};
+pub use annotations::pinned_keys;
+pub use diagnostics::{parse_diagnostics, Diagnostics};
+pub use error::{FlattenError, FlattenResult, Location, RuleError};
 pub use node::*;
+pub use rules::Rules;
+pub use sources::Sources;
 
-use std::collections::BTreeMap;
-pub fn parse(cddl: &str) -> FlattenResult<BTreeMap<String, node::LinkedNode>> {
-    flatten::flatten(cddl).and_then(link::link)
+/// Parse a schema into its linked rules, in the order they are declared
+pub fn parse(cddl: &str) -> FlattenResult<Rules<node::LinkedNode>> {
+    flatten::flatten(cddl)
+        .and_then(link::link)
+        .map_err(|e| e.locate(cddl))
 }
@@ -1,101 +1,473 @@
-use super::{error::*, node::*};
-use std::collections::BTreeMap;
+use super::{error::*, generics::monomorphize, hoist::hoist, node::*, rules::Rules};
+use seedle_regexp::{is_match, is_valid};
+use std::collections::BTreeSet;
 
-pub fn link(nodes: BTreeMap<String, Node>) -> FlattenResult<BTreeMap<String, LinkedNode>> {
+pub fn link(nodes: Rules<Node>) -> FlattenResult<Rules<LinkedNode>> {
     // NOTE have to handle cases where a single node is reference multiple times
     //      where we clone nodes
+    let nodes = monomorphize(nodes).and_then(hoist)?;
     let lookup = nodes.clone();
     nodes
         .into_iter()
-        .map(|(key, node)| Ok((key, link_node(node, &lookup)?)))
+        .map(|(key, node)| link_rule(key, node, &lookup))
         .collect()
 }
 
+pub(crate) fn link_rule(
+    key: String,
+    node: Node,
+    ctx: &Rules<Node>,
+) -> FlattenResult<(String, LinkedNode)> {
+    // The names of bits are only read by the integers they name, so on their own they are a group
+    let node = match node {
+        Node::Names(group) => Node::Group(group),
+        node => node,
+    };
+    match link_node(node, &Scope::new(&key, ctx)) {
+        Ok(node) => Ok((key, node)),
+        Err(e) => Err(e.in_rule(&key)),
+    }
+}
+
+/// Note the rule an error was raised in while following a reference to it. A cycle is left to the
+/// rule being linked, so that each rule of the cycle reports it
+fn in_followed(e: FlattenError, key: &str) -> FlattenError {
+    match e {
+        FlattenError::Cycle(_) => e,
+        e => e.in_rule(key),
+    }
+}
+
+/// The rules to link against, and the path of rules followed to reach the node being linked
+struct Scope<'a> {
+    rules: &'a Rules<Node>,
+    path: Vec<String>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(key: &str, rules: &'a Rules<Node>) -> Scope<'a> {
+        Scope {
+            rules,
+            path: vec![key.to_string()],
+        }
+    }
+
+    /// Follow a reference to a rule, unless the rule is already being followed
+    fn follow(&self, key: &str) -> FlattenResult<(Node, Scope<'a>)> {
+        let node = self
+            .rules
+            .get(key)
+            .ok_or_else(|| FlattenError::ForeignKey(key.to_string()))?;
+        match self.path.iter().position(|seen| seen == key) {
+            Some(start) => {
+                let mut cycle = self.path[start..].to_vec();
+                cycle.push(key.to_string());
+                Err(FlattenError::Cycle(cycle))
+            }
+            None => {
+                let mut path = self.path.clone();
+                path.push(key.to_string());
+                Ok((node.clone(), Scope { rules: self.rules, path }))
+            }
+        }
+    }
+}
+
 /// Main (only) entry function to this module
-fn link_node(node: Node, ctx: &BTreeMap<String, Node>) -> FlattenResult<LinkedNode> {
+fn link_node(node: Node, ctx: &Scope) -> FlattenResult<LinkedNode> {
     match node {
         Node::Literal(lit) => Ok(LinkedNode::Literal(lit)),
         Node::Primative(t) => Ok(LinkedNode::Primative(t)),
+        Node::Range(r) => Ok(LinkedNode::Range(r)),
         Node::Foreign(t) => link_foreign(t, ctx),
         Node::Group(g) => link_group(g, ctx),
         Node::Map(g) => link_struct(g, ctx),
+        Node::Record(g) => link_record(g, ctx),
         Node::Array(a) => link_array(a, ctx),
-        Node::KeyVal(_) => Err(FlattenError::Infallible),
+        Node::Tag(t) => link_tag(t, ctx),
+        // The default is given to the members that hold the type, see link_field_member
+        Node::Default(d) => link_node(*d.ty, ctx),
+        Node::Compare(c) => link_compare(c, ctx),
+        Node::Regexp(r) => link_regexp(r, ctx),
+        Node::Bits(b) => link_bits(b, ctx),
+        Node::Names(_) => Err(FlattenError::NotSupportedChoice),
+        Node::Choice(c) => link_choice(c, ctx),
+        // Members are made optional by the type they hold, see link_field_member
+        Node::Nullable(node) => link_node(*node, ctx),
+        Node::Nil => Ok(LinkedNode::Nil),
+        Node::Enumeration(e) => Ok(LinkedNode::Enumeration(e)),
+        Node::Unwrap(_) => Err(FlattenError::InvalidType),
+        Node::Generic(_) | Node::Instance(_) => Err(FlattenError::Infallible),
+        Node::KeyVal(_) | Node::Optional(_) => Err(FlattenError::Infallible),
     }
 }
 
-fn link_array(arr: Array, ctx: &BTreeMap<String, Node>) -> FlattenResult<LinkedNode> {
+fn link_array(arr: Array, ctx: &Scope) -> FlattenResult<LinkedNode> {
+    // Members of an array are always present, so they may not be null
+    if is_nullable(&arr.ty, ctx)? {
+        return Err(FlattenError::InvalidArray);
+    }
     // Similar to link_foreign, we only accept certain types as an array, and we don't follow
     // nesting types so we can flatten them
     link_node(*arr.ty, ctx).and_then(|node| match node {
-        // We don't accept nested arrays
+        // We accept nested arrays when each inner array has the same length. IE: [4*4 [4*4 i16]]
+        LinkedNode::Array(ref inner) if inner.is_fixed() => {
+            Ok(LinkedNode::Array(LinkedArray::bounded(node, arr.min, arr.len)))
+        }
         LinkedNode::Array(..) => Err(FlattenError::InvalidArray),
         // We don't accept inline fields inside an array
         LinkedNode::Fields(_) => Err(FlattenError::InvalidArray),
-        // We don't accept inline structs defined inside an array
-        LinkedNode::Struct(_) => Err(FlattenError::InvalidArray),
+        // Inline structs are hoisted into rules of their own before linking, see hoist
+        LinkedNode::Struct(_) | LinkedNode::Record(_) => Err(FlattenError::InvalidArray),
+        // We don't accept inline choices defined inside an array
+        LinkedNode::Choice(_) => Err(FlattenError::InvalidArray),
+        // We don't accept inline enumerations defined inside an array
+        LinkedNode::Enumeration(_) => Err(FlattenError::InvalidArray),
+        // We don't accept a tag on each member of an array
+        LinkedNode::Tag(_) => Err(FlattenError::InvalidArray),
+        // We don't accept a pattern on each member of an array
+        LinkedNode::Regexp(_) => Err(FlattenError::InvalidArray),
+        LinkedNode::Nil => Err(FlattenError::InvalidArray),
         // ConstainedType or Struct defined externally are the only acceptable array types
-        n => Ok(LinkedNode::Array(LinkedArray {
-            ty: Box::new(n),
-            len: arr.len,
-        })),
+        n => Ok(LinkedNode::Array(LinkedArray::bounded(n, arr.min, arr.len))),
     })
 }
 
-fn link_foreign(key: String, ctx: &BTreeMap<String, Node>) -> FlattenResult<LinkedNode> {
+fn link_tag(tag: Tag, ctx: &Scope) -> FlattenResult<LinkedNode> {
+    // A tag may only hold a value that is encoded on its own, so that the tag can be written in
+    // front of it
+    link_node(*tag.ty, ctx).and_then(|node| match node {
+        LinkedNode::Primative(ConstrainedPrimative::F16) => Err(FlattenError::InvalidTag),
+        LinkedNode::Constrained(Constrained {
+            ty: ConstrainedPrimative::F16,
+            ..
+        }) => Err(FlattenError::InvalidTag),
+        LinkedNode::Primative(_)
+        | LinkedNode::Constrained(_)
+        | LinkedNode::Bits(_)
+        | LinkedNode::ForeignStruct(_)
+        | LinkedNode::ForeignChoice(_)
+        | LinkedNode::ForeignEnumeration(_) => Ok(LinkedNode::Tag(LinkedTag::new(node, tag.tag))),
+        _ => Err(FlattenError::InvalidTag),
+    })
+}
+
+fn link_compare(compare: Compare, ctx: &Scope) -> FlattenResult<LinkedNode> {
+    // Only numbers are compared, and the constraints of a number named by another rule are kept
+    // so that a member may constrain it further
+    let Compare { constraint, ty } = compare;
+    let mut constrained = match link_node(*ty, ctx)? {
+        LinkedNode::Primative(ty) if is_number(&ty) => Constrained::new(ty, vec![]),
+        LinkedNode::Constrained(constrained) => constrained,
+        _ => return Err(FlattenError::InvalidComparison),
+    };
+    match fits_default(&LinkedNode::Primative(constrained.ty), &constraint.value, ctx) {
+        true => constrained.constraints.push(constraint),
+        false => return Err(FlattenError::InvalidComparison),
+    }
+    // A number that no value satisfies could never be valid
+    match constrained.initial() {
+        Some(_) => Ok(LinkedNode::Constrained(constrained)),
+        None => Err(FlattenError::InvalidComparison),
+    }
+}
+
+fn link_regexp(regexp: Regexp, ctx: &Scope) -> FlattenResult<LinkedNode> {
+    // Only text is matched to a pattern, and the text may only be matched to one pattern. The
+    // pattern is compiled now so that a pattern that could never match is an error
+    use ConstrainedPrimative::{Str, UnboundedStr};
+    match link_node(*regexp.ty, ctx)? {
+        LinkedNode::Primative(ty @ (Str(_) | UnboundedStr)) if is_valid(&regexp.pattern) => {
+            Ok(LinkedRegexp::new(ty, regexp.pattern).into())
+        }
+        _ => Err(FlattenError::InvalidRegexp),
+    }
+}
+
+fn link_bits(bits: Bits, ctx: &Scope) -> FlattenResult<LinkedNode> {
+    use ConstrainedPrimative::*;
+    let (ty, width) = match link_node(*bits.ty, ctx)? {
+        LinkedNode::Primative(U8) => (U8, 8),
+        LinkedNode::Primative(U16) => (U16, 16),
+        LinkedNode::Primative(U32) => (U32, 32),
+        LinkedNode::Primative(U64) => (U64, 64),
+        _ => return Err(FlattenError::InvalidBits),
+    };
+    // The names are the members of a group, written inline or named by another rule
+    let members = match *bits.names {
+        Node::Names(group) => link_field_key_values(group, ctx)?,
+        Node::Foreign(key) => match ctx.follow(&key)? {
+            (Node::Names(group) | Node::Group(group), scope) => {
+                link_field_key_values(group, &scope).map_err(|e| in_followed(e, &key))?
+            }
+            _ => return Err(FlattenError::InvalidBits),
+        },
+        _ => return Err(FlattenError::InvalidBits),
+    };
+    // Each name is given a distinct bit of the integer
+    let mut positions = BTreeSet::new();
+    let mut named = Vec::with_capacity(members.len());
+    for member in members {
+        match member.val() {
+            LinkedNode::Literal(Literal::UInt(n)) if *n < width && positions.insert(*n) => {
+                named.push(Bit::new(member.key().to_string(), *n as u32))
+            }
+            _ => return Err(FlattenError::InvalidBits),
+        }
+    }
+    Ok(LinkedBits::new(ty, named).into())
+}
+
+fn is_number(ty: &ConstrainedPrimative) -> bool {
+    use ConstrainedPrimative::*;
+    matches!(ty, U8 | U16 | U32 | U64 | I8 | I16 | I32 | I64 | F16 | F32 | F64)
+}
+
+fn link_foreign(key: String, ctx: &Scope) -> FlattenResult<LinkedNode> {
     // When linking a "foreign" struct, we simply note it's remote name instead of
-    // following the struct deeper.
-    ctx.get(&key)
-        .map(Node::clone)
-        .ok_or_else(|| FlattenError::ForeignKey(key.clone()))
-        .and_then(|node| match link_node(node, ctx)? {
-            LinkedNode::Struct(_s) => Ok(LinkedNode::ForeignStruct(key)),
-            node => Ok(node),
+    // following the struct deeper. Structs may therefore refer to themselves.
+    match ctx.rules.get(&key) {
+        Some(Node::Map(_)) | Some(Node::Record(_)) => Ok(LinkedNode::ForeignStruct(key)),
+        _ => {
+            let (node, scope) = ctx.follow(&key)?;
+            match link_node(node, &scope).map_err(|e| in_followed(e, &key))? {
+                LinkedNode::Choice(_c) => Ok(LinkedNode::ForeignChoice(key)),
+                LinkedNode::Enumeration(_e) => Ok(LinkedNode::ForeignEnumeration(key)),
+                node => Ok(node),
+            }
+        }
+    }
+}
+
+fn link_choice(choice: Choice, ctx: &Scope) -> FlattenResult<LinkedNode> {
+    // Each alternative must be a named type so that we have something to call the variant, nil
+    // is a variant of its own that holds nothing
+    let members = choice
+        .members
+        .into_iter()
+        .map(|node| match node {
+            Node::Foreign(key) => link_foreign(key.clone(), ctx).map(|n| LinkedKeyVal::new(key, n)),
+            Node::Nil => Ok(LinkedKeyVal::new("nil", LinkedNode::Nil)),
+            _ => Err(FlattenError::InvalidChoiceMember),
         })
+        .collect::<FlattenResult<Vec<LinkedKeyVal>>>()?;
+
+    // A choice between named literals is an enumeration of their values
+    if members.iter().all(|kv| matches!(kv.val(), LinkedNode::Literal(_))) {
+        let literals = members
+            .into_iter()
+            .filter_map(|kv| match kv.into_val() {
+                LinkedNode::Literal(lit) => Some(lit),
+                _ => None,
+            })
+            .collect::<Vec<Literal>>();
+        Enumeration::try_from(literals).map(LinkedNode::Enumeration)
+    } else {
+        Ok(LinkedNode::Choice(Variants { members }))
+    }
 }
 
-fn link_group(map: Group, ctx: &BTreeMap<String, Node>) -> FlattenResult<LinkedNode> {
+fn link_group(map: Group, ctx: &Scope) -> FlattenResult<LinkedNode> {
     link_field_key_values(map, ctx).map(|members| LinkedNode::Fields(Fields { members }))
 }
 
-fn link_struct(map: Group, ctx: &BTreeMap<String, Node>) -> FlattenResult<LinkedNode> {
-    link_field_key_values(map, ctx).map(|members| LinkedNode::Struct(Fields { members }))
+fn link_struct(map: Group, ctx: &Scope) -> FlattenResult<LinkedNode> {
+    let members = link_field_key_values(map, ctx)?;
+    // Members without a declared key are encoded by position, which must not clash with a key
+    let mut indices = BTreeSet::new();
+    for (n, member) in members.iter().enumerate() {
+        let index = member.index().unwrap_or(n as u32);
+        if !indices.insert(index) {
+            // The member is found by its key as it is written, after the members written like it
+            let written = written_key(member);
+            let nth = members[..n]
+                .iter()
+                .filter(|other| written_key(other) == written)
+                .count();
+            return Err(FlattenError::DuplicateMemberKey(index, written, nth));
+        }
+    }
+    Ok(LinkedNode::Struct(Fields { members }))
 }
 
-fn link_field_key_values(
-    map: Group,
-    ctx: &BTreeMap<String, Node>,
-) -> FlattenResult<Vec<LinkedKeyVal>> {
-    Ok(link_fields(map, ctx)?
-        .into_iter()
-        .map(LinkedKeyVal::from)
-        .collect())
+/// IE: 1 for the member 1: bool, and on for the member on: bool
+fn written_key(member: &LinkedKeyVal) -> String {
+    match member.index() {
+        Some(index) => index.to_string(),
+        None => member.key().to_string(),
+    }
 }
 
-fn link_fields(
-    map: Group,
-    ctx: &BTreeMap<String, Node>,
-) -> FlattenResult<Vec<(String, LinkedNode)>> {
+fn link_record(map: Group, ctx: &Scope) -> FlattenResult<LinkedNode> {
+    let members = link_field_key_values(map, ctx)?;
+    // Members are encoded by position, so only trailing members may be absent
+    let required = members.iter().rposition(|m| !m.is_optional());
+    let misplaced = members
+        .iter()
+        .enumerate()
+        .any(|(n, m)| m.is_indexed() || (m.is_optional() && Some(n) < required));
+    match misplaced {
+        true => Err(FlattenError::InvalidRecordMember),
+        false => Ok(LinkedNode::Record(Fields { members })),
+    }
+}
+
+fn link_field_key_values(map: Group, ctx: &Scope) -> FlattenResult<Vec<LinkedKeyVal>> {
     Ok(map
         .members
         .into_iter()
         .map(|node| link_field_member(node, ctx))
-        .collect::<FlattenResult<Vec<Vec<(String, LinkedNode)>>>>()?
+        .collect::<FlattenResult<Vec<Vec<LinkedKeyVal>>>>()?
         .into_iter()
         .flatten()
         .collect())
 }
 
-fn link_field_member(
-    node: Node,
-    ctx: &BTreeMap<String, Node>,
-) -> FlattenResult<Vec<(String, LinkedNode)>> {
+fn link_field_member(node: Node, ctx: &Scope) -> FlattenResult<Vec<LinkedKeyVal>> {
     match node {
-        Node::KeyVal(KeyVal(k, v)) => link_node(*v, ctx).map(|n| vec![(k, n)]),
-        Node::Foreign(key) => match ctx.get(&key).map(Node::clone) {
-            Some(Node::Group(g)) => link_fields(g, ctx),
+        Node::KeyVal(KeyVal(key, v, optional, index)) => {
+            let default = default_value(&v, ctx)?;
+            let optional = optional || is_nullable(&v, ctx)?;
+            let node = link_node(*v, ctx)?;
+            match default {
+                _ if node == LinkedNode::Nil => Err(FlattenError::InvalidNil),
+                Some(value) if !fits_default(&node, &value, ctx) => {
+                    Err(FlattenError::InvalidDefault(key))
+                }
+                default => Ok(vec![LinkedKeyVal {
+                    key,
+                    node,
+                    optional,
+                    index,
+                    default,
+                }]),
+            }
+        }
+        Node::Foreign(key) => match ctx.follow(&key)? {
+            (Node::Group(g), scope) => {
+                link_field_key_values(g, &scope).map_err(|e| in_followed(e, &key))
+            }
+            _ => Err(FlattenError::InvalidGroupMember(key)),
+        },
+        Node::Group(g) => link_field_key_values(g, ctx),
+        Node::Unwrap(node) => match *node {
+            Node::Foreign(key) => match ctx.follow(&key)? {
+                (Node::Map(g) | Node::Record(g) | Node::Group(g), scope) => {
+                    link_field_key_values(g, &scope).map_err(|e| in_followed(e, &key))
+                }
+                _ => Err(FlattenError::InvalidUnwrap(key)),
+            },
             _ => Err(FlattenError::InvalidType),
         },
+        Node::Optional(node) => link_field_member(*node, ctx).map(|members| {
+            members
+                .into_iter()
+                .map(LinkedKeyVal::into_optional)
+                .collect()
+        }),
         _ => Err(FlattenError::InvalidGroupMissingKey),
     }
 }
+
+/// True when a member holds a type that may be null, or a rule that names one. IE: u8 / nil
+fn is_nullable(node: &Node, ctx: &Scope) -> FlattenResult<bool> {
+    match node {
+        Node::Nullable(_) => Ok(true),
+        Node::Foreign(key)
+            if matches!(
+                ctx.rules.get(key),
+                Some(Node::Nullable(_) | Node::Foreign(_))
+            ) =>
+        {
+            let (node, scope) = ctx.follow(key)?;
+            is_nullable(&node, &scope)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// The default of a member, given to its type or to the rule its type is named after
+fn default_value(node: &Node, ctx: &Scope) -> FlattenResult<Option<Literal>> {
+    match node {
+        Node::Default(DefaultValue { value, .. }) => Ok(Some(value.clone())),
+        Node::Compare(Compare { ty, .. })
+        | Node::Regexp(Regexp { ty, .. })
+        | Node::Bits(Bits { ty, .. }) => default_value(ty, ctx),
+        Node::Foreign(key)
+            if matches!(
+                ctx.rules.get(key),
+                Some(
+                    Node::Default(_)
+                        | Node::Compare(_)
+                        | Node::Regexp(_)
+                        | Node::Bits(_)
+                        | Node::Foreign(_)
+                )
+            ) =>
+        {
+            let (node, scope) = ctx.follow(key)?;
+            default_value(&node, &scope)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// A default must be a value of the type of its member, or a member of its enumeration
+fn fits_default(node: &LinkedNode, value: &Literal, ctx: &Scope) -> bool {
+    use ConstrainedPrimative::*;
+    let int = match value {
+        Literal::Int(i) => Some(*i as i128),
+        Literal::UInt(u) => Some(*u as i128),
+        _ => None,
+    };
+    let within = |min: i128, max: i128| int.is_some_and(|n| min <= n && n <= max);
+    let text = match value {
+        Literal::Str(s) => Some(s.len()),
+        Literal::Char(c) => Some(c.len_utf8()),
+        _ => None,
+    };
+    match node {
+        LinkedNode::Primative(ty) => match ty {
+            U8 => within(u8::MIN as i128, u8::MAX as i128),
+            U16 => within(u16::MIN as i128, u16::MAX as i128),
+            U32 => within(u32::MIN as i128, u32::MAX as i128),
+            U64 => within(u64::MIN as i128, u64::MAX as i128),
+            I8 => within(i8::MIN as i128, i8::MAX as i128),
+            I16 => within(i16::MIN as i128, i16::MAX as i128),
+            I32 => within(i32::MIN as i128, i32::MAX as i128),
+            I64 => within(i64::MIN as i128, i64::MAX as i128),
+            F16 | F32 | F64 => int.is_some(),
+            Bool => matches!(value, Literal::Bool(_)),
+            Str(n) => text.is_some_and(|len| len as u64 <= *n),
+            UnboundedStr => text.is_some(),
+            Bytes(_) | UnboundedBytes | Any(_) | UnboundedAny => false,
+        },
+        LinkedNode::Range(range) => {
+            let (min, max) = range.bounds();
+            within(min, max)
+        }
+        LinkedNode::Tag(tag) => fits_default(&tag.ty, value, ctx),
+        LinkedNode::Regexp(LinkedRegexp { ty, pattern }) => {
+            let matches = match value {
+                Literal::Str(s) => is_match(pattern, s),
+                Literal::Char(c) => is_match(pattern, c.encode_utf8(&mut [0; 4])),
+                _ => false,
+            };
+            matches && fits_default(&LinkedNode::Primative(*ty), value, ctx)
+        }
+        LinkedNode::Bits(LinkedBits { ty, .. }) => {
+            fits_default(&LinkedNode::Primative(*ty), value, ctx)
+        }
+        LinkedNode::Constrained(Constrained { ty, constraints }) => {
+            fits_default(&LinkedNode::Primative(*ty), value, ctx)
+                && int.is_some_and(|n| constraints.iter().all(|c| c.accepts(n)))
+        }
+        LinkedNode::Enumeration(enumeration) => enumeration.position(value).is_some(),
+        LinkedNode::ForeignEnumeration(key) => match ctx.follow(key) {
+            Ok((node, scope)) => link_node(node, &scope)
+                .is_ok_and(|node| fits_default(&node, value, &scope)),
+            Err(_) => false,
+        },
+        _ => false,
+    }
+}
@@ -57,6 +57,15 @@ impl From<ConstrainedPrimative> for Value {
             ConstrainedPrimative::I32 => obj.insert("value".into(), Value::Scalar("i32".into())),
             ConstrainedPrimative::I64 => obj.insert("value".into(), Value::Scalar("i64".into())),
             ConstrainedPrimative::Bool => obj.insert("value".into(), Value::Scalar("bool".into())),
+            ConstrainedPrimative::F16 => obj.insert("value".into(), Value::Scalar("f16".into())),
+            ConstrainedPrimative::F32 => obj.insert("value".into(), Value::Scalar("f32".into())),
+            ConstrainedPrimative::F64 => obj.insert("value".into(), Value::Scalar("f64".into())),
+            ConstrainedPrimative::UnboundedStr => {
+                obj.insert("value".into(), Value::Scalar("tstr".into()))
+            }
+            ConstrainedPrimative::UnboundedBytes => {
+                obj.insert("value".into(), Value::Scalar("bstr".into()))
+            }
             ConstrainedPrimative::Str(n) => {
                 obj.insert("value".into(), Value::Scalar("string".into()));
                 obj.insert("len".into(), Value::Scalar((n as i64).into()))
@@ -65,6 +74,13 @@ impl From<ConstrainedPrimative> for Value {
                 obj.insert("value".into(), Value::Scalar("bytes".into()));
                 obj.insert("len".into(), Value::Scalar((n as i64).into()))
             }
+            ConstrainedPrimative::UnboundedAny => {
+                obj.insert("value".into(), Value::Scalar("any".into()))
+            }
+            ConstrainedPrimative::Any(n) => {
+                obj.insert("value".into(), Value::Scalar("any".into()));
+                obj.insert("len".into(), Value::Scalar((n as i64).into()))
+            }
         };
         Value::Object(obj)
     }
@@ -85,6 +101,11 @@ impl TryFrom<Value> for ConstrainedPrimative {
             "i32" => Ok(ConstrainedPrimative::I32),
             "i64" => Ok(ConstrainedPrimative::I64),
             "bool" => Ok(ConstrainedPrimative::Bool),
+            "f16" => Ok(ConstrainedPrimative::F16),
+            "f32" => Ok(ConstrainedPrimative::F32),
+            "f64" => Ok(ConstrainedPrimative::F64),
+            "tstr" => Ok(ConstrainedPrimative::UnboundedStr),
+            "bstr" => Ok(ConstrainedPrimative::UnboundedBytes),
             "string" => {
                 let len = get_value_int("len", &mut obj)?;
                 Ok(ConstrainedPrimative::Str(len as u64))
@@ -93,6 +114,11 @@ impl TryFrom<Value> for ConstrainedPrimative {
                 let len = get_value_int("len", &mut obj)?;
                 Ok(ConstrainedPrimative::Bytes(len as u64))
             }
+            "any" if obj.contains_key("len") => {
+                let len = get_value_int("len", &mut obj)?;
+                Ok(ConstrainedPrimative::Any(len as u64))
+            }
+            "any" => Ok(ConstrainedPrimative::UnboundedAny),
             v => Err(LiquidError::UnexpectedValue(v.to_string())),
         }
     }
@@ -157,7 +183,7 @@ impl TryFrom<Value> for Literal {
             "bool" => Ok(Literal::Bool(get_value_bool("value", &mut obj)?)),
             "char" => Ok(Literal::Char(get_value_char("value", &mut obj)?)),
             "string" => Ok(Literal::Str(get_value_kstr("value", &mut obj)?.to_string())),
-            "bytes" => {
+            "bytes" | "array" => {
                 let v: Vec<u8> = get_value_array("value", &mut obj)?
                     .into_iter()
                     .map(|v| {
@@ -176,11 +202,15 @@ impl TryFrom<Value> for Literal {
 
 impl From<LinkedArray> for Value {
     fn from(value: LinkedArray) -> Self {
-        Value::Object(liquid_core::object!({
+        let mut obj = liquid_core::object!({
             "type": "array",
             "len": value.len,
-            "value": Value::from(*value.ty),
-        }))
+        });
+        if !value.is_fixed() {
+            obj.insert("min".into(), Value::scalar(value.min as i64));
+        }
+        obj.insert("value".into(), Value::from(*value.ty));
+        Value::Object(obj)
     }
 }
 
@@ -189,13 +219,188 @@ impl TryFrom<Value> for LinkedArray {
     fn try_from(value: Value) -> Result<Self, Self::Error> {
         let mut obj = take_value_object(value)?;
         check_valid_type("array", &mut obj)?;
-        Ok(LinkedArray {
-            len: get_value_int("len", &mut obj)? as usize,
-            ty: Box::new(LinkedNode::try_from(take_value("value", &mut obj)?)?),
+        let len = get_value_int("len", &mut obj)? as usize;
+        let min = match obj.contains_key("min") {
+            true => get_value_int("min", &mut obj)? as usize,
+            false => len,
+        };
+        Ok(LinkedArray::bounded(
+            LinkedNode::try_from(take_value("value", &mut obj)?)?,
+            min,
+            len,
+        ))
+    }
+}
+
+impl From<LinkedTag> for Value {
+    fn from(value: LinkedTag) -> Self {
+        Value::Object(liquid_core::object!({
+            "type": "tag",
+            "tag": value.tag as i64,
+            "value": Value::from(*value.ty),
+        }))
+    }
+}
+
+impl TryFrom<Value> for LinkedTag {
+    type Error = LiquidError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut obj = take_value_object(value)?;
+        check_valid_type("tag", &obj)?;
+        let tag = get_value_int("tag", &mut obj)? as u64;
+        Ok(LinkedTag::new(
+            LinkedNode::try_from(take_value("value", &mut obj)?)?,
+            tag,
+        ))
+    }
+}
+
+impl From<Range> for Value {
+    fn from(value: Range) -> Self {
+        Value::Object(liquid_core::object!({
+            "type": "range",
+            "value": Value::from(value.ty),
+            "min": Value::from(value.min),
+            "max": Value::from(value.max),
+        }))
+    }
+}
+
+impl TryFrom<Value> for Range {
+    type Error = LiquidError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut obj = take_value_object(value)?;
+        check_valid_type("range", &obj)?;
+        Ok(Range {
+            ty: ConstrainedPrimative::try_from(take_value("value", &mut obj)?)?,
+            min: Literal::try_from(take_value("min", &mut obj)?)?,
+            max: Literal::try_from(take_value("max", &mut obj)?)?,
         })
     }
 }
 
+impl From<Constraint> for Value {
+    fn from(value: Constraint) -> Self {
+        Value::Object(liquid_core::object!({
+            "type": "constraint",
+            "op": value.op.as_str(),
+            "value": Value::from(value.value),
+        }))
+    }
+}
+
+impl TryFrom<Value> for Constraint {
+    type Error = LiquidError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut obj = take_value_object(value)?;
+        check_valid_type("constraint", &obj)?;
+        let op = get_value_kstr("op", &obj)?;
+        let op = Comparison::from_op(op.as_str())
+            .ok_or_else(|| LiquidError::UnexpectedValue(op.to_string()))?;
+        Ok(Constraint::new(
+            op,
+            Literal::try_from(take_value("value", &mut obj)?)?,
+        ))
+    }
+}
+
+impl From<Constrained> for Value {
+    fn from(value: Constrained) -> Self {
+        let constraints = value.constraints.into_iter().map(Value::from).collect();
+        Value::Object(liquid_core::object!({
+            "type": "constrained",
+            "value": Value::from(value.ty),
+            "constraints": Value::Array(constraints),
+        }))
+    }
+}
+
+impl TryFrom<Value> for Constrained {
+    type Error = LiquidError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut obj = take_value_object(value)?;
+        check_valid_type("constrained", &obj)?;
+        let constraints = take_value_array("constraints", &mut obj)?
+            .into_iter()
+            .map(Constraint::try_from)
+            .collect::<Result<Vec<Constraint>, LiquidError>>()?;
+        Ok(Constrained::new(
+            ConstrainedPrimative::try_from(take_value("value", &mut obj)?)?,
+            constraints,
+        ))
+    }
+}
+
+impl From<LinkedRegexp> for Value {
+    fn from(value: LinkedRegexp) -> Self {
+        Value::Object(liquid_core::object!({
+            "type": "regexp",
+            "value": Value::from(value.ty),
+            "pattern": value.pattern,
+        }))
+    }
+}
+
+impl TryFrom<Value> for LinkedRegexp {
+    type Error = LiquidError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut obj = take_value_object(value)?;
+        check_valid_type("regexp", &obj)?;
+        let pattern = get_value_kstr("pattern", &obj)?.into_string();
+        Ok(LinkedRegexp::new(
+            ConstrainedPrimative::try_from(take_value("value", &mut obj)?)?,
+            pattern,
+        ))
+    }
+}
+
+impl From<Bit> for Value {
+    fn from(value: Bit) -> Self {
+        Value::Object(liquid_core::object!({
+            "type": "bit",
+            "name": value.name,
+            "position": value.position as i64,
+        }))
+    }
+}
+
+impl TryFrom<Value> for Bit {
+    type Error = LiquidError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut obj = take_value_object(value)?;
+        check_valid_type("bit", &obj)?;
+        let position = get_value_int("position", &mut obj)? as u32;
+        Ok(Bit::new(get_value_kstr("name", &obj)?.into_string(), position))
+    }
+}
+
+impl From<LinkedBits> for Value {
+    fn from(value: LinkedBits) -> Self {
+        let bits = value.bits.into_iter().map(Value::from).collect();
+        Value::Object(liquid_core::object!({
+            "type": "bits",
+            "value": Value::from(value.ty),
+            "bits": Value::Array(bits),
+        }))
+    }
+}
+
+impl TryFrom<Value> for LinkedBits {
+    type Error = LiquidError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut obj = take_value_object(value)?;
+        check_valid_type("bits", &obj)?;
+        let bits = take_value_array("bits", &mut obj)?
+            .into_iter()
+            .map(Bit::try_from)
+            .collect::<Result<Vec<Bit>, LiquidError>>()?;
+        Ok(LinkedBits::new(
+            ConstrainedPrimative::try_from(take_value("value", &mut obj)?)?,
+            bits,
+        ))
+    }
+}
+
 struct Members(pub Vec<LinkedKeyVal>);
 impl From<Members> for Fields {
     fn from(value: Members) -> Self {
@@ -258,13 +463,79 @@ impl TryFrom<Value> for Struct {
     }
 }
 
+/// Records are rendered as structs flagged to be encoded as an array of their fields
+struct Record(pub Fields);
+impl From<Record> for Value {
+    fn from(value: Record) -> Self {
+        let members = value.0.members.into_iter().map(Value::from).collect();
+        Value::Object(liquid_core::object!({
+            "type":"struct",
+            "record": true,
+            "value": Value::Array(members)
+        }))
+    }
+}
+
+impl From<Variants> for Value {
+    fn from(value: Variants) -> Self {
+        let members = value.members.into_iter().map(Value::from).collect();
+        Value::Object(liquid_core::object!({
+            "type":"choice",
+            "value": Value::Array(members)
+        }))
+    }
+}
+
+impl TryFrom<Value> for Variants {
+    type Error = LiquidError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let obj = get_value_object(&value)?;
+        check_valid_type("choice", obj)?;
+        Members::try_from(value).map(|members| Variants { members: members.0 })
+    }
+}
+
+impl From<Enumeration> for Value {
+    fn from(value: Enumeration) -> Self {
+        let members = value.members.into_iter().map(Value::from).collect();
+        Value::Object(liquid_core::object!({
+            "type":"enumeration",
+            "value": Value::Array(members)
+        }))
+    }
+}
+
+impl TryFrom<Value> for Enumeration {
+    type Error = LiquidError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut obj = take_value_object(value)?;
+        check_valid_type("enumeration", &obj)?;
+        take_value_array("value", &mut obj)?
+            .into_iter()
+            .map(Literal::try_from)
+            .collect::<Result<Vec<Literal>, LiquidError>>()
+            .map(|members| Enumeration { members })
+    }
+}
+
 impl From<LinkedKeyVal> for Value {
     fn from(value: LinkedKeyVal) -> Self {
-        Value::Object(liquid_core::object!({
+        let mut obj = liquid_core::object!({
             "type":"keyval",
-            "key": value.0,
-            "value": Value::from(value.1),
-        }))
+            "key": value.key,
+            "value": Value::from(value.node),
+        });
+        // Only optional keys are flagged so that required keys render as they always have
+        if value.optional {
+            obj.insert("optional".into(), Value::Scalar(true.into()));
+        }
+        if let Some(index) = value.index {
+            obj.insert("index".into(), Value::Scalar((index as i64).into()));
+        }
+        if let Some(default) = value.default {
+            obj.insert("default".into(), Value::from(default));
+        }
+        Value::Object(obj)
     }
 }
 
@@ -273,10 +544,28 @@ impl TryFrom<Value> for LinkedKeyVal {
     fn try_from(value: Value) -> Result<Self, Self::Error> {
         let mut obj = take_value_object(value)?;
         let value = take_value("value", &mut obj)?;
-        Ok(LinkedKeyVal(
-            get_value_kstr("key", &obj)?.to_string(),
-            LinkedNode::try_from(value)?,
-        ))
+        // A missing optional flag means the key is required
+        let optional = match obj.contains_key("optional") {
+            true => get_value_bool("optional", &mut obj)?,
+            false => false,
+        };
+        // A missing index means the key is encoded by position
+        let index = match obj.contains_key("index") {
+            true => Some(get_value_int("index", &mut obj)? as u32),
+            false => None,
+        };
+        // A missing default means the member is initialized with zero
+        let default = match obj.remove("default") {
+            Some(default) => Some(Literal::try_from(default)?),
+            None => None,
+        };
+        Ok(LinkedKeyVal {
+            key: get_value_kstr("key", &obj)?.to_string(),
+            node: LinkedNode::try_from(value)?,
+            optional,
+            index,
+            default,
+        })
     }
 }
 
@@ -285,13 +574,30 @@ impl From<LinkedNode> for Value {
         match value {
             LinkedNode::Literal(l) => Value::from(l),
             LinkedNode::Primative(p) => Value::from(p),
+            LinkedNode::Range(r) => Value::from(r),
+            LinkedNode::Constrained(c) => Value::from(c),
+            LinkedNode::Regexp(r) => Value::from(r),
+            LinkedNode::Bits(b) => Value::from(b),
             LinkedNode::Array(a) => Value::from(a),
+            LinkedNode::Tag(t) => Value::from(t),
             LinkedNode::Fields(f) => Value::from(f),
             LinkedNode::Struct(s) => Value::from(Struct(s)),
+            LinkedNode::Record(r) => Value::from(Record(r)),
             LinkedNode::ForeignStruct(f) => Value::Object(liquid_core::object!({
                 "type": "foreign",
                 "value": Value::Scalar(f.into())
             })),
+            LinkedNode::Choice(c) => Value::from(c),
+            LinkedNode::ForeignChoice(f) => Value::Object(liquid_core::object!({
+                "type": "foreignChoice",
+                "value": Value::Scalar(f.into())
+            })),
+            LinkedNode::Enumeration(e) => Value::from(e),
+            LinkedNode::ForeignEnumeration(f) => Value::Object(liquid_core::object!({
+                "type": "foreignEnumeration",
+                "value": Value::Scalar(f.into())
+            })),
+            LinkedNode::Nil => Value::Object(liquid_core::object!({"type": "nil"})),
         }
     }
 }
@@ -303,12 +609,29 @@ impl TryFrom<Value> for LinkedNode {
         match get_value_kstr("type", &obj)?.as_str() {
             "literal" => Literal::try_from(value).map(LinkedNode::Literal),
             "primative" => ConstrainedPrimative::try_from(value).map(LinkedNode::Primative),
+            "range" => Range::try_from(value).map(LinkedNode::Range),
+            "constrained" => Constrained::try_from(value).map(LinkedNode::Constrained),
+            "regexp" => LinkedRegexp::try_from(value).map(LinkedNode::Regexp),
+            "bits" => LinkedBits::try_from(value).map(LinkedNode::Bits),
             "array" => LinkedArray::try_from(value).map(LinkedNode::Array),
+            "tag" => LinkedTag::try_from(value).map(LinkedNode::Tag),
             "fields" => Fields::try_from(value).map(LinkedNode::Fields),
+            "struct" if obj.contains_key("record") => {
+                Struct::try_from(value).map(|s| LinkedNode::Record(s.0))
+            }
             "struct" => Struct::try_from(value).map(|s| LinkedNode::Struct(s.0)),
             "foreign" => get_value_kstr("value", &obj)
                 .map(|s| s.into_string())
                 .map(LinkedNode::ForeignStruct),
+            "choice" => Variants::try_from(value).map(LinkedNode::Choice),
+            "foreignChoice" => get_value_kstr("value", obj)
+                .map(|s| s.into_string())
+                .map(LinkedNode::ForeignChoice),
+            "enumeration" => Enumeration::try_from(value).map(LinkedNode::Enumeration),
+            "foreignEnumeration" => get_value_kstr("value", obj)
+                .map(|s| s.into_string())
+                .map(LinkedNode::ForeignEnumeration),
+            "nil" => Ok(LinkedNode::Nil),
             ty => Err(LiquidError::InvalidType(ty.into())),
         }
     }
@@ -12,6 +12,14 @@ pub enum Primative {
     TStr,
     /// The CDDL primative "bool" type
     Bool,
+    /// The CDDL primative "float16" type (a half precision float)
+    Float16,
+    /// The CDDL primative "float32" type (a single precision float)
+    Float32,
+    /// The CDDL primative "float64" type (a double precision float)
+    Float64,
+    /// The CDDL primative "any" type (a single CBOR item of any type)
+    Any,
     /// A CDDL type defined in another rule further in the ruleset
     Unresolved(String),
 }
@@ -29,6 +37,7 @@ impl Primative {
             (Primative::UInt, 8) => Ok(ConstrainedPrimative::U64),
             (Primative::TStr, n) => Ok(ConstrainedPrimative::Str(n)),
             (Primative::BStr, n) => Ok(ConstrainedPrimative::Bytes(n)),
+            (Primative::Any, n) => Ok(ConstrainedPrimative::Any(n)),
             (prim, size) => Err(FlattenError::InvalidSizeConstraint(prim.into(), size)),
         }
     }
@@ -42,6 +51,10 @@ impl From<String> for Primative {
             "tstr" | "text" => Primative::TStr,
             "bstr" | "bytes" => Primative::BStr,
             "bool" | "boolean" => Primative::Bool,
+            "float16" => Primative::Float16,
+            "float32" | "float16-32" => Primative::Float32,
+            "float64" | "float32-64" | "float16-32-64" | "float" => Primative::Float64,
+            "any" => Primative::Any,
             _ => Primative::Unresolved(value),
         }
     }
@@ -55,6 +68,10 @@ impl From<Primative> for String {
             Primative::TStr => "tstr".to_string(),
             Primative::BStr => "bstr".to_string(),
             Primative::Bool => "bool".to_string(),
+            Primative::Float16 => "float16".to_string(),
+            Primative::Float32 => "float32".to_string(),
+            Primative::Float64 => "float64".to_string(),
+            Primative::Any => "any".to_string(),
             Primative::Unresolved(s) => s,
         }
     }
@@ -80,10 +97,24 @@ pub enum ConstrainedPrimative {
     I64,
     /// bool
     Bool,
+    /// float16 (stored as an f32)
+    F16,
+    /// float32
+    F32,
+    /// float64
+    F64,
     /// A tstr of N size
     Str(u64),
     /// A byte array of N size
     Bytes(u64),
+    /// A tstr without a size, only for languages that can allocate
+    UnboundedStr,
+    /// A bstr without a size, only for languages that can allocate
+    UnboundedBytes,
+    /// Any CBOR item of at most N bytes, kept encoded
+    Any(u64),
+    /// Any CBOR item without a size, only for languages that can allocate
+    UnboundedAny,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -98,7 +129,7 @@ pub enum Literal {
     Str(String),
     /// A CDDL literal char, AKA 'G'
     Char(char),
-    /// A CDDL literal byte array AKA [3,2,1]
+    /// A CDDL literal byte string, AKA h'030201', b64'AwIB' or 'abc'
     Bytes(Vec<u8>),
 }
 
@@ -116,22 +147,11 @@ impl From<String> for Literal {
     }
 }
 
+/// Byte strings are kept as bytes even when they are valid UTF-8, so that 'abc' and "abc" are
+/// distinct literals
 impl From<Vec<u8>> for Literal {
     fn from(value: Vec<u8>) -> Self {
-        match std::str::from_utf8(&value) {
-            Ok(s) => Literal::from(s.to_string()),
-            _ => {
-                if value.len() == 1 {
-                    value
-                        .iter()
-                        .next()
-                        .map(|val| Literal::Int(*val as i64))
-                        .unwrap_or_else(|| Literal::Bytes(value))
-                } else {
-                    Literal::Bytes(value)
-                }
-            }
-        }
+        Literal::Bytes(value)
     }
 }
 
@@ -175,11 +195,168 @@ from_uint!(u32);
 from_uint!(u16);
 from_uint!(u8);
 
+/// A choice between literal values
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enumeration {
+    pub members: Vec<Literal>,
+}
+
+impl TryFrom<Vec<Literal>> for Enumeration {
+    type Error = FlattenError;
+    /// An enumeration must be all integers or all text so that it can be decoded unambiguously.
+    /// The integers must all fit an i64 or all fit a u64 so that they share a representation
+    fn try_from(members: Vec<Literal>) -> FlattenResult<Enumeration> {
+        let is_int = |lit: &Literal| matches!(lit, Literal::Int(_) | Literal::UInt(_));
+        let is_text = |lit: &Literal| matches!(lit, Literal::Str(_) | Literal::Char(_));
+        let is_neg = |lit: &Literal| matches!(lit, Literal::Int(i) if *i < 0);
+        let is_big = |lit: &Literal| matches!(lit, Literal::UInt(u) if *u > i64::MAX as u64);
+        let mixed = members.iter().any(is_neg) && members.iter().any(is_big);
+        if mixed || !(members.iter().all(is_int) || members.iter().all(is_text)) {
+            return Err(FlattenError::InvalidEnumeration);
+        }
+        // Each member is a variant named after its value, so values and names are distinct
+        for (n, member) in members.iter().enumerate() {
+            let earlier = &members[..n];
+            if earlier.iter().any(|other| same_value(other, member)) {
+                return Err(FlattenError::DuplicateEnumerationValue(member.text()));
+            }
+            if let Some(other) = earlier.iter().find(|other| same_name(other, member)) {
+                let error = FlattenError::EnumerationNameClash(member.text(), other.text());
+                return Err(error);
+            }
+        }
+        Ok(Enumeration { members })
+    }
+}
+
+impl Enumeration {
+    /// The position of the member that holds a value. IE: "dhcp" is 1 in "static" / "dhcp"
+    pub fn position(&self, value: &Literal) -> Option<usize> {
+        self.members
+            .iter()
+            .position(|member| same_value(member, value))
+    }
+}
+
+impl Literal {
+    /// The value of a literal as it is written in the schema, without quotes
+    fn text(&self) -> String {
+        match self {
+            Literal::Int(i) => i.to_string(),
+            Literal::UInt(u) => u.to_string(),
+            Literal::Bool(b) => b.to_string(),
+            Literal::Str(s) => s.clone(),
+            Literal::Char(c) => c.to_string(),
+            Literal::Bytes(b) => format!("{:?}", b),
+        }
+    }
+}
+
+/// True when two literals hold the same value. IE: 1 and 1u, or "C" and 'C'
+fn same_value(a: &Literal, b: &Literal) -> bool {
+    match (a, b) {
+        (Literal::Int(i), Literal::UInt(u)) | (Literal::UInt(u), Literal::Int(i)) => {
+            *i as i128 == *u as i128
+        }
+        (Literal::Str(s), Literal::Char(c)) | (Literal::Char(c), Literal::Str(s)) => {
+            s.chars().eq(std::iter::once(*c))
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// True when two text literals name the same variant in any language. IE: "dhcp-v4" and
+/// "dhcp_v4" are both DhcpV4, or DHCP_V4 in C
+fn same_name(a: &Literal, b: &Literal) -> bool {
+    use heck::{ToShoutySnakeCase, ToUpperCamelCase};
+    let (a, b) = match (a, b) {
+        (Literal::Str(_) | Literal::Char(_), Literal::Str(_) | Literal::Char(_)) => {
+            (a.text(), b.text())
+        }
+        _ => return false,
+    };
+    let camel = a.to_upper_camel_case();
+    let shouty = a.to_shouty_snake_case();
+    (!camel.is_empty() && camel == b.to_upper_camel_case())
+        || (!shouty.is_empty() && shouty == b.to_shouty_snake_case())
+}
+
+/// An integer constrained to an inclusive range of values
+/// IE: channel = 1..14
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    /// The smallest primative that holds every value of the range
+    pub ty: ConstrainedPrimative,
+    /// The smallest value of the range
+    pub min: Literal,
+    /// The largest value of the range
+    pub max: Literal,
+}
+
+impl Range {
+    pub fn new(min: i128, max: i128) -> FlattenResult<Range> {
+        let ty = match (min, max) {
+            (min, max) if min > max => Err(FlattenError::InvalidRange),
+            (min, max) if min >= 0 && max <= u8::MAX as i128 => Ok(ConstrainedPrimative::U8),
+            (min, max) if min >= 0 && max <= u16::MAX as i128 => Ok(ConstrainedPrimative::U16),
+            (min, max) if min >= 0 && max <= u32::MAX as i128 => Ok(ConstrainedPrimative::U32),
+            (min, max) if min >= 0 && max <= u64::MAX as i128 => Ok(ConstrainedPrimative::U64),
+            (min, max) if min >= i8::MIN as i128 && max <= i8::MAX as i128 => {
+                Ok(ConstrainedPrimative::I8)
+            }
+            (min, max) if min >= i16::MIN as i128 && max <= i16::MAX as i128 => {
+                Ok(ConstrainedPrimative::I16)
+            }
+            (min, max) if min >= i32::MIN as i128 && max <= i32::MAX as i128 => {
+                Ok(ConstrainedPrimative::I32)
+            }
+            (min, max) if min >= i64::MIN as i128 && max <= i64::MAX as i128 => {
+                Ok(ConstrainedPrimative::I64)
+            }
+            _ => Err(FlattenError::InvalidRange),
+        }?;
+        let literal = |n: i128| match n {
+            n if n < 0 => Literal::Int(n as i64),
+            n => Literal::UInt(n as u64),
+        };
+        Ok(Range {
+            ty,
+            min: literal(min),
+            max: literal(max),
+        })
+    }
+
+    /// The smallest and largest values of the range
+    pub fn bounds(&self) -> (i128, i128) {
+        let int = |lit: &Literal| match lit {
+            Literal::Int(i) => *i as i128,
+            Literal::UInt(u) => *u as i128,
+            _ => 0,
+        };
+        (int(&self.min), int(&self.max))
+    }
+}
+
+/// A key, a value, if the key may be absent, and the declared integer key if any.
+/// IE: ? gateway: ip-addr or 1: u8
 #[derive(Clone, Debug, PartialEq)]
-pub struct KeyVal(pub(crate) String, pub(crate) Box<Node>);
+pub struct KeyVal(
+    pub(crate) String,
+    pub(crate) Box<Node>,
+    pub(crate) bool,
+    pub(crate) Option<u32>,
+);
 impl KeyVal {
     pub fn new<'a, K: Into<Cow<'a, str>>>(key: K, node: Node) -> KeyVal {
-        KeyVal(key.into().into(), Box::new(node))
+        KeyVal(key.into().into(), Box::new(node), false, None)
+    }
+
+    pub fn optional<'a, K: Into<Cow<'a, str>>>(key: K, node: Node) -> KeyVal {
+        KeyVal(key.into().into(), Box::new(node), true, None)
+    }
+
+    pub fn indexed(self, index: u32) -> KeyVal {
+        KeyVal(self.0, self.1, self.2, Some(index))
     }
 }
 impl From<(&str, Node)> for KeyVal {
@@ -188,9 +365,11 @@ impl From<(&str, Node)> for KeyVal {
     }
 }
 
+/// An array of at least min and at most len members. IE: [4*4 u8] or [0*4 u8]
 #[derive(Debug, Clone, PartialEq)]
 pub struct Array {
     pub len: usize,
+    pub min: usize,
     pub ty: Box<Node>,
 }
 
@@ -198,6 +377,15 @@ impl Array {
     pub fn new(node: Node, len: usize) -> Array {
         Array {
             ty: Box::new(node),
+            min: len,
+            len,
+        }
+    }
+
+    pub fn bounded(node: Node, min: usize, len: usize) -> Array {
+        Array {
+            ty: Box::new(node),
+            min,
             len,
         }
     }
@@ -208,6 +396,146 @@ pub struct Group {
     pub members: Vec<Node>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub members: Vec<Node>,
+}
+
+/// A type wrapped in a CBOR tag
+/// IE: #6.1(uint .size 8)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    pub tag: u64,
+    pub ty: Box<Node>,
+}
+
+impl Tag {
+    pub fn new(node: Node, tag: u64) -> Tag {
+        Tag {
+            tag,
+            ty: Box::new(node),
+        }
+    }
+}
+
+/// A type given the value its members take when they are not set
+/// IE: uint .size 2 .default 80
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultValue {
+    pub value: Literal,
+    pub ty: Box<Node>,
+}
+
+/// How a number is compared to the value of a constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// .lt
+    Lt,
+    /// .le
+    Le,
+    /// .gt
+    Gt,
+    /// .ge
+    Ge,
+    /// .ne
+    Ne,
+}
+
+impl Comparison {
+    /// The comparison of a control operator. IE: "le" is Le
+    pub fn from_op(op: &str) -> Option<Comparison> {
+        match op {
+            "lt" => Some(Comparison::Lt),
+            "le" => Some(Comparison::Le),
+            "gt" => Some(Comparison::Gt),
+            "ge" => Some(Comparison::Ge),
+            "ne" => Some(Comparison::Ne),
+            _ => None,
+        }
+    }
+
+    /// The name of the control operator
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Comparison::Lt => "lt",
+            Comparison::Le => "le",
+            Comparison::Gt => "gt",
+            Comparison::Ge => "ge",
+            Comparison::Ne => "ne",
+        }
+    }
+}
+
+/// A comparison every value of a number must satisfy
+/// IE: .le 100
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    pub op: Comparison,
+    pub value: Literal,
+}
+
+impl Constraint {
+    pub fn new(op: Comparison, value: Literal) -> Constraint {
+        Constraint { op, value }
+    }
+
+    /// True when a number satisfies the constraint
+    pub fn accepts(&self, n: i128) -> bool {
+        let value = match self.value {
+            Literal::Int(i) => i as i128,
+            Literal::UInt(u) => u as i128,
+            _ => return false,
+        };
+        match self.op {
+            Comparison::Lt => n < value,
+            Comparison::Le => n <= value,
+            Comparison::Gt => n > value,
+            Comparison::Ge => n >= value,
+            Comparison::Ne => n != value,
+        }
+    }
+}
+
+/// A type compared to a value
+/// IE: uint .size 1 .le 100
+#[derive(Debug, Clone, PartialEq)]
+pub struct Compare {
+    pub constraint: Constraint,
+    pub ty: Box<Node>,
+}
+
+/// A text string that must match a pattern
+/// IE: tstr .size 32 .regexp "[a-z]+"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regexp {
+    pub pattern: String,
+    pub ty: Box<Node>,
+}
+
+/// An unsigned integer whose bits are named by the members of a group
+/// IE: uint .size 1 .bits status-bits
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bits {
+    pub names: Box<Node>,
+    pub ty: Box<Node>,
+}
+
+/// A rule with generic parameters
+/// IE: message<T> = { id: u32, body: T }
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generic {
+    pub params: Vec<String>,
+    pub node: Box<Node>,
+}
+
+/// A generic rule given arguments
+/// IE: message<status>
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance {
+    pub name: String,
+    pub args: Vec<Node>,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Node {
     /// A Literal type such as "true" or 3 or "hello"
@@ -223,9 +551,57 @@ pub enum Node {
     /// A CDDL map defined using curly braces { }
     /// IE: network = { network-group }
     Map(Group),
+    /// A CDDL array of named members defined using square brackets [ ]
+    /// IE: point = [ x: i16, y: i16 ]
+    Record(Group),
     /// A single key: value item
     /// IE: foo: int .size 2
     KeyVal(KeyVal),
+    /// An integer constrained to a range of values
+    /// IE: temp = -40..125
+    Range(Range),
+    /// A CDDL type choice defined using a slash /
+    /// IE: msg = ping / pong / reset
+    Choice(Choice),
+    /// A CDDL type choice between literal values
+    /// IE: mode = "dhcp" / "static" / "off"
+    Enumeration(Enumeration),
+    /// A type wrapped in a CBOR tag
+    /// IE: epoch = #6.1(uint .size 8)
+    Tag(Tag),
+    /// A type with a default value, given to the members that hold it
+    /// IE: port: uint .size 2 .default 80
+    Default(DefaultValue),
+    /// A number that must satisfy a comparison, checked by the validator of the struct holding it
+    /// IE: percent: uint .size 1 .le 100
+    Compare(Compare),
+    /// A text string that must match a pattern, checked when it is decoded
+    /// IE: hostname: tstr .size 32 .regexp "[a-z0-9.-]+"
+    Regexp(Regexp),
+    /// An unsigned integer whose bits are named, given accessors by the struct holding it
+    /// IE: status: uint .size 1 .bits status-bits
+    Bits(Bits),
+    /// The values of named members, read as the names of the bits of an integer
+    /// IE: status-bits = &( ready: 0, fault: 1 )
+    Names(Group),
+    /// A rule with generic parameters, replaced by an instance for each set of arguments
+    /// IE: message<T> = { id: u32, body: T }
+    Generic(Generic),
+    /// A generic rule given arguments, resolved to a rule named after both
+    /// IE: message<status> ; message_status
+    Instance(Instance),
+    /// The members of a map or array included in another
+    /// IE: ~header
+    Unwrap(Box<Node>),
+    /// A group included in a struct whose members may be absent together
+    /// IE: ? footer
+    Optional(Box<Node>),
+    /// The CBOR null, a variant of its own in a choice
+    /// IE: reply = ack / nak / nil
+    Nil,
+    /// A type that may also be null, the members that hold it are optional
+    /// IE: gateway: ip-addr / nil
+    Nullable(Box<Node>),
     /// An unresovoved primative expects to be resolved via second pass when creating a LinkedNode
     /// String is a key to a Node::Foreign (or will error)
     Foreign(String),
@@ -262,34 +638,90 @@ pub struct Fields {
     pub members: Vec<LinkedKeyVal>,
 }
 
+/// A member of a struct, fully resolved
 #[derive(Debug, Clone, PartialEq)]
-pub struct LinkedKeyVal(pub String, pub LinkedNode);
+pub struct LinkedKeyVal {
+    pub key: String,
+    pub node: LinkedNode,
+    /// The key may be absent from the encoded struct
+    pub optional: bool,
+    /// The declared integer key of the member if any
+    pub index: Option<u32>,
+    /// The value of the member when it is not set if any
+    pub default: Option<Literal>,
+}
 impl LinkedKeyVal {
     pub fn new<'a, K: Into<Cow<'a, str>>>(key: K, node: LinkedNode) -> LinkedKeyVal {
-        LinkedKeyVal(key.into().into(), node)
+        LinkedKeyVal {
+            key: key.into().into(),
+            node,
+            optional: false,
+            index: None,
+            default: None,
+        }
+    }
+
+    pub fn optional<'a, K: Into<Cow<'a, str>>>(key: K, node: LinkedNode) -> LinkedKeyVal {
+        LinkedKeyVal::new(key, node).into_optional()
+    }
+
+    /// Encode the member with a declared integer key instead of its position. IE: 1: u8
+    pub fn indexed(self, index: u32) -> LinkedKeyVal {
+        let index = Some(index);
+        LinkedKeyVal { index, ..self }
+    }
+
+    /// The member may be absent, IE: when it is included by an optional group
+    pub fn into_optional(self) -> LinkedKeyVal {
+        LinkedKeyVal {
+            optional: true,
+            ..self
+        }
+    }
+
+    /// The member is initialized with a value other than zero. IE: port: u16 .default 80
+    pub fn with_default(self, value: Literal) -> LinkedKeyVal {
+        let default = Some(value);
+        LinkedKeyVal { default, ..self }
     }
 
     pub fn key(&self) -> &str {
-        &self.0
+        &self.key
     }
 
     pub fn val(&self) -> &LinkedNode {
-        &self.1
+        &self.node
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+
+    pub fn index(&self) -> Option<u32> {
+        self.index
+    }
+
+    pub fn is_indexed(&self) -> bool {
+        self.index.is_some()
+    }
+
+    pub fn default_value(&self) -> Option<&Literal> {
+        self.default.as_ref()
     }
 
     pub fn into_key(self) -> String {
-        self.0
+        self.key
     }
 
     pub fn into_val(self) -> LinkedNode {
-        self.1
+        self.node
     }
 }
 
 /// Helper when creating Maps from Key/Value tuples.
 impl From<(String, LinkedNode)> for LinkedKeyVal {
     fn from(t: (String, LinkedNode)) -> LinkedKeyVal {
-        LinkedKeyVal(t.0, t.1)
+        LinkedKeyVal::new(t.0, t.1)
     }
 }
 
@@ -300,15 +732,138 @@ impl From<(String, LinkedNode)> for LinkedKeyVal {
 #[derive(Clone, Debug, PartialEq)]
 pub struct LinkedArray {
     pub len: usize,
+    pub min: usize,
     pub ty: Box<LinkedNode>,
 }
 impl LinkedArray {
     pub fn new(node: LinkedNode, len: usize) -> LinkedArray {
         LinkedArray {
             ty: Box::new(node),
+            min: len,
             len,
         }
     }
+
+    pub fn bounded(node: LinkedNode, min: usize, len: usize) -> LinkedArray {
+        LinkedArray {
+            ty: Box::new(node),
+            min,
+            len,
+        }
+    }
+
+    /// A fixed array always has len members, otherwise the array tracks how many it holds
+    pub fn is_fixed(&self) -> bool {
+        self.min == self.len
+    }
+}
+
+/// A linked tag, similiar to ivt::Tag, except with a LinkedNode
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkedTag {
+    pub tag: u64,
+    pub ty: Box<LinkedNode>,
+}
+impl LinkedTag {
+    pub fn new(node: LinkedNode, tag: u64) -> LinkedTag {
+        LinkedTag {
+            tag,
+            ty: Box::new(node),
+        }
+    }
+}
+
+/// A primative that only accepts the values that satisfy every one of its constraints
+/// IE: int .size 2 .ge -40 .lt 0
+#[derive(Clone, Debug, PartialEq)]
+pub struct Constrained {
+    pub ty: ConstrainedPrimative,
+    pub constraints: Vec<Constraint>,
+}
+impl Constrained {
+    pub fn new(ty: ConstrainedPrimative, constraints: Vec<Constraint>) -> Constrained {
+        Constrained { ty, constraints }
+    }
+
+    /// The value closest to zero that satisfies every constraint, which a member starts with
+    /// because zero may not be valid. None when no value of the type satisfies the constraints
+    pub fn initial(&self) -> Option<i128> {
+        use ConstrainedPrimative::*;
+        let (min, max) = match self.ty {
+            U8 => (u8::MIN as i128, u8::MAX as i128),
+            U16 => (u16::MIN as i128, u16::MAX as i128),
+            U32 => (u32::MIN as i128, u32::MAX as i128),
+            U64 => (u64::MIN as i128, u64::MAX as i128),
+            I8 => (i8::MIN as i128, i8::MAX as i128),
+            I16 => (i16::MIN as i128, i16::MAX as i128),
+            I32 => (i32::MIN as i128, i32::MAX as i128),
+            I64 => (i64::MIN as i128, i64::MAX as i128),
+            _ => (i128::MIN, i128::MAX),
+        };
+        // The closest value is zero or next to the value of a constraint
+        let values = self.constraints.iter().filter_map(|c| match c.value {
+            Literal::Int(i) => Some(i as i128),
+            Literal::UInt(u) => Some(u as i128),
+            _ => None,
+        });
+        values
+            .flat_map(|n| [n - 1, n, n + 1])
+            .chain([0])
+            .filter(|n| min <= *n && *n <= max)
+            .filter(|n| self.constraints.iter().all(|c| c.accepts(*n)))
+            .min_by_key(|n| (n.unsigned_abs(), *n < 0))
+    }
+}
+
+/// A text string that only accepts the values that match its pattern
+/// IE: tstr .size 32 .regexp "[a-z]+"
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkedRegexp {
+    pub ty: ConstrainedPrimative,
+    pub pattern: String,
+}
+impl LinkedRegexp {
+    pub fn new<'a, P: Into<Cow<'a, str>>>(ty: ConstrainedPrimative, pattern: P) -> LinkedRegexp {
+        LinkedRegexp {
+            ty,
+            pattern: pattern.into().into(),
+        }
+    }
+}
+
+/// A named bit of an unsigned integer, where position 0 is the least significant bit
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bit {
+    pub name: String,
+    pub position: u32,
+}
+impl Bit {
+    pub fn new<'a, N: Into<Cow<'a, str>>>(name: N, position: u32) -> Bit {
+        Bit {
+            name: name.into().into(),
+            position,
+        }
+    }
+}
+
+/// An unsigned integer whose bits are named
+/// IE: uint .size 1 .bits &( ready: 0, fault: 1 )
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkedBits {
+    pub ty: ConstrainedPrimative,
+    pub bits: Vec<Bit>,
+}
+impl LinkedBits {
+    pub fn new(ty: ConstrainedPrimative, bits: Vec<Bit>) -> LinkedBits {
+        LinkedBits { ty, bits }
+    }
+}
+
+/// Similar to Fields, except each member is an alternative named after the type it holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variants {
+    /// The alternatives of a choice
+    pub members: Vec<LinkedKeyVal>,
 }
 
 /// When we have an IVT node, we lookup unresolved types and build a complete tree
@@ -318,14 +873,36 @@ pub enum LinkedNode {
     Literal(Literal),
     /// A primative type fully qualified
     Primative(ConstrainedPrimative),
+    /// A primative type that only accepts a range of values
+    Range(Range),
+    /// A number that only accepts values that satisfy its constraints
+    Constrained(Constrained),
+    /// A text string that only accepts values that match its pattern
+    Regexp(LinkedRegexp),
+    /// An unsigned integer with named bits
+    Bits(LinkedBits),
     /// An array is of a fixed size of a single type
     Array(LinkedArray),
+    /// A primative or named type wrapped in a CBOR tag
+    Tag(LinkedTag),
     /// A group of fields missing context (might be a struct)
     Fields(Fields),
     /// A fully qualified struct with fields (Can only exist at top level)
     Struct(Fields),
+    /// A fully qualified struct encoded as an array of its fields (Can only exist at top level)
+    Record(Fields),
     /// If a struct contains a nested struct, we store flatten instead of nest
     ForeignStruct(String),
+    /// A discriminated choice between types (Can only exist at top level)
+    Choice(Variants),
+    /// If a struct contains a choice, we store flatten instead of nest
+    ForeignChoice(String),
+    /// A choice between literal values (Can only exist at top level)
+    Enumeration(Enumeration),
+    /// If a struct contains an enumeration, we store flatten instead of nest
+    ForeignEnumeration(String),
+    /// The CBOR null, only held by a variant of a choice
+    Nil,
 }
 
 impl From<ConstrainedPrimative> for LinkedNode {
@@ -339,3 +916,52 @@ impl From<LinkedArray> for LinkedNode {
         LinkedNode::Array(value)
     }
 }
+
+impl From<LinkedTag> for LinkedNode {
+    fn from(value: LinkedTag) -> Self {
+        LinkedNode::Tag(value)
+    }
+}
+
+impl From<Constrained> for LinkedNode {
+    fn from(value: Constrained) -> Self {
+        LinkedNode::Constrained(value)
+    }
+}
+
+impl From<LinkedRegexp> for LinkedNode {
+    fn from(value: LinkedRegexp) -> Self {
+        LinkedNode::Regexp(value)
+    }
+}
+
+impl From<LinkedBits> for LinkedNode {
+    fn from(value: LinkedBits) -> Self {
+        LinkedNode::Bits(value)
+    }
+}
+
+impl LinkedNode {
+    /// The node without the constraints of its values, which do not change how the node is
+    /// stored. IE: uint .size 1 .le 100 is a u8, and uint .size 1 .bits status-bits is a u8.
+    /// The pattern of a text string is checked when it is decoded, so it is kept by the printers
+    /// of codecs before the node is stripped
+    pub fn unconstrained(&self) -> Cow<'_, LinkedNode> {
+        match self {
+            LinkedNode::Constrained(constrained) => {
+                Cow::Owned(LinkedNode::Primative(constrained.ty))
+            }
+            LinkedNode::Regexp(regexp) => Cow::Owned(LinkedNode::Primative(regexp.ty)),
+            LinkedNode::Bits(bits) => Cow::Owned(LinkedNode::Primative(bits.ty)),
+            LinkedNode::Array(array) => match array.ty.unconstrained() {
+                Cow::Owned(ty) => Cow::Owned(LinkedArray::bounded(ty, array.min, array.len).into()),
+                Cow::Borrowed(_) => Cow::Borrowed(self),
+            },
+            LinkedNode::Tag(tag) => match tag.ty.unconstrained() {
+                Cow::Owned(ty) => Cow::Owned(LinkedTag::new(ty, tag.tag).into()),
+                Cow::Borrowed(_) => Cow::Borrowed(self),
+            },
+            node => Cow::Borrowed(node),
+        }
+    }
+}
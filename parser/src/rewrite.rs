@@ -0,0 +1,170 @@
+use std::borrow::Cow;
+
+/// The controls that are read after a type that may already hold a control
+const WRAPPED: [&str; 8] = [
+    ".default", ".lt", ".le", ".gt", ".ge", ".ne", ".regexp", ".bits",
+];
+
+/// The CDDL parser does not read every form of CDDL that seedle supports, so the schema is
+/// rewritten into a form that it does read before it is parsed
+///
+/// A tag is read as a control on the type it holds.
+/// IE: #6.1(uint .size 8) is read as (uint .size 8) .tag 1
+///
+/// The type given a default, compared to a value, matched to a pattern or given named bits is
+/// wrapped in parentheses, so that it may hold a control of its own.
+/// IE: tstr .size 16 .default "node" is read as (tstr .size 16) .default "node"
+///
+/// Comments and strings are copied as is, and nothing is moved to another line, so the lines of
+/// the schema are left where they were.
+pub(crate) fn rewrite(cddl: &str) -> Cow<'_, str> {
+    match cddl.contains("#6.") || WRAPPED.iter().any(|op| cddl.contains(op)) {
+        true => Cow::Owned(rewrite_all(cddl)),
+        false => Cow::Borrowed(cddl),
+    }
+}
+
+fn rewrite_all(cddl: &str) -> String {
+    let mut out = String::with_capacity(cddl.len());
+    let mut rest = cddl;
+    while let Some(c) = rest.chars().next() {
+        let skip = match c {
+            ';' => rest.find('\n').unwrap_or(rest.len()),
+            '"' | '\'' => quoted(rest, c),
+            '#' => match tag(rest) {
+                Some((tag, inner, len)) => {
+                    out.push('(');
+                    out.push_str(&rewrite_all(inner));
+                    out.push_str(") .tag ");
+                    out.push_str(tag);
+                    rest = &rest[len..];
+                    continue;
+                }
+                None => 1,
+            },
+            '.' if is_wrapped(rest) && !out.ends_with(is_name) => {
+                wrap_type(&mut out);
+                1
+            }
+            c => c.len_utf8(),
+        };
+        out.push_str(&rest[..skip]);
+        rest = &rest[skip..];
+    }
+    out
+}
+
+/// True when a string starts with a control that is not the end of a longer name, IE: in
+/// tstr .default "x" but not in site.default. The caller checks the text in front of it
+fn is_wrapped(s: &str) -> bool {
+    WRAPPED.iter().any(|op| match s.strip_prefix(op) {
+        Some(rest) => !rest.starts_with(is_name),
+        None => false,
+    })
+}
+
+/// A character that may continue a name
+fn is_name(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || c == '.'
+}
+
+/// Wrap the type at the end of a line in parentheses. IE: port: u16 is written port: (u16) and
+/// [*4 u8 is written [*4 (u8). Strings and comments are skipped, so that a pattern like "a:b"
+/// is not read as the end of the type
+fn wrap_type(out: &mut String) {
+    let end = out.trim_end().len();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in code(&out[..end]).into_iter().rev() {
+        match c {
+            ')' | ']' | '}' => depth += 1,
+            '(' | '[' | '{' if depth > 0 => depth -= 1,
+            '(' | '[' | '{' | ':' | ',' | '=' | '>' | '/' | '\n' if depth == 0 => {
+                start = i + c.len_utf8();
+                break;
+            }
+            _ => {}
+        }
+    }
+    let start = end - occurrence(out[start..end].trim_start()).len();
+    out.insert(end, ')');
+    out.insert(start, '(');
+}
+
+/// The characters of the schema outside of strings and comments, and where they are
+fn code(s: &str) -> Vec<(usize, char)> {
+    let mut code = Vec::new();
+    let mut i = 0;
+    while let Some(c) = s[i..].chars().next() {
+        i += match c {
+            ';' => s[i..].find('\n').unwrap_or(s.len() - i),
+            '"' | '\'' => quoted(&s[i..], c),
+            c => {
+                code.push((i, c));
+                c.len_utf8()
+            }
+        };
+    }
+    code
+}
+
+/// A type without the occurrence in front of it. IE: *4 u8 is u8
+fn occurrence(s: &str) -> &str {
+    let len = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '*' || c == '+' || c == '?'))
+        .unwrap_or(s.len());
+    match s[..len].chars().any(|c| matches!(c, '*' | '+' | '?')) {
+        true => s[len..].trim_start(),
+        false => s,
+    }
+}
+
+/// The length of a string including its quotes
+fn quoted(s: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == quote => return i + 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// The tag number, the type it holds, and the length of a tag at the start of a string
+fn tag(s: &str) -> Option<(&str, &str, usize)> {
+    let rest = s.strip_prefix("#6.")?;
+    let digits = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let tag = &rest[..digits];
+    let body = rest[digits..].strip_prefix('(')?;
+    if tag.is_empty() {
+        return None;
+    }
+    let mut depth = 1;
+    let mut i = 0;
+    while i < body.len() {
+        let c = body[i..].chars().next()?;
+        i += match c {
+            ';' => body[i..].find('\n').unwrap_or(body.len() - i),
+            '"' | '\'' => quoted(&body[i..], c),
+            '(' => {
+                depth += 1;
+                1
+            }
+            ')' if depth == 1 => {
+                let len = "#6.".len() + digits + 1 + i + 1;
+                return Some((tag, &body[..i], len));
+            }
+            ')' => {
+                depth -= 1;
+                1
+            }
+            c => c.len_utf8(),
+        };
+    }
+    None
+}
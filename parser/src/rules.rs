@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+use std::ops::Index;
+
+/// Rules by name, iterated in the order they are declared in the CDDL source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rules<T> {
+    order: Vec<String>,
+    rules: BTreeMap<String, T>,
+}
+
+impl<T> Default for Rules<T> {
+    fn default() -> Self {
+        Rules {
+            order: Vec::new(),
+            rules: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> Rules<T> {
+    pub fn new() -> Rules<T> {
+        Rules::default()
+    }
+
+    /// A rule that is declared again keeps the position it was first declared at
+    pub fn insert(&mut self, key: String, value: T) -> Option<T> {
+        if !self.rules.contains_key(&key) {
+            self.order.push(key.clone());
+        }
+        self.rules.insert(key, value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&T> {
+        self.rules.get(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.rules.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.order.iter()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &T)> {
+        self.order
+            .iter()
+            .filter_map(move |key| self.rules.get_key_value(key))
+    }
+}
+
+impl<T> IntoIterator for Rules<T> {
+    type Item = (String, T);
+    type IntoIter = std::vec::IntoIter<(String, T)>;
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.order
+            .into_iter()
+            .filter_map(|key| self.rules.remove_entry(&key))
+            .collect::<Vec<(String, T)>>()
+            .into_iter()
+    }
+}
+
+impl<T> FromIterator<(String, T)> for Rules<T> {
+    fn from_iter<I: IntoIterator<Item = (String, T)>>(iter: I) -> Self {
+        let mut rules = Rules::new();
+        for (key, value) in iter {
+            rules.insert(key, value);
+        }
+        rules
+    }
+}
+
+impl<T> Index<&str> for Rules<T> {
+    type Output = T;
+    fn index(&self, key: &str) -> &T {
+        &self.rules[key]
+    }
+}
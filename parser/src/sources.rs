@@ -0,0 +1,139 @@
+use super::annotations::{includes, pinned_keys, rule_name};
+use super::{diagnostics::*, error::*, node::LinkedNode, rules::Rules};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// CDDL from several files merged into one schema, so that rules may refer to rules of another
+/// file. The files a file includes are merged before it. IE:
+///
+/// ```cddl
+/// ; @include "common.cddl"
+/// network = { ip: ip-addr }
+/// ```
+#[derive(Debug, Default)]
+pub struct Sources {
+    cddl: String,
+    /// The name of each file and the number of lines merged before it
+    files: Vec<(String, usize)>,
+}
+
+impl Sources {
+    pub fn new() -> Sources {
+        Sources::default()
+    }
+
+    /// Read a file and every file it includes. Relative includes are found from the directory of
+    /// the file that includes them, and a file included more than once is merged once
+    pub fn read<P: AsRef<Path>>(path: P) -> FlattenResult<Sources> {
+        let mut sources = Sources::new();
+        sources.include(path.as_ref(), &mut BTreeSet::new())?;
+        Ok(sources)
+    }
+
+    fn include(&mut self, path: &Path, seen: &mut BTreeSet<PathBuf>) -> FlattenResult<()> {
+        let name = path.display().to_string();
+        let cddl = fs::read_to_string(path).map_err(|e| FlattenError::Include(name.clone(), e))?;
+        let path = path
+            .canonicalize()
+            .map_err(|e| FlattenError::Include(name.clone(), e))?;
+        if seen.insert(path.clone()) {
+            let dir = path.parent().unwrap_or_else(|| Path::new("."));
+            for include in includes(&cddl) {
+                self.include(&dir.join(include), seen)?;
+            }
+            self.push(name, &cddl);
+        }
+        Ok(())
+    }
+
+    /// Merge CDDL after the rules already merged. Include directives are not followed
+    pub fn push<S: Into<String>>(&mut self, name: S, cddl: &str) {
+        self.files
+            .push((name.into(), self.cddl.matches('\n').count()));
+        self.cddl.push_str(cddl);
+        if !cddl.ends_with('\n') {
+            self.cddl.push('\n');
+        }
+    }
+
+    /// The merged CDDL
+    pub fn cddl(&self) -> &str {
+        &self.cddl
+    }
+
+    /// Parse the merged schema, see [`crate::parse`]
+    pub fn parse(&self) -> FlattenResult<Rules<LinkedNode>> {
+        match self.duplicates().into_iter().next() {
+            Some(e) => Err(e),
+            None => crate::parse(&self.cddl).map_err(|e| self.remap(e)),
+        }
+    }
+
+    /// Parse the merged schema and report every problem, see [`crate::parse_diagnostics`]
+    pub fn parse_diagnostics(&self) -> (Rules<LinkedNode>, Diagnostics) {
+        let (rules, diagnostics) = parse_diagnostics(&self.cddl);
+        let errors = diagnostics.errors.into_iter().map(|e| self.remap(e));
+        let warnings = diagnostics.warnings.into_iter().map(|e| self.remap(e));
+        let diagnostics = Diagnostics {
+            errors: self.duplicates().into_iter().chain(errors).collect(),
+            warnings: warnings.collect(),
+        };
+        (rules, diagnostics)
+    }
+
+    /// The keys pinned in every file, see [`crate::pinned_keys`]
+    pub fn pinned_keys(&self) -> FlattenResult<BTreeMap<String, u8>> {
+        pinned_keys(&self.cddl).map_err(|e| self.remap(e))
+    }
+
+    /// A rule declared more than once is an error at each declaration after the first. Rules
+    /// extended with /= are not declared again
+    fn duplicates(&self) -> Vec<FlattenError> {
+        let mut declared: BTreeMap<&str, Location> = BTreeMap::new();
+        let mut errors = Vec::new();
+        let mut offset = 0;
+        for line in self.cddl.split_inclusive('\n') {
+            let name = rule_name(line).filter(|name| {
+                let rest = line.trim_start()[name.len()..].trim_start();
+                !rest.starts_with("/=")
+            });
+            if let Some(name) = name {
+                let indent = line.len() - line.trim_start().len();
+                let at = self.locate(Location::new(&self.cddl, offset + indent));
+                match declared.get(name) {
+                    Some(first) => errors.push(FlattenError::Rule(Box::new(RuleError {
+                        rule: name.to_string(),
+                        location: Some(at),
+                        error: FlattenError::DuplicateRule(first.clone()),
+                    }))),
+                    None => {
+                        declared.insert(name, at);
+                    }
+                }
+            }
+            offset += line.len();
+        }
+        errors
+    }
+
+    /// Point the location of an error into the file it was merged from
+    fn remap(&self, error: FlattenError) -> FlattenError {
+        match error {
+            FlattenError::Rule(mut e) => {
+                e.location = e.location.map(|at| self.locate(at));
+                FlattenError::Rule(e)
+            }
+            error => error,
+        }
+    }
+
+    /// The file is only named when there is more than one
+    fn locate(&self, mut at: Location) -> Location {
+        if let Some((file, before)) = self.files.iter().rev().find(|(_, n)| *n < at.line) {
+            at.file = Some(file.clone()).filter(|_| self.files.len() > 1);
+            at.line -= before;
+        }
+        at
+    }
+}
@@ -148,6 +148,37 @@ fn expect_primative_from_value() {
     );
 }
 
+#[test]
+fn expect_float_round_trip() {
+    let v = Value::Object(liquid_core::object!({
+        "type": "primative",
+        "value": "f16",
+    }));
+    assert_eq!(v, Value::from(ConstrainedPrimative::F16));
+    for float in [
+        ConstrainedPrimative::F16,
+        ConstrainedPrimative::F32,
+        ConstrainedPrimative::F64,
+    ] {
+        let v = Value::from(float);
+        assert_eq!(float, ConstrainedPrimative::try_from(v).unwrap());
+    }
+}
+
+#[test]
+fn expect_unbounded_round_trip() {
+    let v = Value::Object(liquid_core::object!({
+        "type": "primative",
+        "value": "tstr",
+    }));
+    assert_eq!(v, Value::from(ConstrainedPrimative::UnboundedStr));
+    let v = Value::from(ConstrainedPrimative::UnboundedBytes);
+    assert_eq!(
+        ConstrainedPrimative::UnboundedBytes,
+        ConstrainedPrimative::try_from(v).unwrap()
+    );
+}
+
 #[test]
 fn expect_value_from_literal() {
     let expect = Value::Object(liquid_core::object!({
@@ -233,6 +264,8 @@ fn expect_literal_from_value() {
         "value": Value::Array(arr),
         "valueType": "bytes"}));
     assert_eq!(Literal::Bytes(vec![1, 2, 3]), Literal::try_from(v).unwrap());
+    let v = Value::from(Literal::Bytes(vec![0xde, 0xad]));
+    assert_eq!(Literal::Bytes(vec![0xde, 0xad]), Literal::try_from(v).unwrap());
 }
 
 #[test]
@@ -338,3 +371,250 @@ fn expect_keyval_from_value() {
         LinkedKeyVal::try_from(v).unwrap()
     );
 }
+
+#[test]
+fn expect_value_from_choice() {
+    let variants = Variants {
+        members: vec![
+            LinkedKeyVal::new("ping", LinkedNode::ForeignStruct("ping".into())),
+            LinkedKeyVal::new("reset", ConstrainedPrimative::Bool.into()),
+        ],
+    };
+    let values = variants
+        .members
+        .clone()
+        .into_iter()
+        .map(Value::from)
+        .collect::<Vec<Value>>();
+    let expect = Value::Object(liquid_core::object!({
+        "type": "choice",
+        "value": Value::Array(values)
+    }));
+    assert_eq!(expect, Value::from(LinkedNode::Choice(variants)));
+}
+
+#[test]
+fn expect_choice_from_value() {
+    let variants = Variants {
+        members: vec![
+            LinkedKeyVal::new("ping", LinkedNode::ForeignStruct("ping".into())),
+            LinkedKeyVal::new("reset", ConstrainedPrimative::Bool.into()),
+        ],
+    };
+    let v = Value::from(LinkedNode::Choice(variants.clone()));
+    assert_eq!(
+        LinkedNode::Choice(variants),
+        LinkedNode::try_from(v).unwrap()
+    );
+    let v = Value::from(LinkedNode::ForeignChoice("msg".into()));
+    assert_eq!(
+        LinkedNode::ForeignChoice("msg".into()),
+        LinkedNode::try_from(v).unwrap()
+    );
+}
+
+#[test]
+fn expect_value_from_enumeration() {
+    let enumeration = Enumeration {
+        members: vec![Literal::Str("dhcp".into()), Literal::Str("off".into())],
+    };
+    let expect = Value::Object(liquid_core::object!({
+        "type": "enumeration",
+        "value": Value::Array(vec![
+            Value::from(Literal::Str("dhcp".into())),
+            Value::from(Literal::Str("off".into())),
+        ])
+    }));
+    assert_eq!(expect, Value::from(LinkedNode::Enumeration(enumeration)));
+}
+
+#[test]
+fn expect_enumeration_from_value() {
+    let enumeration = Enumeration {
+        members: vec![Literal::Int(-1), Literal::UInt(1)],
+    };
+    let v = Value::from(LinkedNode::Enumeration(enumeration.clone()));
+    assert_eq!(
+        LinkedNode::Enumeration(enumeration),
+        LinkedNode::try_from(v).unwrap()
+    );
+    let v = Value::from(LinkedNode::ForeignEnumeration("mode".into()));
+    assert_eq!(
+        LinkedNode::ForeignEnumeration("mode".into()),
+        LinkedNode::try_from(v).unwrap()
+    );
+}
+
+#[test]
+fn expect_value_from_range() {
+    let range = Range::new(-40, 125).unwrap();
+    let expect = Value::Object(liquid_core::object!({
+        "type": "range",
+        "value": Value::from(ConstrainedPrimative::I8),
+        "min": Value::from(Literal::Int(-40)),
+        "max": Value::from(Literal::UInt(125)),
+    }));
+    assert_eq!(expect, Value::from(LinkedNode::Range(range)));
+}
+
+#[test]
+fn expect_range_from_value() {
+    let range = Range::new(1, 14).unwrap();
+    let v = Value::from(LinkedNode::Range(range.clone()));
+    assert_eq!(LinkedNode::Range(range), LinkedNode::try_from(v).unwrap());
+}
+
+#[test]
+fn expect_optional_keyval_from_value() {
+    let keyval = LinkedKeyVal::optional("foo", ConstrainedPrimative::U8.into());
+    let expect = Value::Object(liquid_core::object!({
+        "type": "keyval",
+        "key":"foo",
+        "value": Value::from(ConstrainedPrimative::U8),
+        "optional": true,
+    }));
+    assert_eq!(expect, Value::from(keyval.clone()));
+    assert_eq!(keyval, LinkedKeyVal::try_from(expect).unwrap());
+}
+
+#[test]
+fn expect_indexed_keyval_from_value() {
+    let keyval = LinkedKeyVal::optional("field_3", ConstrainedPrimative::Bool.into()).indexed(3);
+    let expect = Value::Object(liquid_core::object!({
+        "type": "keyval",
+        "key":"field_3",
+        "value": Value::from(ConstrainedPrimative::Bool),
+        "optional": true,
+        "index": 3,
+    }));
+    assert_eq!(expect, Value::from(keyval.clone()));
+    assert_eq!(keyval, LinkedKeyVal::try_from(expect).unwrap());
+}
+
+#[test]
+fn expect_bounded_array_from_value() {
+    let array = LinkedArray::bounded(ConstrainedPrimative::U8.into(), 1, 8);
+    let expect = Value::Object(liquid_core::object!({
+        "type": "array",
+        "value": Value::from(ConstrainedPrimative::U8),
+        "len": 8,
+        "min": 1,
+    }));
+    assert_eq!(expect, Value::from(array.clone()));
+    assert_eq!(array, LinkedArray::try_from(expect).unwrap());
+}
+
+#[test]
+fn expect_record_from_value() {
+    let record = LinkedNode::Record(Fields {
+        members: vec![
+            LinkedKeyVal::new("x", ConstrainedPrimative::I16.into()),
+            LinkedKeyVal::optional("y", ConstrainedPrimative::I16.into()),
+        ],
+    });
+    let expect = Value::Object(liquid_core::object!({
+        "type": "struct",
+        "record": true,
+        "value": Value::Array(vec![
+            Value::from(LinkedKeyVal::new("x", ConstrainedPrimative::I16.into())),
+            Value::from(LinkedKeyVal::optional("y", ConstrainedPrimative::I16.into())),
+        ])
+    }));
+    assert_eq!(expect, Value::from(record.clone()));
+    assert_eq!(record, LinkedNode::try_from(expect).unwrap());
+}
+
+#[test]
+fn expect_tag_from_value() {
+    let tag = LinkedTag::new(ConstrainedPrimative::U64.into(), 1);
+    let expect = Value::Object(liquid_core::object!({
+        "type": "tag",
+        "tag": 1,
+        "value": Value::from(ConstrainedPrimative::U64),
+    }));
+    assert_eq!(expect, Value::from(LinkedNode::Tag(tag.clone())));
+    assert_eq!(LinkedNode::Tag(tag), LinkedNode::try_from(expect).unwrap());
+}
+
+#[test]
+fn expect_default_keyval_from_value() {
+    let keyval =
+        LinkedKeyVal::new("port", ConstrainedPrimative::U16.into()).with_default(80u16.into());
+    let expect = Value::Object(liquid_core::object!({
+        "type": "keyval",
+        "key":"port",
+        "value": Value::from(ConstrainedPrimative::U16),
+        "default": Value::from(Literal::UInt(80)),
+    }));
+    assert_eq!(expect, Value::from(keyval.clone()));
+    assert_eq!(keyval, LinkedKeyVal::try_from(expect).unwrap());
+}
+
+#[test]
+fn expect_constrained_from_value() {
+    let constrained = Constrained::new(
+        ConstrainedPrimative::I16,
+        vec![
+            Constraint::new(Comparison::Ge, Literal::Int(-40)),
+            Constraint::new(Comparison::Lt, Literal::UInt(0)),
+        ],
+    );
+    let expect = Value::Object(liquid_core::object!({
+        "type": "constrained",
+        "value": Value::from(ConstrainedPrimative::I16),
+        "constraints": [
+            {"type": "constraint", "op": "ge", "value": Value::from(Literal::Int(-40))},
+            {"type": "constraint", "op": "lt", "value": Value::from(Literal::UInt(0))},
+        ],
+    }));
+    assert_eq!(expect, Value::from(LinkedNode::Constrained(constrained.clone())));
+    assert_eq!(
+        LinkedNode::Constrained(constrained),
+        LinkedNode::try_from(expect).unwrap()
+    );
+}
+
+#[test]
+fn expect_regexp_and_bits_from_value() {
+    let regexp = LinkedRegexp::new(ConstrainedPrimative::Str(32), "[a-z]+");
+    let expect = Value::Object(liquid_core::object!({
+        "type": "regexp",
+        "value": Value::from(ConstrainedPrimative::Str(32)),
+        "pattern": "[a-z]+",
+    }));
+    assert_eq!(expect, Value::from(LinkedNode::Regexp(regexp.clone())));
+    assert_eq!(
+        LinkedNode::Regexp(regexp),
+        LinkedNode::try_from(expect).unwrap()
+    );
+    let bits = LinkedBits::new(
+        ConstrainedPrimative::U8,
+        vec![Bit::new("ready", 0), Bit::new("fault", 1)],
+    );
+    let expect = Value::Object(liquid_core::object!({
+        "type": "bits",
+        "value": Value::from(ConstrainedPrimative::U8),
+        "bits": [
+            {"type": "bit", "name": "ready", "position": 0},
+            {"type": "bit", "name": "fault", "position": 1},
+        ],
+    }));
+    assert_eq!(expect, Value::from(LinkedNode::Bits(bits.clone())));
+    assert_eq!(LinkedNode::Bits(bits), LinkedNode::try_from(expect).unwrap());
+}
+
+#[test]
+fn expect_any_and_nil_round_trip() {
+    let v = Value::Object(liquid_core::object!({
+        "type": "primative",
+        "value": "any",
+        "len": 32,
+    }));
+    assert_eq!(v, Value::from(ConstrainedPrimative::Any(32)));
+    assert_eq!(ConstrainedPrimative::Any(32), ConstrainedPrimative::try_from(v).unwrap());
+    let v = Value::from(ConstrainedPrimative::UnboundedAny);
+    assert_eq!(ConstrainedPrimative::UnboundedAny, ConstrainedPrimative::try_from(v).unwrap());
+    let v = Value::Object(liquid_core::object!({"type": "nil"}));
+    assert_eq!(v, Value::from(LinkedNode::Nil));
+    assert_eq!(LinkedNode::Nil, LinkedNode::try_from(v).unwrap());
+}
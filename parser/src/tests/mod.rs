@@ -1,7 +1,7 @@
 #[cfg(feature = "liquid")]
 mod liquid;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use super::{flatten::flatten, iters::*, link::link, *};
 
@@ -50,15 +50,65 @@ fn expect_primatives() {
     );
 }
 
+#[test]
+fn expect_floats() {
+    const TEST_DATA: &'static str = r#"
+        half = float16
+        single = float32
+        double = float64
+        any = float
+        readings = [ 4*4 float32 ]
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert_eq!(nodes["half"], Node::Primative(ConstrainedPrimative::F16));
+    assert_eq!(nodes["single"], Node::Primative(ConstrainedPrimative::F32));
+    assert_eq!(nodes["double"], Node::Primative(ConstrainedPrimative::F64));
+    assert_eq!(nodes["any"], Node::Primative(ConstrainedPrimative::F64));
+    assert_eq!(
+        nodes["readings"],
+        Array::new(ConstrainedPrimative::F32.into(), 4).into()
+    );
+}
+
+#[test]
+fn expect_unbounded_strings() {
+    const TEST_DATA: &'static str = r#"
+        note = { text: tstr, blob: bstr }
+        wrapper = { inner: note }
+        outer = { wrapped: [ 2*2 wrapper ] }
+        either = note / wrapper
+        fixed = { ip: tstr .size 16 }
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    let linked = link(nodes).unwrap();
+    assert_eq!(
+        linked["note"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("text", ConstrainedPrimative::UnboundedStr.into()),
+                LinkedKeyVal::new("blob", ConstrainedPrimative::UnboundedBytes.into()),
+            ]
+        })
+    );
+    let unbounded = unbounded_types(&linked);
+    assert_eq!(
+        unbounded.into_iter().collect::<Vec<String>>(),
+        vec!["either", "note", "outer", "wrapper"]
+    );
+}
+
 #[test]
 fn expect_top_level_literal() {
-    // TODO does not support literal byte array?
     const TEST_DATA: &'static str = r#"
         u = 3
         i = -3
         s = "bar"
         b = false
         c = "A"
+        h = h'deadbeef'
+        h1 = h'01'
+        b64 = b64'3q2-7w'
+        t = 'A'
         d = 3
         f = d
         ff = f
@@ -70,6 +120,12 @@ fn expect_top_level_literal() {
     assert_eq!(nodes["s"], Node::Literal(Literal::Str("bar".into())));
     assert_eq!(nodes["b"], Node::Literal(Literal::Bool(false)));
     assert_eq!(nodes["c"], Node::Literal(Literal::Char('A')));
+    // Byte strings stay bytes, even when they are text or a single byte
+    let magic = Node::Literal(Literal::Bytes(vec![0xde, 0xad, 0xbe, 0xef]));
+    assert_eq!(nodes["h"], magic);
+    assert_eq!(nodes["b64"], magic);
+    assert_eq!(nodes["h1"], Node::Literal(Literal::Bytes(vec![1])));
+    assert_eq!(nodes["t"], Node::Literal(Literal::Bytes(b"A".to_vec())));
     assert_eq!(nodes["f"], Node::Foreign("d".into()));
 
     let linked = link(nodes).unwrap();
@@ -239,6 +295,90 @@ fn expect_array_w_foreign_element() {
     );
 }
 
+#[test]
+fn expect_bounded_array() {
+    const TEST_DATA: &'static str = r#"
+        port = { http: uint .size 2 }
+        ports = [ 0*16 port ]
+        ids = [ 1*8 uint .size 4 ]
+        buf = [ *64 uint .size 1 ]
+        maybe = [ ? uint .size 1 ]
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert_eq!(
+        nodes["ids"],
+        Array::bounded(ConstrainedPrimative::U32.into(), 1, 8).into()
+    );
+    assert_eq!(
+        nodes["buf"],
+        Array::bounded(ConstrainedPrimative::U8.into(), 0, 64).into()
+    );
+    assert_eq!(
+        nodes["maybe"],
+        Array::bounded(ConstrainedPrimative::U8.into(), 0, 1).into()
+    );
+    let linked = link(nodes).unwrap();
+    assert_eq!(
+        linked["ports"],
+        LinkedArray::bounded(LinkedNode::ForeignStruct("port".into()), 0, 16).into()
+    );
+}
+
+#[test]
+fn expect_nested_array() {
+    const TEST_DATA: &'static str = r#"
+        row = [ 4*4 int .size 2 ]
+        matrix = [ 4*4 [ 4*4 int .size 2 ] ]
+        table = [ 0*8 row ]
+        cube = [ 2*2 [ 3*3 [ 4*4 float32 ] ] ]
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert_eq!(
+        nodes["matrix"],
+        Array::new(Array::new(ConstrainedPrimative::I16.into(), 4).into(), 4).into()
+    );
+    let linked = link(nodes).unwrap();
+    let row = LinkedArray::new(ConstrainedPrimative::I16.into(), 4);
+    assert_eq!(
+        linked["matrix"],
+        LinkedArray::new(row.clone().into(), 4).into()
+    );
+    assert_eq!(
+        linked["table"],
+        LinkedArray::bounded(row.into(), 0, 8).into()
+    );
+    let row = LinkedArray::new(ConstrainedPrimative::F32.into(), 4);
+    let plane = LinkedArray::new(row.into(), 3);
+    assert_eq!(linked["cube"], LinkedArray::new(plane.into(), 2).into());
+}
+
+#[test]
+fn expect_nested_bounded_array_is_error() {
+    for cddl in [
+        "xs = [ 4*4 [ 0*4 uint .size 1 ] ]",
+        "ys = [ 0*4 uint .size 1 ]\nxs = [ 2*2 ys ]",
+    ] {
+        assert!(matches!(
+            parse(cddl).map_err(FlattenError::into_kind),
+            Err(FlattenError::InvalidArray)
+        ));
+    }
+}
+
+#[test]
+fn expect_unbounded_array_is_error() {
+    for cddl in [
+        "xs = [ * uint .size 1 ]",
+        "xs = [ + uint .size 1 ]",
+        "xs = [ 2* uint .size 1 ]",
+    ] {
+        assert!(matches!(
+            flatten(cddl).map_err(FlattenError::into_kind),
+            Err(FlattenError::InvalidArraySize)
+        ));
+    }
+}
+
 #[test]
 fn expect_nested_maps() {
     const TEST_DATA: &'static str = r#"
@@ -285,34 +425,230 @@ fn expect_nested_maps() {
             .into()],
         })
     );
+    // Maps declared inline are linked as rules of their own, named after their members
     let linked = link(nodes).unwrap();
     assert_eq!(
         linked["data"],
         LinkedNode::Struct(Fields {
             members: vec![LinkedKeyVal::new(
                 "foo",
-                LinkedNode::Struct(Fields {
-                    members: vec![
-                        LinkedKeyVal::new("a", ConstrainedPrimative::U8.into()).into(),
-                        LinkedKeyVal::new("b", ConstrainedPrimative::Str(32).into()),
-                        LinkedKeyVal::new(
-                            "bar",
-                            LinkedNode::Struct(Fields {
-                                members: vec![
-                                    LinkedKeyVal::new("c", ConstrainedPrimative::U16.into()).into(),
-                                    LinkedKeyVal::new("d", ConstrainedPrimative::Bytes(32).into())
-                                ]
-                            })
-                        )
-                        .into()
-                    ],
-                }),
-            )
-            .into()],
+                LinkedNode::ForeignStruct("data_foo".into())
+            )],
+        })
+    );
+    assert_eq!(
+        linked["data_foo"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("a", ConstrainedPrimative::U8.into()),
+                LinkedKeyVal::new("b", ConstrainedPrimative::Str(32).into()),
+                LinkedKeyVal::new("bar", LinkedNode::ForeignStruct("data_foo_bar".into())),
+            ],
+        })
+    );
+    assert_eq!(
+        linked["data_foo_bar"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("c", ConstrainedPrimative::U16.into()),
+                LinkedKeyVal::new("d", ConstrainedPrimative::Bytes(32).into())
+            ]
+        })
+    );
+    assert_eq!(
+        linked.keys().collect::<Vec<_>>(),
+        ["u8", "u16", "data", "data_foo", "data_foo_bar"]
+    );
+}
+
+#[test]
+fn expect_inline_maps_in_arrays() {
+    const TEST_DATA: &str = r#"
+        thing = {
+            ports: [ 2*2 { http: uint .size 2 } ],
+            ? gateway: [ ip: tstr .size 16, status: #6.1({ up: bool }) ],
+        }
+        readings = [ 0*8 { at: uint .size 4 } ]
+		"#;
+    let linked = parse(TEST_DATA).unwrap();
+    assert_eq!(
+        linked["thing"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new(
+                    "ports",
+                    LinkedArray::new(LinkedNode::ForeignStruct("thing_ports".into()), 2).into()
+                ),
+                LinkedKeyVal::optional(
+                    "gateway",
+                    LinkedNode::ForeignStruct("thing_gateway".into())
+                ),
+            ],
+        })
+    );
+    assert!(matches!(linked["thing_ports"], LinkedNode::Struct(_)));
+    assert!(matches!(linked["thing_gateway"], LinkedNode::Record(_)));
+    assert_eq!(
+        linked["thing_gateway_status"],
+        LinkedNode::Struct(Fields {
+            members: vec![LinkedKeyVal::new("up", ConstrainedPrimative::Bool.into())],
+        })
+    );
+    assert_eq!(
+        linked["readings"],
+        LinkedArray::bounded(LinkedNode::ForeignStruct("readings_item".into()), 0, 8).into()
+    );
+    assert!(matches!(linked["readings_item"], LinkedNode::Struct(_)));
+}
+
+#[test]
+fn expect_inline_map_named_like_rule_is_error() {
+    let err = parse("a = { b: { c: bool } }\na_b = { d: bool }").unwrap_err();
+    assert_eq!(err.rule(), Some("a"));
+    assert!(matches!(err.kind(), FlattenError::HoistedRule(key) if key == "a_b"));
+}
+
+#[test]
+fn expect_choice() {
+    const TEST_DATA: &str = r#"
+        u8 = uint .size 1
+        ping = { seq: u8 }
+        pong = { seq: u8 }
+        reset = bool
+        msg = ping / pong / reset
+        data = { msg: msg }
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert_eq!(
+        nodes["msg"],
+        Node::Choice(Choice {
+            members: vec![
+                Node::Foreign("ping".into()),
+                Node::Foreign("pong".into()),
+                Node::Foreign("reset".into()),
+            ]
+        })
+    );
+    let linked = link(nodes).unwrap();
+    assert_eq!(
+        linked["msg"],
+        LinkedNode::Choice(Variants {
+            members: vec![
+                LinkedKeyVal::new("ping", LinkedNode::ForeignStruct("ping".into())),
+                LinkedKeyVal::new("pong", LinkedNode::ForeignStruct("pong".into())),
+                LinkedKeyVal::new("reset", ConstrainedPrimative::Bool.into()),
+            ]
+        })
+    );
+    assert_eq!(
+        linked["data"],
+        LinkedNode::Struct(Fields {
+            members: vec![LinkedKeyVal::new(
+                "msg",
+                LinkedNode::ForeignChoice("msg".into())
+            )]
         })
     );
 }
 
+#[test]
+fn expect_choice_of_unnamed_types_is_error() {
+    const TEST_DATA: &str = r#"
+        msg = bool / tstr .size 4
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert!(matches!(
+        link(nodes).map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidChoiceMember)
+    ));
+}
+
+#[test]
+fn expect_enumeration() {
+    const TEST_DATA: &str = r#"
+        mode = "dhcp" / "static" / "off"
+        level = -1 / 0 / 1
+        dhcp = "dhcp"
+        off = "off"
+        named = dhcp / off
+        data = { mode: mode }
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert_eq!(
+        nodes["mode"],
+        Node::Enumeration(Enumeration {
+            members: vec![
+                Literal::Str("dhcp".into()),
+                Literal::Str("static".into()),
+                Literal::Str("off".into()),
+            ]
+        })
+    );
+    assert_eq!(
+        nodes["level"],
+        Node::Enumeration(Enumeration {
+            members: vec![Literal::Int(-1), Literal::UInt(0), Literal::UInt(1)]
+        })
+    );
+    let linked = link(nodes).unwrap();
+    assert_eq!(
+        linked["named"],
+        LinkedNode::Enumeration(Enumeration {
+            members: vec![Literal::Str("dhcp".into()), Literal::Str("off".into())]
+        })
+    );
+    assert_eq!(
+        linked["data"],
+        LinkedNode::Struct(Fields {
+            members: vec![LinkedKeyVal::new(
+                "mode",
+                LinkedNode::ForeignEnumeration("mode".into())
+            )]
+        })
+    );
+}
+
+#[test]
+fn expect_mixed_enumeration_is_error() {
+    assert!(matches!(
+        flatten(r#"mode = "dhcp" / 3"#).map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidEnumeration)
+    ));
+    assert!(matches!(
+        flatten("big = -1 / 9223372036854775808").map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidEnumeration)
+    ));
+    assert!(flatten("big = 1 / 18446744073709551615").is_ok());
+}
+
+#[test]
+fn expect_duplicate_enumeration_is_error() {
+    for (cddl, value) in [
+        ("m = \"a\" / \"a\"", "a"),
+        ("m = 1 / 2 / 1", "1"),
+        ("a = \"x\"\nb = \"x\"\nm = a / b", "x"),
+    ] {
+        let err = parse(cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("m"));
+        assert!(matches!(err.kind(), FlattenError::DuplicateEnumerationValue(v) if v == value));
+    }
+    for (cddl, value, other) in [
+        ("m = \"dhcp-v4\" / \"dhcp_v4\"", "dhcp_v4", "dhcp-v4"),
+        ("m = \"static\" / \"Static\"", "Static", "static"),
+    ] {
+        let err = parse(cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("m"));
+        assert!(matches!(
+            err.kind(),
+            FlattenError::EnumerationNameClash(v, o) if v == value && o == other
+        ));
+    }
+    // The clashing value is pointed at
+    let err = parse("u8 = uint .size 1\nm = \"dhcp-v4\" / \"dhcp_v4\"\n").unwrap_err();
+    assert_eq!(err.location().map(|at| (at.line, at.column)), Some((2, 18)));
+    assert!(parse("m = \"dhcp-v4\" / \"dhcp-v6\" / \"\"").is_ok());
+}
+
 const ITER_TEST_DATA: &'static str = r#"
         p0 = bool
         p1 = bool
@@ -448,3 +784,1217 @@ fn expect_fold_group_borrowed() {
     assert_eq!("l1", ungrouped[1].0);
     assert_eq!("l2", ungrouped[2].0);
 }
+
+#[test]
+fn expect_range() {
+    const TEST_DATA: &str = r#"
+        temp = -40..125
+        channel = 1..14
+        percent = 0...101
+        counter = 0..70000
+        data = { temp: temp, channels: [ 2*2 channel ] }
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert_eq!(
+        nodes["temp"],
+        Node::Range(Range {
+            ty: ConstrainedPrimative::I8,
+            min: Literal::Int(-40),
+            max: Literal::UInt(125),
+        })
+    );
+    assert_eq!(
+        nodes["channel"],
+        Node::Range(Range {
+            ty: ConstrainedPrimative::U8,
+            min: Literal::UInt(1),
+            max: Literal::UInt(14),
+        })
+    );
+    assert_eq!(
+        nodes["percent"],
+        Node::Range(Range {
+            ty: ConstrainedPrimative::U8,
+            min: Literal::UInt(0),
+            max: Literal::UInt(100),
+        })
+    );
+    assert_eq!(
+        nodes["counter"],
+        Node::Range(Range {
+            ty: ConstrainedPrimative::U32,
+            min: Literal::UInt(0),
+            max: Literal::UInt(70000),
+        })
+    );
+    let linked = link(nodes).unwrap();
+    assert_eq!(
+        linked["data"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("temp", LinkedNode::Range(Range::new(-40, 125).unwrap())),
+                LinkedKeyVal::new(
+                    "channels",
+                    LinkedArray::new(LinkedNode::Range(Range::new(1, 14).unwrap()), 2).into()
+                ),
+            ]
+        })
+    );
+}
+
+#[test]
+fn expect_invalid_range_is_error() {
+    assert!(matches!(
+        flatten("empty = 10..1").map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidRange)
+    ));
+    assert!(matches!(
+        flatten("floats = 0.5..1.5").map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidRange)
+    ));
+}
+
+#[test]
+fn expect_optional_members() {
+    const TEST_DATA: &str = r#"
+        ip-addr = tstr .size 16
+        network = {
+            ip: ip-addr,
+            ? gateway: ip-addr,
+            0*1 port: uint .size 2,
+        }
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert_eq!(
+        nodes["network"],
+        Node::Map(Group {
+            members: vec![
+                KeyVal::new("ip", Node::Foreign("ip-addr".into())).into(),
+                KeyVal::optional("gateway", Node::Foreign("ip-addr".into())).into(),
+                KeyVal::optional("port", ConstrainedPrimative::U16.into()).into(),
+            ]
+        })
+    );
+    let linked = link(nodes).unwrap();
+    assert_eq!(
+        linked["network"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("ip", ConstrainedPrimative::Str(16).into()),
+                LinkedKeyVal::optional("gateway", ConstrainedPrimative::Str(16).into()),
+                LinkedKeyVal::optional("port", ConstrainedPrimative::U16.into()),
+            ]
+        })
+    );
+}
+
+#[test]
+fn expect_integer_keys() {
+    const TEST_DATA: &str = r#"
+        reading = {
+            "unit": uint .size 2,
+            1: uint .size 1,
+            2: tstr .size 16,
+            ? 3 => bool,
+        }
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert_eq!(
+        nodes["reading"],
+        Node::Map(Group {
+            members: vec![
+                KeyVal::new("unit", ConstrainedPrimative::U16.into()).into(),
+                KeyVal::new("field_1", ConstrainedPrimative::U8.into())
+                    .indexed(1)
+                    .into(),
+                KeyVal::new("field_2", ConstrainedPrimative::Str(16).into())
+                    .indexed(2)
+                    .into(),
+                KeyVal::optional("field_3", ConstrainedPrimative::Bool.into())
+                    .indexed(3)
+                    .into(),
+            ]
+        })
+    );
+    let linked = link(nodes).unwrap();
+    assert_eq!(
+        linked["reading"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("unit", ConstrainedPrimative::U16.into()),
+                LinkedKeyVal::new("field_1", ConstrainedPrimative::U8.into()).indexed(1),
+                LinkedKeyVal::new("field_2", ConstrainedPrimative::Str(16).into()).indexed(2),
+                LinkedKeyVal::optional("field_3", ConstrainedPrimative::Bool.into()).indexed(3),
+            ]
+        })
+    );
+}
+
+#[test]
+fn expect_integer_keys_named_by_comments() {
+    const TEST_DATA: &str = r#"
+        reading = {
+            1: uint .size 2, ; celsius
+            2: uint .size 4 ; at
+            3: bool, ; not a name
+            "unit": bool, ; kelvin
+            ? 4 => bool, ; unit
+        }
+        ; label
+        other = { 1: tstr .regexp "a;b", ; label
+            2: bool }
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert_eq!(
+        nodes["reading"],
+        Node::Map(Group {
+            members: vec![
+                KeyVal::new("celsius", ConstrainedPrimative::U16.into())
+                    .indexed(1)
+                    .into(),
+                KeyVal::new("at", ConstrainedPrimative::U32.into())
+                    .indexed(2)
+                    .into(),
+                KeyVal::new("field_3", ConstrainedPrimative::Bool.into())
+                    .indexed(3)
+                    .into(),
+                KeyVal::new("unit", ConstrainedPrimative::Bool.into()).into(),
+                KeyVal::optional("field_4", ConstrainedPrimative::Bool.into())
+                    .indexed(4)
+                    .into(),
+            ]
+        })
+    );
+    let other = match &nodes["other"] {
+        Node::Map(group) => group.members.clone(),
+        node => panic!("expected a map, found {:?}", node),
+    };
+    let keys = other.iter().map(|member| match member {
+        Node::KeyVal(KeyVal(key, ..)) => key.as_str(),
+        _ => "",
+    });
+    assert_eq!(keys.collect::<Vec<&str>>(), ["label", "field_2"]);
+}
+
+#[test]
+fn expect_invalid_integer_keys_is_error() {
+    assert!(matches!(
+        flatten("reading = { -1: bool }").map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidMemberKey)
+    ));
+    assert!(matches!(
+        link(flatten("reading = { 1: bool, on: bool }").unwrap()).map_err(FlattenError::into_kind),
+        Err(FlattenError::DuplicateMemberKey(1, key, 0)) if key == "on"
+    ));
+}
+
+#[test]
+fn expect_records() {
+    const TEST_DATA: &str = r#"
+        vector = [ x: int .size 2, y: int .size 2, ? z: int .size 2 ]
+        sample = [ at: uint .size 4, point: vector ]
+		"#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert_eq!(
+        nodes["vector"],
+        Node::Record(Group {
+            members: vec![
+                KeyVal::new("x", ConstrainedPrimative::I16.into()).into(),
+                KeyVal::new("y", ConstrainedPrimative::I16.into()).into(),
+                KeyVal::optional("z", ConstrainedPrimative::I16.into()).into(),
+            ]
+        })
+    );
+    let linked = link(nodes).unwrap();
+    assert_eq!(
+        linked["sample"],
+        LinkedNode::Record(Fields {
+            members: vec![
+                LinkedKeyVal::new("at", ConstrainedPrimative::U32.into()),
+                LinkedKeyVal::new("point", LinkedNode::ForeignStruct("vector".into())),
+            ]
+        })
+    );
+}
+
+#[test]
+fn expect_invalid_record_is_error() {
+    assert!(matches!(
+        link(flatten("vector = [ ? x: bool, y: bool ]").unwrap()).map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidRecordMember)
+    ));
+    assert!(matches!(
+        link(flatten("vector = [ 1: bool, 2: bool ]").unwrap()).map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidRecordMember)
+    ));
+    assert!(matches!(
+        link(flatten("vector = [ bool, bool ]").unwrap()).map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidGroupMissingKey)
+    ));
+}
+
+#[test]
+fn expect_error_location() {
+    const TEST_DATA: &str = "u8 = uint .size 1\nnetwork = {\n    ip: ip-addr,\n}\n";
+    let err = parse(TEST_DATA).unwrap_err();
+    assert!(matches!(err.kind(), FlattenError::ForeignKey(_)));
+    assert_eq!(err.rule(), Some("network"));
+    assert_eq!(
+        err.location(),
+        Some(&Location {
+            file: None,
+            line: 3,
+            column: 9,
+            snippet: "    ip: ip-addr,".into()
+        })
+    );
+    let expect = [
+        "foreign key not defined [ip-addr]",
+        " --> rule [network] at 3:9",
+        "  |",
+        "3 |     ip: ip-addr,",
+        "  |         ^",
+    ];
+    assert_eq!(err.to_string(), expect.join("\n"));
+    let err = parse("u8 = uint .size 1\n  xs = [ * u8 ]\n").unwrap_err();
+    assert!(matches!(err.kind(), FlattenError::InvalidArraySize));
+    assert_eq!(err.location().map(|at| (at.line, at.column)), Some((2, 3)));
+    // The error of the example of RuleError
+    let cddl = format!(
+        "{}reading = {{ at: bool, value: temperature }}\n",
+        "\n".repeat(11)
+    );
+    let expect = [
+        "foreign key not defined [temperature]",
+        "  --> rule [reading] at 12:30",
+        "   |",
+        "12 | reading = { at: bool, value: temperature }",
+        "   |                              ^",
+    ];
+    assert_eq!(parse(&cddl).unwrap_err().to_string(), expect.join("\n"));
+}
+
+#[test]
+fn expect_member_error_location() {
+    const U8: &str = "u8 = uint .size 1\n";
+    for (cddl, at) in [
+        // The second key, not the first
+        ("m = { 1: u8, 1: u8 }", (2, 14)),
+        // The member keyed by its position
+        ("m = { 1: u8, on: u8 }", (2, 14)),
+        // The type, not the member named like it
+        ("m = { c: c }", (2, 10)),
+        ("m = { \"c\" => bool, d: c }", (2, 23)),
+        ("m = { a: bool, u8 }", (2, 16)),
+        ("m = { u8: bool, ~u8 }", (2, 18)),
+    ] {
+        let err = parse(&format!("{}{}", U8, cddl)).unwrap_err();
+        assert_eq!(err.rule(), Some("m"));
+        assert_eq!(err.location().map(|at| (at.line, at.column)), Some(at));
+    }
+    let err = parse("m = { 1: bool, 1: bool }").unwrap_err();
+    assert!(matches!(err.kind(), FlattenError::DuplicateMemberKey(1, key, 1) if key == "1"));
+    assert!(err.to_string().starts_with("duplicate member key [1]"));
+    let err = parse("m = { a: bool, b }\nb = bool").unwrap_err();
+    assert!(matches!(err.kind(), FlattenError::InvalidGroupMember(key) if key == "b"));
+    let err = parse("m = { a: bool, ~b }\nb = bool").unwrap_err();
+    assert!(matches!(err.kind(), FlattenError::InvalidUnwrap(key) if key == "b"));
+}
+
+#[test]
+fn expect_parser_error_location() {
+    const TEST_DATA: &str = "u8 = uint .size 1 .default 0\nnetwork = {\n    ip: u8 }},\n";
+    let err = parse(TEST_DATA).unwrap_err();
+    assert!(matches!(err.kind(), FlattenError::Parser(_)));
+    assert_eq!(err.rule(), Some("network"));
+    let expect = [
+        "failed to parse [},] (Unparseable)",
+        " --> rule [network] at 3:13",
+        "  |",
+        "3 |     ip: u8 }},",
+        "  |             ^",
+    ];
+    assert_eq!(err.to_string(), expect.join("\n"));
+    let (_, diagnostics) = parse_diagnostics(TEST_DATA);
+    assert_eq!(diagnostics.errors[0].location(), err.location());
+    let err = parse("a = { b: \"\\q\" }\n").unwrap_err();
+    assert_eq!(err.location().map(|at| (at.line, at.column)), Some((1, 5)));
+}
+
+#[test]
+fn expect_all_diagnostics() {
+    const TEST_DATA: &str = r#"
+        reading = { at: u32, value: [ * u8 ] }
+        sample = [ at: u32, reading: reading ]
+        config = { mode: mode }
+        extra = ( debug: bool )
+        u32 = uint .size 4
+        u8 = uint .size 1
+		"#;
+    let (nodes, diagnostics) = parse_diagnostics(TEST_DATA);
+    assert!(!diagnostics.is_ok());
+    let errors = diagnostics
+        .errors
+        .iter()
+        .map(|e| (e.rule(), e.location().map(|at| at.line)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        vec![(Some("reading"), Some(2)), (Some("config"), Some(4))]
+    );
+    assert!(matches!(
+        diagnostics.errors[0].kind(),
+        FlattenError::InvalidArraySize
+    ));
+    assert!(matches!(
+        diagnostics.errors[1].kind(),
+        FlattenError::ForeignKey(_)
+    ));
+    assert_eq!(diagnostics.warnings.len(), 1);
+    assert_eq!(diagnostics.warnings[0].rule(), Some("extra"));
+    assert!(nodes.contains_key("u32"));
+    assert!(!nodes.contains_key("sample"));
+}
+
+#[test]
+fn expect_cycle_is_error() {
+    let err = link(flatten("a = b\nb = c\nc = a").unwrap()).unwrap_err();
+    assert_eq!(err.rule(), Some("a"));
+    assert!(matches!(
+        err.into_kind(),
+        FlattenError::Cycle(path) if path == ["a", "b", "c", "a"]
+    ));
+    assert!(matches!(
+        link(flatten("g = ( x: bool, g )").unwrap()).map_err(FlattenError::into_kind),
+        Err(FlattenError::Cycle(path)) if path == ["g", "g"]
+    ));
+    assert!(matches!(
+        link(flatten("cmd = cmd / bool").unwrap()).map_err(FlattenError::into_kind),
+        Err(FlattenError::Cycle(_))
+    ));
+}
+
+#[test]
+fn expect_cycle_diagnostics() {
+    let (nodes, diagnostics) = parse_diagnostics("a = b\nb = a\nc = a\n");
+    assert!(nodes.is_empty());
+    let errors = diagnostics
+        .errors
+        .iter()
+        .map(|e| (e.rule(), e.location().map(|at| at.line)))
+        .collect::<Vec<_>>();
+    assert_eq!(errors, vec![(Some("a"), Some(1)), (Some("b"), Some(2))]);
+    assert!(matches!(
+        diagnostics.errors[0].kind(),
+        FlattenError::Cycle(path) if *path == ["a", "b", "a"]
+    ));
+    assert!(matches!(
+        diagnostics.errors[1].kind(),
+        FlattenError::Cycle(path) if *path == ["b", "a", "b"]
+    ));
+}
+
+#[test]
+fn expect_expansion_diagnostics() {
+    const TEST_DATA: &str = r#"
+        message<T> = { id: T }
+        a = { m: message<u8, u8> }
+        b = { c: { d: bool } }
+        b_c = { e: bool }
+        f = { g: message<u8> }
+        h = { i: a }
+        u8 = uint .size 1
+		"#;
+    let (nodes, diagnostics) = parse_diagnostics(TEST_DATA);
+    let errors = diagnostics
+        .errors
+        .iter()
+        .map(|e| (e.rule(), e.location().map(|at| at.line)))
+        .collect::<Vec<_>>();
+    assert_eq!(errors, vec![(Some("a"), Some(3)), (Some("b"), Some(4))]);
+    assert!(matches!(
+        diagnostics.errors[0].kind(),
+        FlattenError::InvalidGenericArgs(name) if name == "message"
+    ));
+    assert!(matches!(
+        diagnostics.errors[1].kind(),
+        FlattenError::HoistedRule(key) if key == "b_c"
+    ));
+    // The rules that expanded are still linked, and references to those that did not are not
+    // reported again
+    assert!(nodes.contains_key("b_c"));
+    assert!(nodes.contains_key("f"));
+    assert!(nodes.contains_key("message_u8"));
+    assert!(!nodes.contains_key("a"));
+    assert!(!nodes.contains_key("b"));
+    assert!(!nodes.contains_key("h"));
+}
+
+#[test]
+fn expect_recursive_struct_reference() {
+    const TEST_DATA: &str = r#"
+        tree = leaf / branch
+        leaf = { value: uint .size 1 }
+        branch = { left: tree, right: tree, ? parent: branch }
+		"#;
+    let linked = link(flatten(TEST_DATA).unwrap()).unwrap();
+    assert_eq!(
+        linked["branch"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("left", LinkedNode::ForeignChoice("tree".into())),
+                LinkedKeyVal::new("right", LinkedNode::ForeignChoice("tree".into())),
+                LinkedKeyVal::optional("parent", LinkedNode::ForeignStruct("branch".into())),
+            ]
+        })
+    );
+    // Each member that leads back to its struct is held through a pointer
+    let recursive = recursive_members(&linked);
+    assert_eq!(recursive.keys().collect::<Vec<&String>>(), vec!["branch"]);
+    assert_eq!(
+        recursive["branch"].iter().collect::<Vec<&String>>(),
+        vec!["left", "parent", "right"]
+    );
+    let unbounded = unbounded_types(&linked);
+    assert_eq!(
+        unbounded.into_iter().collect::<Vec<String>>(),
+        vec!["branch", "tree"]
+    );
+}
+
+#[test]
+fn expect_declaration_order() {
+    const TEST_DATA: &str = r#"
+        u8 = uint .size 1
+        zone = { id: u8 }
+        alarm = { zone: zone }
+        "#;
+    let nodes = parse(TEST_DATA).unwrap();
+    let keys = nodes.keys().map(String::as_str).collect::<Vec<&str>>();
+    assert_eq!(keys, vec!["u8", "zone", "alarm"]);
+}
+
+#[test]
+fn expect_pinned_keys() {
+    const TEST_DATA: &str = r#"
+        u8 = uint .size 1
+        ; @key 4
+        zone = { id: u8 }
+        port = {
+            ; @key 5 is ignored when the comment is not directly above a rule
+            id: u8
+        }
+
+        ; Alarms are raised by a zone
+        ; @key 0
+        alarm = { zone: zone }
+        "#;
+    let keys = pinned_keys(TEST_DATA).unwrap();
+    let expect = BTreeMap::from([("zone".to_string(), 4), ("alarm".to_string(), 0)]);
+    assert_eq!(keys, expect);
+}
+
+#[test]
+fn expect_pinned_key_errors() {
+    assert!(matches!(
+        pinned_keys("; @key 256\nzone = { id: uint }").map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidKeyAnnotation(_))
+    ));
+    let err =
+        pinned_keys("; @key 1\nzone = { id: uint }\n; @key 1\nalarm = { id: uint }").unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        FlattenError::DuplicateKeyAnnotation(1)
+    ));
+    assert_eq!(err.rule(), Some("alarm"));
+    assert_eq!(err.location().map(|at| at.line), Some(4));
+}
+
+#[test]
+fn expect_included_files() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/tests/include/product.cddl"
+    );
+    let nodes = Sources::read(path).unwrap().parse().unwrap();
+    let keys = nodes.keys().map(String::as_str).collect::<Vec<&str>>();
+    assert_eq!(keys, vec!["u8", "ip-addr", "network", "device"]);
+    assert!(matches!(
+        Sources::read("missing.cddl"),
+        Err(FlattenError::Include(..))
+    ));
+}
+
+#[test]
+fn expect_sources_link_across_files() {
+    let mut sources = Sources::new();
+    sources.push("common.cddl", "u8 = uint .size 1");
+    sources.push(
+        "product.cddl",
+        "device = {\n    id: u8,\n    ip: ip-addr,\n}",
+    );
+    let err = sources.parse().unwrap_err();
+    assert!(matches!(err.kind(), FlattenError::ForeignKey(_)));
+    let at = err.location().unwrap();
+    assert_eq!(at.file.as_deref(), Some("product.cddl"));
+    assert_eq!((at.line, at.column), (3, 9));
+
+    sources.push("network.cddl", "ip-addr = tstr .size 16");
+    let nodes = sources.parse().unwrap();
+    assert!(matches!(
+        nodes["device"],
+        LinkedNode::Struct(Fields { ref members }) if members.len() == 2
+    ));
+}
+
+#[test]
+fn expect_duplicate_rules_across_files() {
+    let mut sources = Sources::new();
+    sources.push("common.cddl", "u8 = uint .size 1\nu16 = uint .size 2");
+    sources.push("product.cddl", "device = { id: u8 }\n  u8 = uint .size 1");
+    let (_, diagnostics) = sources.parse_diagnostics();
+    let err = &diagnostics.errors[0];
+    assert_eq!(err.rule(), Some("u8"));
+    assert_eq!(
+        err.location().map(ToString::to_string).as_deref(),
+        Some("product.cddl:2:3")
+    );
+    match err.kind() {
+        FlattenError::DuplicateRule(first) => assert_eq!(first.to_string(), "common.cddl:1:1"),
+        kind => panic!("unexpected error {:?}", kind),
+    }
+    assert!(matches!(
+        sources.parse().map_err(FlattenError::into_kind),
+        Err(FlattenError::DuplicateRule(_))
+    ));
+}
+
+#[test]
+fn expect_group_references() {
+    const TEST_DATA: &str = r#"
+        u8 = uint .size 1
+        stamp = ( at: u8 )
+        header = ( id: u8, stamp, ? seq: u8 )
+        footer = ( crc: u8 )
+        frame = { header, ( len: u8 ), body: u8, ? footer }
+        sample = [ header ]
+        point = [ x: u8, ? footer ]
+        "#;
+    let nodes = link(flatten(TEST_DATA).unwrap()).unwrap();
+    let expect = vec![
+        LinkedKeyVal::new("id", LinkedNode::Primative(ConstrainedPrimative::U8)),
+        LinkedKeyVal::new("at", LinkedNode::Primative(ConstrainedPrimative::U8)),
+        LinkedKeyVal::optional("seq", LinkedNode::Primative(ConstrainedPrimative::U8)),
+        LinkedKeyVal::new("len", LinkedNode::Primative(ConstrainedPrimative::U8)),
+        LinkedKeyVal::new("body", LinkedNode::Primative(ConstrainedPrimative::U8)),
+        LinkedKeyVal::optional("crc", LinkedNode::Primative(ConstrainedPrimative::U8)),
+    ];
+    assert_eq!(
+        nodes["frame"],
+        LinkedNode::Struct(Fields {
+            members: expect.clone()
+        })
+    );
+    assert_eq!(
+        nodes["sample"],
+        LinkedNode::Record(Fields {
+            members: expect[..3].to_vec()
+        })
+    );
+    assert_eq!(
+        nodes["point"],
+        LinkedNode::Record(Fields {
+            members: vec![
+                LinkedKeyVal::new("x", LinkedNode::Primative(ConstrainedPrimative::U8)),
+                LinkedKeyVal::optional("crc", LinkedNode::Primative(ConstrainedPrimative::U8)),
+            ]
+        })
+    );
+    assert!(matches!(
+        link(
+            flatten(
+                "u8 = uint .size 1
+footer = ( crc: u8 )
+point = [ ? footer, x: u8 ]"
+            )
+            .unwrap()
+        )
+        .map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidRecordMember)
+    ));
+}
+
+#[test]
+fn expect_generics() {
+    const TEST_DATA: &str = r#"
+        u8 = uint .size 1
+        u32 = uint .size 4
+        status = { code: u8 }
+        header<K> = ( kind: K )
+        message<T> = { header<u8>, id: u32, body: T }
+        pair<A, B> = [ first: A, second: B ]
+        update = { status: message<status>, pairs: [ 2*2 pair<u8, message<status>> ] }
+        flags = { ~status, on: bool }
+        "#;
+    let nodes = link(flatten(TEST_DATA).unwrap()).unwrap();
+    let keys = nodes.keys().map(String::as_str).collect::<Vec<&str>>();
+    assert_eq!(
+        keys,
+        vec![
+            "u8",
+            "u32",
+            "status",
+            "update",
+            "flags",
+            "message_status",
+            "pair_u8_message_status",
+            "header_u8"
+        ]
+    );
+    assert_eq!(
+        nodes["message_status"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("kind", LinkedNode::Primative(ConstrainedPrimative::U8)),
+                LinkedKeyVal::new("id", LinkedNode::Primative(ConstrainedPrimative::U32)),
+                LinkedKeyVal::new("body", LinkedNode::ForeignStruct("status".into())),
+            ]
+        })
+    );
+    assert_eq!(
+        nodes["pair_u8_message_status"],
+        LinkedNode::Record(Fields {
+            members: vec![
+                LinkedKeyVal::new("first", LinkedNode::Primative(ConstrainedPrimative::U8)),
+                LinkedKeyVal::new("second", LinkedNode::ForeignStruct("message_status".into())),
+            ]
+        })
+    );
+    assert_eq!(
+        nodes["flags"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("code", LinkedNode::Primative(ConstrainedPrimative::U8)),
+                LinkedKeyVal::new("on", LinkedNode::Primative(ConstrainedPrimative::Bool)),
+            ]
+        })
+    );
+}
+
+#[test]
+fn expect_generic_errors() {
+    const TEST_DATA: &str = r#"
+        u8 = uint .size 1
+        message<T> = { body: T }
+        "#;
+    for (rule, expect) in [
+        ("a = { m: message<u8, u8> }", "message"),
+        ("a = { m: message }", "message"),
+        ("a = { m: u8<u8> }", "u8"),
+    ] {
+        let cddl = format!("{}{}", TEST_DATA, rule);
+        let err = parse(&cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("a"));
+        assert!(matches!(err.kind(), FlattenError::InvalidGenericArgs(name) if name == expect));
+    }
+    let cddl = format!("{}a = {{ m: message<{{ x: u8 }}> }}", TEST_DATA);
+    assert!(matches!(
+        parse(&cddl).map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidGenericArg)
+    ));
+    let cddl = format!(
+        "{}nest<T> = {{ next: nest<message<T>> }}\na = nest<u8>",
+        TEST_DATA
+    );
+    assert!(matches!(
+        parse(&cddl).map_err(FlattenError::into_kind),
+        Err(FlattenError::Cycle(_))
+    ));
+    let cddl = format!(
+        "{}a = {{ m: message<u8> }}\nmessage_u8 = {{ id: u8 }}",
+        TEST_DATA
+    );
+    let err = parse(&cddl).unwrap_err();
+    assert_eq!(err.rule(), Some("a"));
+    assert!(matches!(
+        err.kind(),
+        FlattenError::InstancedRule(generic, key) if generic == "message" && key == "message_u8"
+    ));
+    for rule in [
+        "a = { f: pair<x_y, z>, g: pair<x, y_z> }",
+        "b = { f: pair<x_y, z> }\na = { g: pair<x, y_z> }",
+    ] {
+        let cddl = format!(
+            "{}x = u8\ny_z = u8\nx_y = u8\nz = u8\npair<T, U> = [ first: T, second: U ]\n{}",
+            TEST_DATA, rule
+        );
+        let err = parse(&cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("a"));
+        assert!(matches!(
+            err.kind(),
+            FlattenError::InstanceClash(generic, key) if generic == "pair" && key == "pair_x_y_z"
+        ));
+    }
+}
+
+#[test]
+fn expect_tags() {
+    const TEST_DATA: &str = r##"
+        u64 = uint .size 8
+        mode = "on" / "off"
+        epoch = #6.1(u64)
+        event = {
+            created: epoch,   ; #6.2(ignored)
+            id: #6.37(bytes .size 16),
+            ? mode: #6.1000(mode),
+            note: "#6.3(u8)",
+        }
+        "##;
+    let nodes = parse(TEST_DATA).unwrap();
+    let epoch = LinkedNode::Tag(LinkedTag::new(ConstrainedPrimative::U64.into(), 1));
+    assert_eq!(nodes["epoch"], epoch);
+    assert_eq!(
+        nodes["event"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("created", epoch),
+                LinkedKeyVal::new(
+                    "id",
+                    LinkedTag::new(ConstrainedPrimative::Bytes(16).into(), 37).into()
+                ),
+                LinkedKeyVal::optional(
+                    "mode",
+                    LinkedTag::new(LinkedNode::ForeignEnumeration("mode".into()), 1000).into()
+                ),
+                LinkedKeyVal::new("note", LinkedNode::Literal(Literal::Str("#6.3(u8)".into()))),
+            ]
+        })
+    );
+}
+
+#[test]
+fn expect_tag_errors() {
+    for cddl in [
+        "a = #6.1([ 2*2 bool ])",
+        "a = #6.1(1..4)",
+        "a = #6.1(float16)",
+        "a = #6.1(#6.2(bool))",
+        "a = [ 2*2 #6.1(bool) ]",
+    ] {
+        let err = parse(cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("a"));
+        assert!(matches!(
+            err.kind(),
+            FlattenError::InvalidTag | FlattenError::InvalidArray
+        ));
+    }
+    // Errors are located in the schema as written, not as it is read
+    let err = parse("a = { x: #6.1(bool) }\nb = #6.1(float16)").unwrap_err();
+    assert_eq!(err.location().map(|l| l.line), Some(2));
+    for cddl in ["a = bool .tag 1", "a = (bool) .tag \"b\""] {
+        let err = parse(cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("a"));
+        assert!(matches!(err.kind(), FlattenError::InvalidTagArg));
+        assert!(err.kind().to_string().contains("unsigned tag number"));
+    }
+}
+
+#[test]
+fn expect_default_values() {
+    const TEST_DATA: &str = r#"
+        u8 = uint .size 1
+        u16 = uint .size 2
+        http = u16 .default 80
+        site.default = u8
+        addressing = "static" / "dhcp"
+        settings = {
+            port: u16 .default 8080,
+            web: http,
+            name: tstr .size 16 .default "node",
+            ? retries: u8 .default 3,
+            "mode" => u8 .default 1, ; .default 2
+            level: -40..125 .default -10,
+            on: bool .default true,
+            epoch: #6.1(u16) .default 7,
+            zero: u8,
+            site: site.default,
+            ip: addressing .default "dhcp",
+            host: tstr .size 16 .regexp "a:b/c,d" .default "a:b/c,d",
+        }
+        "#;
+    let nodes = parse(TEST_DATA).unwrap();
+    assert_eq!(
+        nodes["http"],
+        LinkedNode::Primative(ConstrainedPrimative::U16)
+    );
+    let defaults = match &nodes["settings"] {
+        LinkedNode::Struct(Fields { members }) => members
+            .iter()
+            .map(|kv| (kv.key(), kv.default_value().cloned()))
+            .collect::<Vec<(&str, Option<Literal>)>>(),
+        node => panic!("expected struct, found {:?}", node),
+    };
+    assert_eq!(
+        defaults,
+        vec![
+            ("port", Some(Literal::UInt(8080))),
+            ("web", Some(Literal::UInt(80))),
+            ("name", Some(Literal::Str("node".into()))),
+            ("retries", Some(Literal::UInt(3))),
+            ("mode", Some(Literal::UInt(1))),
+            ("level", Some(Literal::Int(-10))),
+            ("on", Some(Literal::Bool(true))),
+            ("epoch", Some(Literal::UInt(7))),
+            ("zero", None),
+            ("site", None),
+            ("ip", Some(Literal::Str("dhcp".into()))),
+            ("host", Some(Literal::Str("a:b/c,d".into()))),
+        ]
+    );
+}
+
+#[test]
+fn expect_default_value_errors() {
+    for cddl in [
+        "u8 = uint .size 1\na = { b: u8 .default 256 }",
+        "a = { b: tstr .size 2 .default \"node\" }",
+        "a = { b: bool .default 1 }",
+        "a = { b: 1..4 .default 5 }",
+        "c = \"on\" / \"off\"\na = { b: c .default \"auto\" }",
+    ] {
+        let err = parse(cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("a"));
+        assert!(matches!(err.kind(), FlattenError::InvalidDefault(key) if key == "b"));
+    }
+    let err = parse("a = { b: bool .default { c: bool } }").map_err(FlattenError::into_kind);
+    assert!(matches!(err, Err(FlattenError::InvalidDefaultArg)));
+    assert!(err.unwrap_err().to_string().contains("literal value"));
+}
+
+#[test]
+fn expect_comparisons() {
+    const TEST_DATA: &str = r#"
+        u8 = uint .size 1
+        temp = int .size 2 .ge -40
+        limits = {
+            percent: u8 .le 100,
+            cold: temp .lt 0, ; .gt 0
+            level: uint .size 2 .gt 0 .ne 7,
+            ratio: float32 .ge 0 .default 1,
+            samples: [*4 u8 .lt 10],
+            epoch: #6.1(uint .size 8 .ne 0),
+        }
+        "#;
+    let nodes = parse(TEST_DATA).unwrap();
+    let constrained = |ty, constraints: Vec<(Comparison, Literal)>| {
+        let constraints = constraints
+            .into_iter()
+            .map(|(op, value)| Constraint::new(op, value))
+            .collect();
+        LinkedNode::Constrained(Constrained::new(ty, constraints))
+    };
+    assert_eq!(
+        nodes["temp"],
+        constrained(
+            ConstrainedPrimative::I16,
+            vec![(Comparison::Ge, Literal::Int(-40))]
+        )
+    );
+    let initial = |ty, constraints: Vec<(Comparison, Literal)>| {
+        let constraints = constraints
+            .into_iter()
+            .map(|(op, value)| Constraint::new(op, value))
+            .collect();
+        Constrained::new(ty, constraints).initial()
+    };
+    assert_eq!(initial(ConstrainedPrimative::U8, vec![]), Some(0));
+    assert_eq!(
+        initial(ConstrainedPrimative::U8, vec![(Comparison::Ne, 0u8.into())]),
+        Some(1)
+    );
+    assert_eq!(
+        initial(
+            ConstrainedPrimative::I8,
+            vec![(Comparison::Lt, Literal::Int(-3))]
+        ),
+        Some(-4)
+    );
+    assert_eq!(
+        initial(
+            ConstrainedPrimative::U16,
+            vec![(Comparison::Gt, 10u8.into()), (Comparison::Ne, 11u8.into())]
+        ),
+        Some(12)
+    );
+    assert_eq!(
+        nodes["limits"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new(
+                    "percent",
+                    constrained(
+                        ConstrainedPrimative::U8,
+                        vec![(Comparison::Le, 100u8.into())]
+                    )
+                ),
+                LinkedKeyVal::new(
+                    "cold",
+                    constrained(
+                        ConstrainedPrimative::I16,
+                        vec![
+                            (Comparison::Ge, Literal::Int(-40)),
+                            (Comparison::Lt, 0u8.into())
+                        ]
+                    )
+                ),
+                LinkedKeyVal::new(
+                    "level",
+                    constrained(
+                        ConstrainedPrimative::U16,
+                        vec![(Comparison::Gt, 0u8.into()), (Comparison::Ne, 7u8.into())]
+                    )
+                ),
+                LinkedKeyVal::new(
+                    "ratio",
+                    constrained(
+                        ConstrainedPrimative::F32,
+                        vec![(Comparison::Ge, 0u8.into())]
+                    )
+                )
+                .with_default(1u8.into()),
+                LinkedKeyVal::new(
+                    "samples",
+                    LinkedArray::bounded(
+                        constrained(
+                            ConstrainedPrimative::U8,
+                            vec![(Comparison::Lt, 10u8.into())]
+                        ),
+                        0,
+                        4
+                    )
+                    .into()
+                ),
+                LinkedKeyVal::new(
+                    "epoch",
+                    LinkedTag::new(
+                        constrained(
+                            ConstrainedPrimative::U64,
+                            vec![(Comparison::Ne, 0u8.into())]
+                        ),
+                        1
+                    )
+                    .into()
+                ),
+            ]
+        })
+    );
+}
+
+#[test]
+fn expect_comparison_errors() {
+    for cddl in [
+        "a = { b: tstr .le 4 }",
+        "a = { b: uint .size 1 .le 256 }",
+        "a = { b: uint .size 1 .ge -1 }",
+        "a = { b: 1..4 .ne 2 }",
+        "c = { d: bool }\na = { b: c .lt 1 }",
+        "a = { b: uint .size 1 .gt 3 .lt 4 }",
+    ] {
+        let err = parse(cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("a"));
+        assert!(matches!(err.kind(), FlattenError::InvalidComparison));
+    }
+    assert!(matches!(
+        parse("a = { b: uint .size 1 .le \"c\" }").map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidControlArg)
+    ));
+    assert!(matches!(
+        parse("a = { b: uint .size 1 .le 4 .default 5 }").map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidDefault(key)) if key == "b"
+    ));
+}
+
+#[test]
+fn expect_regexp_and_bits() {
+    const TEST_DATA: &str = r#"
+        hostname = tstr .size 32 .regexp "[a-z0-9.-]+"
+        status-bits = &( ready: 0, fault: 1 )
+        mode-bits = ( manual: 3 )
+        device = {
+            host: hostname,
+            ? serial: tstr .regexp "\\d{4}-\\d{4}",
+            status: uint .size 1 .bits status-bits,
+            mode: uint .size 2 .bits &mode-bits .default 8,
+            alarms: [2*2 uint .size 1 .bits &( low: 0, high: 7 )],
+        }
+        "#;
+    let nodes = parse(TEST_DATA).unwrap();
+    let hostname = LinkedNode::Regexp(LinkedRegexp::new(
+        ConstrainedPrimative::Str(32),
+        "[a-z0-9.-]+",
+    ));
+    assert_eq!(nodes["hostname"], hostname);
+    assert_eq!(
+        nodes["status-bits"],
+        LinkedNode::Fields(Fields {
+            members: vec![
+                LinkedKeyVal::new("ready", LinkedNode::Literal(0u8.into())),
+                LinkedKeyVal::new("fault", LinkedNode::Literal(1u8.into())),
+            ]
+        })
+    );
+    let bits = |ty, bits: Vec<(&str, u32)>| {
+        let bits = bits
+            .into_iter()
+            .map(|(name, position)| Bit::new(name, position))
+            .collect();
+        LinkedNode::Bits(LinkedBits::new(ty, bits))
+    };
+    assert_eq!(
+        nodes["device"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("host", hostname),
+                LinkedKeyVal::optional(
+                    "serial",
+                    LinkedNode::Regexp(LinkedRegexp::new(
+                        ConstrainedPrimative::UnboundedStr,
+                        "\\d{4}-\\d{4}"
+                    ))
+                ),
+                LinkedKeyVal::new(
+                    "status",
+                    bits(ConstrainedPrimative::U8, vec![("ready", 0), ("fault", 1)])
+                ),
+                LinkedKeyVal::new("mode", bits(ConstrainedPrimative::U16, vec![("manual", 3)]))
+                    .with_default(8u8.into()),
+                LinkedKeyVal::new(
+                    "alarms",
+                    LinkedNode::Array(LinkedArray::new(
+                        bits(ConstrainedPrimative::U8, vec![("low", 0), ("high", 7)]),
+                        2
+                    ))
+                ),
+            ]
+        })
+    );
+}
+
+#[test]
+fn expect_regexp_and_bits_errors() {
+    for cddl in [
+        "a = { b: uint .size 1 .regexp \"c\" }",
+        "a = { b: bstr .regexp \"c\" }",
+        "a = { b: tstr .regexp \"(c\" }",
+        "a = { b: tstr .regexp \"c{2,1}\" }",
+        "a = { b: tstr .regexp \"*c\" }",
+        "a = { b: tstr .regexp \"c{256}\" }",
+        "c = tstr .regexp \"d\"\na = { b: c .regexp \"e\" }",
+    ] {
+        let err = parse(cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("a"));
+        assert!(matches!(err.kind(), FlattenError::InvalidRegexp));
+    }
+    for cddl in [
+        "a = { b: int .size 1 .bits &( c: 0 ) }",
+        "a = { b: tstr .bits &( c: 0 ) }",
+        "a = { b: uint .size 1 .bits &( c: 8 ) }",
+        "a = { b: uint .size 1 .bits &( c: 1, d: 1 ) }",
+        "a = { b: uint .size 1 .bits &( c: \"d\" ) }",
+        "c = { d: uint .size 1 }\na = { b: uint .size 1 .bits c }",
+    ] {
+        let err = parse(cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("a"));
+        assert!(matches!(err.kind(), FlattenError::InvalidBits));
+    }
+    let err = parse("a = { b: tstr .regexp 4 }").map_err(FlattenError::into_kind);
+    assert!(matches!(err, Err(FlattenError::InvalidRegexpArg)));
+    assert!(err.unwrap_err().to_string().contains("text pattern"));
+    let err = parse("a = { b: uint .size 1 .bits 4 }").map_err(FlattenError::into_kind);
+    assert!(matches!(err, Err(FlattenError::InvalidBitsArg)));
+    assert!(err.unwrap_err().to_string().contains("named bits"));
+    assert!(matches!(
+        parse("c = &( d: 0 )\na = { b: [*2 tstr .regexp \"e\"], f: c }")
+            .map_err(FlattenError::into_kind),
+        Err(FlattenError::InvalidArray)
+    ));
+    for cddl in [
+        "a = { b: tstr .size 8 .regexp \"[a-z]+\" .default \"node1\" }",
+        "c = tstr .regexp \"[0-9]+\"\na = { b: c .default \"d\" }",
+    ] {
+        let err = parse(cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("a"));
+        assert!(matches!(err.kind(), FlattenError::InvalidDefault(key) if key == "b"));
+    }
+    assert!(parse("a = { b: tstr .size 8 .regexp \"[a-z]+\" .default \"node\" }").is_ok());
+}
+
+#[test]
+fn expect_nil_and_any() {
+    const TEST_DATA: &str = r#"
+        gateway = tstr .size 16 / null
+        ping = { seq: uint .size 1 }
+        pong = { seq: uint .size 1 }
+        reply = ping / pong / nil
+        vendor = {
+            payload: any .size 32,
+            ? extra: any,
+            gateway: gateway,
+            mask: uint .size 1 / nil,
+            reply: reply,
+        }
+        "#;
+    let nodes = flatten(TEST_DATA).unwrap();
+    assert_eq!(
+        nodes["gateway"],
+        Node::Nullable(Box::new(Node::Primative(ConstrainedPrimative::Str(16))))
+    );
+    assert_eq!(
+        nodes["reply"],
+        Node::Choice(Choice {
+            members: vec![
+                Node::Foreign("ping".into()),
+                Node::Foreign("pong".into()),
+                Node::Nil
+            ]
+        })
+    );
+    let linked = link(nodes).unwrap();
+    assert_eq!(
+        linked["reply"],
+        LinkedNode::Choice(Variants {
+            members: vec![
+                LinkedKeyVal::new("ping", LinkedNode::ForeignStruct("ping".into())),
+                LinkedKeyVal::new("pong", LinkedNode::ForeignStruct("pong".into())),
+                LinkedKeyVal::new("nil", LinkedNode::Nil),
+            ]
+        })
+    );
+    assert_eq!(
+        linked["vendor"],
+        LinkedNode::Struct(Fields {
+            members: vec![
+                LinkedKeyVal::new("payload", ConstrainedPrimative::Any(32).into()),
+                LinkedKeyVal::optional("extra", ConstrainedPrimative::UnboundedAny.into()),
+                LinkedKeyVal::optional("gateway", ConstrainedPrimative::Str(16).into()),
+                LinkedKeyVal::optional("mask", ConstrainedPrimative::U8.into()),
+                LinkedKeyVal::new("reply", LinkedNode::ForeignChoice("reply".into())),
+            ]
+        })
+    );
+}
+
+#[test]
+fn expect_nil_errors() {
+    let err = parse("a = { b: nil }").unwrap_err();
+    assert_eq!(err.rule(), Some("a"));
+    assert!(matches!(err.kind(), FlattenError::InvalidNil));
+    for cddl in [
+        "a = { b: [2*2 uint .size 1 / nil] }",
+        "a = { b: [2*2 nil] }",
+    ] {
+        let err = parse(cddl).unwrap_err();
+        assert_eq!(err.rule(), Some("a"));
+        assert!(matches!(err.kind(), FlattenError::InvalidArray));
+    }
+}
@@ -0,0 +1,411 @@
+#![no_std]
+//! The patterns of the .regexp control of CDDL. The parser rejects a pattern that can not be read
+//! when the schema is read, and the decoders of seedle_extra match text against the same pattern
+
+/// The most states a pattern may compile to. A pattern with more states matches nothing, and
+/// is rejected by the parser when the schema is read
+pub const MAX_STATES: usize = 256;
+
+/// True when the whole text matches a pattern. The patterns of CDDL are XSD regular expressions,
+/// which are anchored at both ends and support the subset read here: literals, ., classes such as
+/// [a-z0-9_] and [^,], the escapes \d \w \s and their negations, groups, alternatives with | and
+/// the quantifiers ? * + {n} {n,} {n,m}. A pattern that can not be read matches nothing.
+///
+/// The pattern is compiled to a program of at most MAX_STATES states, which is run over the text
+/// one character at a time with every state it could be in at once. The time taken grows with
+/// the length of the text, the memory used is fixed, and nothing is allocated
+pub fn is_match(pattern: &str, text: &str) -> bool {
+    Program::compile(pattern).is_some_and(|program| program.is_match(text))
+}
+
+/// True when a pattern can be read, and compiles to no more than MAX_STATES states. IE: [a-z]+
+/// is valid, (ab and a{300} are not
+pub fn is_valid(pattern: &str) -> bool {
+    Program::compile(pattern).is_some()
+}
+
+/// A state of a compiled pattern. Atoms are read from the pattern where they are written
+#[derive(Copy, Clone, PartialEq)]
+enum Inst {
+    /// Consume a character accepted by the atom at start..end of the pattern
+    Atom(u16, u16),
+    /// Continue from both states
+    Split(u16, u16),
+    Jump(u16),
+    Match,
+}
+
+struct Program<'p> {
+    pattern: &'p str,
+    insts: [Inst; MAX_STATES],
+    len: usize,
+}
+
+impl<'p> Program<'p> {
+    /// None when the pattern can not be read, or needs more than MAX_STATES states
+    fn compile(pattern: &'p str) -> Option<Program<'p>> {
+        if pattern.len() > u16::MAX as usize {
+            return None;
+        }
+        let mut program = Program {
+            pattern,
+            insts: [Inst::Match; MAX_STATES],
+            len: 0,
+        };
+        program.alternatives(0, pattern.len())?;
+        program.push(Inst::Match)?;
+        Some(program)
+    }
+
+    fn push(&mut self, inst: Inst) -> Option<u16> {
+        let at = self.len;
+        *self.insts.get_mut(at)? = inst;
+        self.len += 1;
+        Some(at as u16)
+    }
+
+    fn here(&self) -> u16 {
+        self.len as u16
+    }
+
+    /// Point the splits and jumps of a chain to a state. A chain is linked through the states
+    /// that are not yet known, and ends at a state that points to itself
+    fn patch(&mut self, mut chain: Option<u16>, to: u16) {
+        while let Some(at) = chain {
+            let (next, inst) = match self.insts[at as usize] {
+                Inst::Split(x, y) => (y, Inst::Split(x, to)),
+                Inst::Jump(x) => (x, Inst::Jump(to)),
+                inst => (at, inst),
+            };
+            self.insts[at as usize] = inst;
+            chain = (next != at).then_some(next);
+        }
+    }
+
+    /// One of the alternatives of start..end. IE: a|b
+    fn alternatives(&mut self, start: usize, end: usize) -> Option<()> {
+        let mut jumps = None;
+        let mut at = start;
+        loop {
+            let len = alternative_len(&self.pattern[at..end]);
+            if at + len == end {
+                self.sequence(at, end)?;
+                break;
+            }
+            let split = self.push(Inst::Split(self.here() + 1, self.here()))?;
+            self.sequence(at, at + len)?;
+            let jump = self.push(Inst::Jump(jumps.unwrap_or(self.here())))?;
+            jumps = Some(jump);
+            let next = self.here();
+            self.patch(Some(split), next);
+            at += len + 1;
+        }
+        let end = self.here();
+        self.patch(jumps, end);
+        Some(())
+    }
+
+    /// Each atom of start..end in turn, as many times as its quantifier allows
+    fn sequence(&mut self, start: usize, end: usize) -> Option<()> {
+        let mut at = start;
+        while at < end {
+            let pattern = &self.pattern[at..end];
+            if pattern.starts_with([')', '*', '+', '?', '{']) {
+                return None;
+            }
+            let len = atom_len(pattern)?;
+            let (min, max, rest) = quantifier(&pattern[len..])?;
+            self.repeat(at, at + len, min, max)?;
+            at = end - rest.len();
+        }
+        Some(())
+    }
+
+    /// An atom at least min and at most max times
+    fn repeat(&mut self, start: usize, end: usize, min: usize, max: usize) -> Option<()> {
+        for _ in 0..min {
+            self.single(start, end)?;
+        }
+        if max == usize::MAX {
+            let split = self.push(Inst::Split(self.here() + 1, self.here()))?;
+            self.single(start, end)?;
+            self.push(Inst::Jump(split))?;
+            let next = self.here();
+            self.patch(Some(split), next);
+            return Some(());
+        }
+        let mut splits = None;
+        for _ in min..max {
+            let split = self.push(Inst::Split(self.here() + 1, splits.unwrap_or(self.here())))?;
+            splits = Some(split);
+            self.single(start, end)?;
+        }
+        let next = self.here();
+        self.patch(splits, next);
+        Some(())
+    }
+
+    /// An atom once
+    fn single(&mut self, start: usize, end: usize) -> Option<()> {
+        match self.pattern[start..end].starts_with('(') {
+            true => self.alternatives(start + 1, end - 1),
+            false => self.push(Inst::Atom(start as u16, end as u16)).map(|_| ()),
+        }
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        let mut current = States::default();
+        let mut next = States::default();
+        current.add(&self.insts, 0);
+        for c in text.chars() {
+            next.clear();
+            for &at in current.list() {
+                if let Inst::Atom(start, end) = self.insts[at as usize] {
+                    if accepts(&self.pattern[start as usize..end as usize], c) {
+                        next.add(&self.insts, at + 1);
+                    }
+                }
+            }
+            core::mem::swap(&mut current, &mut next);
+            if current.len == 0 {
+                return false;
+            }
+        }
+        current
+            .list()
+            .iter()
+            .any(|at| self.insts[*at as usize] == Inst::Match)
+    }
+}
+
+/// The states a match could be in after a character, each at most once
+struct States {
+    list: [u16; MAX_STATES],
+    len: usize,
+    seen: [bool; MAX_STATES],
+}
+
+impl Default for States {
+    fn default() -> States {
+        States {
+            list: [0; MAX_STATES],
+            len: 0,
+            seen: [false; MAX_STATES],
+        }
+    }
+}
+
+impl States {
+    fn list(&self) -> &[u16] {
+        &self.list[..self.len]
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+        self.seen = [false; MAX_STATES];
+    }
+
+    /// Add a state and every state it leads to without consuming a character. A state is only
+    /// visited once, so that a repeated group that matches nothing can not repeat forever
+    fn add(&mut self, insts: &[Inst], at: u16) {
+        let mut stack = [0u16; MAX_STATES];
+        let mut depth = 0;
+        if !self.seen[at as usize] {
+            self.seen[at as usize] = true;
+            stack[0] = at;
+            depth = 1;
+        }
+        while depth > 0 {
+            depth -= 1;
+            let at = stack[depth];
+            let next = match insts[at as usize] {
+                Inst::Jump(x) => [Some(x), None],
+                Inst::Split(x, y) => [Some(y), Some(x)],
+                Inst::Atom(..) | Inst::Match => {
+                    self.list[self.len] = at;
+                    self.len += 1;
+                    [None, None]
+                }
+            };
+            for x in next.into_iter().flatten() {
+                if !self.seen[x as usize] {
+                    self.seen[x as usize] = true;
+                    stack[depth] = x;
+                    depth += 1;
+                }
+            }
+        }
+    }
+}
+
+/// The length of the first alternative of a pattern, which ends at a | outside of any group
+fn alternative_len(pattern: &str) -> usize {
+    let mut at = 0;
+    while at < pattern.len() && !pattern[at..].starts_with('|') {
+        at += atom_len(&pattern[at..]).unwrap_or(pattern.len() - at);
+    }
+    at
+}
+
+/// True when a character is accepted by an atom that is not a group
+fn accepts(atom: &str, c: char) -> bool {
+    let mut chars = atom.chars();
+    match chars.next() {
+        Some('.') => c != '\n' && c != '\r',
+        Some('[') => class(&atom[1..atom.len() - 1], c),
+        Some('\\') => escape(chars.next(), c),
+        Some(a) => a == c,
+        None => false,
+    }
+}
+
+/// True when a character is one of a class. IE: a-z0-9_ or ^,
+fn class(class: &str, c: char) -> bool {
+    let (negated, class) = match class.strip_prefix('^') {
+        Some(class) => (true, class),
+        None => (false, class),
+    };
+    let mut found = false;
+    let mut chars = class.chars();
+    while let Some(first) = chars.next() {
+        if first == '\\' {
+            found |= escape(chars.next(), c);
+            continue;
+        }
+        let mut range = chars.clone();
+        match (range.next(), range.next()) {
+            (Some('-'), Some(last)) => {
+                found |= first <= c && c <= last;
+                chars = range;
+            }
+            _ => found |= first == c,
+        }
+    }
+    found != negated
+}
+
+/// True when a character is matched by an escape. IE: \d or \.
+fn escape(escaped: Option<char>, c: char) -> bool {
+    let is_space = |c: char| matches!(c, ' ' | '\t' | '\n' | '\r');
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    match escaped {
+        Some('d') => c.is_ascii_digit(),
+        Some('D') => !c.is_ascii_digit(),
+        Some('w') => is_word(c),
+        Some('W') => !is_word(c),
+        Some('s') => is_space(c),
+        Some('S') => !is_space(c),
+        Some('n') => c == '\n',
+        Some('r') => c == '\r',
+        Some('t') => c == '\t',
+        Some(escaped) => escaped == c,
+        None => false,
+    }
+}
+
+/// The length of the atom a pattern starts with, or None when the atom does not end
+fn atom_len(pattern: &str) -> Option<usize> {
+    let mut chars = pattern.char_indices();
+    match chars.next()?.1 {
+        '\\' => chars.next().map(|(at, c)| at + c.len_utf8()),
+        '[' => {
+            let mut escaped = false;
+            for (at, c) in chars {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    ']' => return Some(at + 1),
+                    _ => {}
+                }
+            }
+            None
+        }
+        '(' => {
+            let mut at = 1;
+            while at < pattern.len() {
+                match pattern[at..].starts_with(')') {
+                    true => return Some(at + 1),
+                    false => at += atom_len(&pattern[at..])?,
+                }
+            }
+            None
+        }
+        c => Some(c.len_utf8()),
+    }
+}
+
+/// The least and most times the atom before a quantifier is matched, and the rest of the pattern
+fn quantifier(pattern: &str) -> Option<(usize, usize, &str)> {
+    let mut chars = pattern.chars();
+    match chars.next() {
+        Some('?') => Some((0, 1, chars.as_str())),
+        Some('*') => Some((0, usize::MAX, chars.as_str())),
+        Some('+') => Some((1, usize::MAX, chars.as_str())),
+        Some('{') => {
+            let (bounds, rest) = chars.as_str().split_once('}')?;
+            let (min, max) = match bounds.split_once(',') {
+                Some((min, "")) => (min.parse().ok()?, usize::MAX),
+                Some((min, max)) => (min.parse().ok()?, max.parse().ok()?),
+                None => (bounds.parse().ok()?, bounds.parse().ok()?),
+            };
+            match min <= max {
+                true => Some((min, max, rest)),
+                false => None,
+            }
+        }
+        _ => Some((1, 1, pattern)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_match() {
+        let pattern = "[a-z0-9.-]+";
+        assert!(is_match(pattern, "node-1.local"));
+        assert!(!is_match(pattern, "Node"));
+        assert!(!is_match(pattern, ""));
+        let pattern = "\\d{4}-\\d{2,}(-[A-F]?)*";
+        assert!(is_match(pattern, "2024-123"));
+        assert!(is_match(pattern, "2024-12-A-"));
+        assert!(!is_match(pattern, "2024-1"));
+        assert!(!is_match(pattern, "202-12"));
+        let pattern = "(on|off|auto)\\s?[^,]";
+        assert!(is_match(pattern, "on x"));
+        assert!(is_match(pattern, "autoy"));
+        assert!(!is_match(pattern, "off,"));
+        assert!(!is_match(pattern, "of x"));
+        assert!(is_match("(a*)*b", "aab"));
+        assert!(is_match("a.c", "abc"));
+        assert!(!is_match("a\\.c", "abc"));
+        assert!(!is_match("a{2,1}", "aa"));
+        assert!(!is_match("(ab", "ab"));
+        assert!(is_match("x(a|b|)y", "xy"));
+        assert!(is_match("a{2,3}|b", "aaa"));
+        assert!(!is_match("a{2,3}|b", "aaaa"));
+        assert!(!is_match("a{300}", &"a".repeat(300)));
+    }
+
+    #[test]
+    fn test_is_match_long_text() {
+        // Neither the time nor the stack taken grows with the ways a text could be matched
+        let text = [b'a'; 100_000];
+        let text = core::str::from_utf8(&text).unwrap();
+        assert!(is_match("a*", text));
+        assert!(is_match("(a|aa)*", text));
+        assert!(!is_match("(a*)*b", text));
+        assert!(!is_match("(a|a)*b", &text[..64]));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(is_valid("[a-z]+"));
+        assert!(is_valid("a{255}"));
+        assert!(!is_valid("a{256}"));
+        assert!(!is_valid("(ab"));
+        assert!(!is_valid("a{2,1}"));
+        assert!(!is_valid("*a"));
+    }
+}